@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract Token {
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    function transfer(address to, uint256 amount) public returns (bool) {
+	balanceOf[msg.sender] -= amount;
+	balanceOf[to] += amount;
+	return true;
+    }
+
+    function approve(address spender, uint256 amount) external returns (bool) {
+	allowance[msg.sender][spender] = amount;
+	return true;
+    }
+
+    function forward(address target, address to, uint256 amount) public returns (bool) {
+	(bool ok, ) = target.call(abi.encodeWithSelector(this.transfer.selector, to, amount));
+	return ok;
+    }
+}
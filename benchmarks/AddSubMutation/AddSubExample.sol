@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract AddSubExample {
+    mapping(address => uint256) balances;
+
+    function transfer(address to, uint256 amount) public {
+	balances[msg.sender] -= amount;
+	balances[to] += amount;
+    }
+
+    function total(uint256 a, uint256 b) public pure returns (uint256) {
+	return a + b;
+    }
+}
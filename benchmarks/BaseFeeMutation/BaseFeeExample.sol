@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.7;
+
+contract BaseFeeExample {
+    uint256 constant GAS_PER_CALL = 50000;
+
+    function priorityFee() public view returns (uint256) {
+	return tx.gasprice - block.basefee;
+    }
+
+    function callPrice() public view returns (uint256) {
+	return block.basefee * GAS_PER_CALL;
+    }
+}
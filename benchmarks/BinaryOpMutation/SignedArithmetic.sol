@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.0;
+
+contract SignedArithmetic {
+    function delta(int256 a, int256 b) public pure returns (int256) {
+	return a - b;
+    }
+
+    function scale(int256 a, int256 factor) public pure returns (int256) {
+	return a * factor / 100;
+    }
+
+    function square(int256 a) public pure returns (int256) {
+	return a ** 2;
+    }
+}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract ConstantFoldExample {
+    function myTwo() public pure returns (uint256) {
+	return 1 + 1;
+    }
+
+    function myByte() public pure returns (uint256) {
+	return 2 ** 8;
+    }
+
+    function myDeposit() public pure returns (uint256) {
+	return 1 ether + 1;
+    }
+}
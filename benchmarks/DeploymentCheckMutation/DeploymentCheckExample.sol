@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract Pair {
+    address public token0;
+    address public token1;
+
+    constructor(address _token0, address _token1) {
+        token0 = _token0;
+        token1 = _token1;
+    }
+}
+
+contract DeploymentCheckExample {
+    Pair public pair;
+    mapping(bytes32 => Pair) public pairs;
+
+    function createPair(address a, address b) public returns (Pair) {
+        pair = new Pair(a, b);
+        require(address(pair) != address(0), "deployment failed");
+        return pair;
+    }
+
+    function createPair2(address a, address b) public returns (Pair) {
+        bytes32 salt = keccak256(abi.encodePacked(a, b));
+        pairs[salt] = new Pair{salt: salt}(a, b);
+        require(address(0) != address(pairs[salt]));
+        return pairs[salt];
+    }
+}
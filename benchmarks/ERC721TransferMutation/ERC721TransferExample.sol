@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+interface IERC721 {
+    function safeTransferFrom(address from, address to, uint256 tokenId) external;
+    function transferFrom(address from, address to, uint256 tokenId) external;
+}
+
+contract ERC721TransferExample {
+    IERC721 nft;
+    address vault;
+
+    constructor(IERC721 _nft, address _vault) {
+	nft = _nft;
+	vault = _vault;
+    }
+
+    function deposit(uint256 tokenId) public {
+	nft.safeTransferFrom(msg.sender, vault, tokenId);
+    }
+
+    function withdraw(uint256 tokenId) public {
+	nft.transferFrom(vault, msg.sender, tokenId);
+    }
+}
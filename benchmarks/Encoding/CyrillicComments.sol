@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract CyrillicComments {
+    string public greeting = "Привет, мир";
+
+    // Складываем два числа.
+    function add(uint256 a, uint256 b) public pure returns (uint256) {
+	return a + b; // сумма
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract EthSendMethodExample {
+    mapping(address => uint256) balances;
+
+    function withdraw() public {
+	uint256 amount = balances[msg.sender];
+	balances[msg.sender] = 0;
+	payable(msg.sender).transfer(amount);
+    }
+
+    function tryWithdraw() public returns (bool) {
+	uint256 amount = balances[msg.sender];
+	balances[msg.sender] = 0;
+	return payable(msg.sender).send(amount);
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract EventIndexingExample {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event Approval(address indexed owner, address indexed spender, uint256 value);
+
+    mapping(address => uint256) public balances;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    function transfer(address to, uint256 value) public {
+	balances[msg.sender] -= value;
+	balances[to] += value;
+	emit Transfer(msg.sender, to, value);
+    }
+
+    function approve(address spender, uint256 value) public {
+	allowance[msg.sender][spender] = value;
+	emit Approval(msg.sender, spender, value);
+    }
+}
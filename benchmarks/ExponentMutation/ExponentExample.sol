@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract ExponentExample {
+    function square(uint256 x) public pure returns (uint256) {
+	return x ** 2;
+    }
+
+    function double(uint256 x) public pure returns (uint256) {
+	return x * 2;
+    }
+
+    function scale(uint256 x, uint256 decimals) public pure returns (uint256) {
+	return x * 10 ** decimals;
+    }
+}
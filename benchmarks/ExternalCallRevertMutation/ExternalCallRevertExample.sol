@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+interface IOracle {
+    function update(uint256 price) external;
+    function price() external view returns (uint256);
+}
+
+contract ExternalCallRevert {
+    IOracle oracle;
+    uint256 lastPrice;
+
+    constructor(IOracle _oracle) {
+	oracle = _oracle;
+    }
+
+    function refresh(uint256 price) external {
+	oracle.update(price);
+	lastPrice = oracle.price();
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+interface IERC20 {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+contract FlashLoanExample {
+    IERC20 public token;
+
+    function uniswapV2Call(address sender, uint256 amount0, uint256 amount1, bytes calldata data) external {
+	require(msg.sender == address(this));
+	token.transfer(sender, amount0 + amount1);
+    }
+
+    function onFlashLoan(address initiator, address, uint256 amount, uint256 fee, bytes calldata) external returns (bytes32) {
+	require(address(this) == msg.sender, "untrusted lender");
+	token.transfer(initiator, amount + fee);
+	return keccak256("ERC3156FlashBorrower.onFlashLoan");
+    }
+}
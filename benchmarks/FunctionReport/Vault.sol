@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract Vault {
+    uint256 public total;
+    uint256 public limit;
+
+    function version() public pure returns (uint256) {
+	return 1;
+    }
+
+    function deposit(uint256 amount) public {
+	require(amount > 0);
+	total = total + amount;
+    }
+
+    function withdraw(uint256 amount) public {
+	require(amount <= total);
+	if (amount > limit) {
+	    amount = limit;
+	}
+	for (uint256 i = 0; i < amount; i++) {
+	    total = total - 1;
+	}
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract FunctionStubExample {
+    uint256 total;
+    mapping(address => uint256) balances;
+
+    function deposit(uint256 amount) public {
+	balances[msg.sender] += amount;
+	total += amount;
+    }
+
+    function balanceAndShare(address owner) public view returns (uint256, bool) {
+	uint256 balance = balances[owner];
+	return (balance, balance * 2 > total);
+    }
+
+    function share(address owner) public view returns (uint256 s) {
+	s = balances[owner] * 100;
+	s = s / total;
+    }
+}
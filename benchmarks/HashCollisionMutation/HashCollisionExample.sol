@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract HashCollisionExample {
+    mapping(bytes32 => bool) registered;
+
+    function register(string memory name, string memory symbol) public {
+	bytes32 id = keccak256(abi.encodePacked(name, symbol));
+	require(!registered[id]);
+	registered[id] = true;
+    }
+
+    function digest(bytes calldata sig, bytes calldata data) public pure returns (bytes32) {
+	return keccak256(abi.encodePacked(sig, data));
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+interface IERC20 {
+    function transfer(address to, uint256 amount) external returns (bool);
+}
+
+interface IPair {
+    function getReserves() external view returns (uint112, uint112, uint32);
+}
+
+contract IgnoreReturn {
+    function pay(IERC20 token, address to, uint256 amount) external {
+	bool ok = token.transfer(to, amount);
+	require(ok, "transfer failed");
+    }
+
+    function sync(IPair pair) external view {
+	(uint112 reserve0, uint112 reserve1, ) = pair.getReserves();
+    }
+}
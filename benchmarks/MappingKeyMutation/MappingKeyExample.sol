@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract MappingKeyExample {
+    mapping(address => mapping(address => uint256)) public allowances;
+
+    function approve(address spender, uint256 amount) public {
+	allowances[msg.sender][spender] = amount;
+    }
+
+    function allowance(address owner, address spender) public view returns (uint256) {
+	return allowances[owner][spender];
+    }
+}
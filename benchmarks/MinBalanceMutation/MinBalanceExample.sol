@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract MinBalanceExample {
+    uint256 public constant MINIMUM_LIQUIDITY = 10**3;
+    uint256 public minDeposit = 1 ether;
+    mapping(address => uint256) public balances;
+    uint256 public totalSupply;
+
+    function mint(uint256 liquidity) public {
+	require(liquidity > MINIMUM_LIQUIDITY, "insufficient liquidity");
+	totalSupply += liquidity;
+    }
+
+    function deposit() public payable {
+	require(msg.value >= minDeposit);
+	balances[msg.sender] += msg.value;
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract A {
+    function foo() public pure virtual returns (uint256) {
+	return 1;
+    }
+}
+
+contract B is A {
+    function foo() public pure virtual override returns (uint256) {
+	return 2;
+    }
+}
+
+contract C is A {
+    function foo() public pure virtual override returns (uint256) {
+	return 3;
+    }
+}
+
+contract D is B, C {
+    function foo() public pure override(B, C) returns (uint256) {
+	return super.foo();
+    }
+}
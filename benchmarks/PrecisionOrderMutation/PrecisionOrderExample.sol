@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract PrecisionOrderExample {
+    uint256 constant BPS = 10000;
+
+    function fee(uint256 amount, uint256 rate) public pure returns (uint256) {
+	return (amount / BPS) * rate;
+    }
+
+    function interest(uint256 principal, uint256 rate, uint256 time) public pure returns (uint256) {
+	return principal * (rate / 365 days) * time;
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract PureToViewExample {
+    uint256 public rate = 3;
+
+    function fee(uint256 amount) public pure returns (uint256) {
+	return amount / 100;
+    }
+
+    function scaled(uint256 amount) public view returns (uint256) {
+	return amount * rate;
+    }
+}
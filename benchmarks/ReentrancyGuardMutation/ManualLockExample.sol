@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract ManualLockVault {
+    mapping(address => uint256) public balances;
+    bool private locked;
+
+    function deposit() external payable {
+	balances[msg.sender] += msg.value;
+    }
+
+    function withdraw(uint256 amount) external {
+	require(!locked, "locked");
+	locked = true;
+	require(balances[msg.sender] >= amount);
+	(bool ok, ) = msg.sender.call{value: amount}("");
+	require(ok);
+	balances[msg.sender] -= amount;
+	locked = false;
+    }
+}
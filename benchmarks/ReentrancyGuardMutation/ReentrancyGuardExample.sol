@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+abstract contract ReentrancyGuard {
+    uint256 private constant NOT_ENTERED = 1;
+    uint256 private constant ENTERED = 2;
+    uint256 private status = NOT_ENTERED;
+
+    modifier nonReentrant() {
+	require(status != ENTERED, "ReentrancyGuard: reentrant call");
+	status = ENTERED;
+	_;
+	status = NOT_ENTERED;
+    }
+}
+
+contract Vault is ReentrancyGuard {
+    mapping(address => uint256) public balances;
+
+    function deposit() external payable {
+	balances[msg.sender] += msg.value;
+    }
+
+    function withdraw(uint256 amount) external nonReentrant {
+	require(balances[msg.sender] >= amount);
+	(bool ok, ) = msg.sender.call{value: amount}("");
+	require(ok);
+	balances[msg.sender] -= amount;
+    }
+}
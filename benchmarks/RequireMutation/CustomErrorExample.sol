@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract CustomErrorExample {
+    error ZeroAmount();
+    error Paused();
+    error TooLarge(uint256 amount);
+    error Overdrawn(uint256 balance);
+
+    bool paused;
+    uint256 limit;
+    mapping(address => uint256) balances;
+
+    function withdraw(uint256 amount) public {
+	if (paused) revert Paused();
+	if (amount == 0) {
+	    revert ZeroAmount();
+	}
+	if (amount > limit) revert TooLarge(amount);
+	if (balances[msg.sender] < amount) revert Overdrawn(balances[msg.sender]);
+	balances[msg.sender] -= amount;
+    }
+
+    function onFlashLoan(address initiator) external view returns (bytes32) {
+	if (msg.sender != address(this)) revert Paused();
+	return keccak256(abi.encode(initiator));
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract RevertMessageExample {
+    address owner;
+    mapping(address => uint256) balances;
+
+    constructor() {
+	owner = msg.sender;
+    }
+
+    function withdraw(uint256 amount) public {
+	require(amount > 0, "zero amount");
+	if (balances[msg.sender] < amount) {
+	    revert("insufficient balance");
+	}
+	balances[msg.sender] -= amount;
+    }
+
+    function close() public {
+	require(msg.sender == owner, "not owner");
+	selfdestruct(payable(owner));
+    }
+}
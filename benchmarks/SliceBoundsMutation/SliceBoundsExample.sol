@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >=0.8.5;
+
+contract SliceBoundsExample {
+    function selector(bytes calldata data) external pure returns (bytes4) {
+	return bytes4(data[:4]);
+    }
+
+    function recipient(bytes calldata data) external pure returns (address) {
+	return abi.decode(data[4:36], (address));
+    }
+
+    function payload(bytes calldata data, uint256 offset) external pure returns (bytes memory) {
+	return data[offset + 4:];
+    }
+}
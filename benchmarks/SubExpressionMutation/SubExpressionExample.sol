@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+contract SubExpressionExample {
+    function myFee(uint256 amount, uint256 rate, uint256 base) public pure returns (uint256) {
+	return (amount * rate) / base;
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity ^0.8.13;
+
+contract TupleComponentSwap {
+    uint112 reserve0;
+    uint112 reserve1;
+    uint32 blockTimestampLast;
+
+    function _update(uint256 balance0, uint256 balance1) private {
+	(reserve0, reserve1, blockTimestampLast) = (uint112(balance0), uint112(balance1), uint32(block.timestamp));
+    }
+
+    function getReserves() public view returns (uint112, uint112, uint32) {
+	return (reserve0, reserve1, blockTimestampLast);
+    }
+
+    function price() external view returns (uint256) {
+	(uint112 _reserve0, uint112 _reserve1, ) = getReserves();
+	return uint256(_reserve1) * 1e18 / _reserve0;
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: GPL-3.0-only
+pragma solidity >0.7.0;
+pragma experimental ABIEncoderV2;
+
+interface IVotes {
+    function getVotes(address account) external view returns (uint256);
+    function getPastVotes(address account, uint256 blockNumber) external view returns (uint256);
+}
+
+contract VoteWeightExample {
+    IVotes token;
+    uint256 threshold;
+    mapping(uint256 => uint256) forVotes;
+    mapping(uint256 => uint256) snapshots;
+
+    constructor(IVotes _token, uint256 _threshold) {
+	token = _token;
+	threshold = _threshold;
+    }
+
+    function propose(uint256 id) public {
+	require(token.getVotes(msg.sender) >= threshold);
+	snapshots[id] = block.number - 1;
+    }
+
+    function castVote(uint256 id) public {
+	uint256 weight = token.getPastVotes(msg.sender, snapshots[id]);
+	forVotes[id] += weight;
+    }
+}
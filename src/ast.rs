@@ -2,6 +2,9 @@ use itertools::Itertools;
 use regex::Regex;
 use serde::Deserialize;
 use serde_json::Value;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::SolcVersion;
 
 /// This is a thin wrapper around the json AST
 /// generated by the solidity compiler.
@@ -31,16 +34,226 @@ struct Replacement {
     new: String,
 }
 
+/// Checks whether nodes of type `node_type` are statements.
+fn is_statement(node_type: &str) -> bool {
+    node_type.ends_with("Statement")
+        || matches!(
+            node_type,
+            "Return" | "Break" | "Continue" | "Throw" | "InlineAssembly"
+        )
+}
+
+/// The name and number of parameters of the custom errors defined among
+/// `nodes`.
+fn errors(nodes: &[Value]) -> Vec<(String, usize)> {
+    nodes
+        .iter()
+        .filter(|n| n["nodeType"] == "ErrorDefinition")
+        .filter_map(|n| {
+            Some((
+                n["name"].as_str()?.to_string(),
+                n["parameters"]["parameters"].as_array()?.len(),
+            ))
+        })
+        .collect()
+}
+
+/// What mutations need to know about the contract that a node belongs to.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ContractInfo {
+    /// The name and selector of every function of the contract that has
+    /// one, i.e., its public and external functions.
+    pub selectors: Vec<(String, String)>,
+    /// The name and number of parameters of every custom error defined
+    /// in the contract.
+    pub errors: Vec<(String, usize)>,
+    /// The ids of the declarations of the state variables and constants
+    /// of every contract in the file, and of the constants outside of them.
+    pub state_variables: Vec<i64>,
+}
+
+impl ContractInfo {
+    /// Collects what is defined in the source unit `unit` outside of its
+    /// contracts, for the code outside of them and as the start of the
+    /// information about each contract.
+    fn file(unit: &Value) -> Self {
+        let nodes = unit["nodes"].as_array().cloned().unwrap_or_default();
+        fn state_variables(v: &Value, ids: &mut Vec<i64>) {
+            match v {
+                Value::Object(obj) => {
+                    let is_state = obj.get("stateVariable") == Some(&Value::Bool(true))
+                        || obj.get("constant") == Some(&Value::Bool(true));
+                    if obj.get("nodeType").and_then(|t| t.as_str()) == Some("VariableDeclaration")
+                        && is_state
+                    {
+                        ids.extend(obj.get("id").and_then(|id| id.as_i64()));
+                    }
+                    obj.values().for_each(|v| state_variables(v, ids));
+                }
+                Value::Array(arr) => arr.iter().for_each(|v| state_variables(v, ids)),
+                _ => {}
+            }
+        }
+        let mut ids = vec![];
+        state_variables(unit, &mut ids);
+        ContractInfo {
+            selectors: vec![],
+            errors: errors(&nodes),
+            state_variables: ids,
+        }
+    }
+
+    /// Collects the information about the contract definition `contract`
+    /// in a file of which `file` is known.
+    fn new(contract: &Value, file: Option<&ContractInfo>) -> Self {
+        let nodes = contract["nodes"].as_array().cloned().unwrap_or_default();
+        let of_type = |t: &'static str| nodes.iter().filter(move |n| n["nodeType"] == t);
+        let file = file.map_or_else(ContractInfo::default, |f| ContractInfo {
+            selectors: vec![],
+            errors: f.errors.clone(),
+            state_variables: f.state_variables.clone(),
+        });
+        ContractInfo {
+            selectors: of_type("FunctionDefinition")
+                .filter_map(|n| {
+                    Some((
+                        n["name"].as_str()?.to_string(),
+                        n["functionSelector"].as_str()?.to_string(),
+                    ))
+                })
+                .collect(),
+            errors: errors(&nodes).into_iter().chain(file.errors).collect(),
+            state_variables: file.state_variables,
+        }
+    }
+}
+
+/// Names the variable, or the member or element of one, that `node`
+/// refers to, e.g., `pairs[salt]` for `pairs[salt]`, so that its
+/// occurrences can be matched across the function.
+pub(crate) fn variable_key(node: &SolAST) -> Option<String> {
+    match node.node_type()?.as_str() {
+        "Identifier" => node.name(),
+        "MemberAccess" => Some(format!(
+            "{}.{}",
+            variable_key(&node.expression())?,
+            node.get_string("memberName")?
+        )),
+        "IndexAccess" => Some(format!(
+            "{}[{}]",
+            variable_key(&node.base_expression())?,
+            variable_key(&node.index_expression())?
+        )),
+        _ => None,
+    }
+}
+
+/// The variables that a function assigns a newly deployed contract to,
+/// i.e., the result of `new C(...)`, `new C{salt: s}(...)`, or `create`
+/// or `create2` in assembly, in the order they are traversed.
+/// Only the first `seen` of them come before the node they belong to.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Deployments {
+    names: Rc<RefCell<Vec<String>>>,
+    seen: usize,
+}
+
+/// Collects the ids of the declarations that identifiers anywhere in `v`
+/// refer to, including the external references of inline assembly.
+fn referenced_declarations(v: &Value, ids: &mut Vec<i64>) {
+    match v {
+        Value::Object(obj) => {
+            ids.extend(obj.get("referencedDeclaration").and_then(|id| id.as_i64()));
+            if let Some(refs) = obj.get("externalReferences").and_then(|r| r.as_array()) {
+                ids.extend(refs.iter().filter_map(|r| r["declaration"].as_i64()));
+            }
+            obj.values().for_each(|v| referenced_declarations(v, ids));
+        }
+        Value::Array(arr) => arr.iter().for_each(|v| referenced_declarations(v, ids)),
+        _ => {}
+    }
+}
+
+/// The variables that `node` assigns a newly deployed contract to.
+fn deployed_variables(node: &SolAST) -> Vec<String> {
+    let is_new = |value: &SolAST| {
+        let mut callee = value.expression();
+        if callee.node_type().as_deref() == Some("FunctionCallOptions") {
+            callee = callee.expression();
+        }
+        value.node_type().as_deref() == Some("FunctionCall")
+            && callee.node_type().as_deref() == Some("NewExpression")
+            && callee.get_node("typeName").node_type().as_deref() == Some("UserDefinedTypeName")
+    };
+    let is_create = |value: &SolAST| {
+        value.node_type().as_deref() == Some("YulFunctionCall")
+            && matches!(
+                value.get_node("functionName").name().as_deref(),
+                Some("create" | "create2")
+            )
+    };
+    let names = |list: SolAST| {
+        list.get_object()
+            .and_then(|l| l.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| v["name"].as_str().map(|n| n.to_string()))
+            .collect()
+    };
+    match node.node_type().as_deref() {
+        Some("Assignment") if is_new(&node.right_hand_side()) => {
+            variable_key(&node.left_hand_side()).into_iter().collect()
+        }
+        Some("VariableDeclarationStatement") if is_new(&node.get_node("initialValue")) => {
+            names(node.get_node("declarations"))
+        }
+        Some("YulAssignment") if is_create(&node.get_node("value")) => {
+            names(node.get_node("variableNames"))
+        }
+        Some("YulVariableDeclaration") if is_create(&node.get_node("value")) => {
+            names(node.get_node("variables"))
+        }
+        _ => vec![],
+    }
+}
+
 /// Solidity AST representation.
-/// There are two fields, `element`
-/// which is the underlying json object representing
-/// an AST node and `contract` which indicates
-/// the name of the contract that this node belongs to.
 #[derive(Debug, Deserialize, Default, Clone)]
 #[serde(default)]
 pub struct SolAST {
+    /// The underlying json object representing an AST node.
     pub(crate) element: Option<Value>,
+    /// The name of the contract that this node belongs to.
     pub(crate) contract: Option<String>,
+    /// The name of the function (or modifier) that this node belongs to.
+    pub(crate) function: Option<String>,
+    /// The name and parameter types of that function, see `signature`.
+    pub(crate) function_signature: Option<String>,
+    /// The `stateMutability` of that function (`None` for modifiers).
+    pub(crate) function_mutability: Option<String>,
+    /// The bounds of the innermost statement that this node belongs to,
+    /// if it is not a block. The expressions in the header of a `for`
+    /// belong to the `for`.
+    pub(crate) statement: Option<(usize, usize)>,
+    /// What is defined in the contract.
+    #[serde(skip)]
+    pub(crate) contract_info: Option<Rc<ContractInfo>>,
+    /// The variables that the function assigns a newly deployed contract
+    /// to before this node.
+    #[serde(skip)]
+    pub(crate) deployments: Deployments,
+    /// The names of the modifiers that guard against reentrancy
+    /// (`nonReentrant` unless set with `with_reentrancy_guards`).
+    #[serde(skip)]
+    pub(crate) reentrancy_guards: Option<Rc<Vec<String>>>,
+    /// The version of the compiler that the file is compiled with, if
+    /// known, see `with_solc_version`.
+    #[serde(skip)]
+    pub(crate) solc_version: Option<SolcVersion>,
+    /// The ids of the declarations that the function (or modifier) that
+    /// this node belongs to refers to, see `is_referenced`.
+    #[serde(skip)]
+    pub(crate) references: Option<Rc<Vec<i64>>>,
 }
 
 impl SolAST {
@@ -50,15 +263,59 @@ impl SolAST {
             Self {
                 element: None,
                 contract: None,
+                function: None,
+                function_signature: None,
+                function_mutability: None,
+                statement: None,
+                contract_info: None,
+                deployments: Deployments::default(),
+                reentrancy_guards: None,
+                solc_version: None,
+                references: None,
             }
         } else {
             Self {
                 element: Some(v),
                 contract: c,
+                function: None,
+                function_signature: None,
+                function_mutability: None,
+                statement: None,
+                contract_info: None,
+                deployments: Deployments::default(),
+                reentrancy_guards: None,
+                solc_version: None,
+                references: None,
             }
         }
     }
 
+    /// Create a node for `v` that belongs to the same contract
+    /// and function as this one.
+    fn child(&self, v: Value) -> Self {
+        let mut child = Self::new(v, self.contract.clone());
+        if child.element.is_some() {
+            child.function = self.function.clone();
+            child.function_signature = self.function_signature.clone();
+            child.function_mutability = self.function_mutability.clone();
+            child.statement = self.statement;
+            child.contract_info = self.contract_info.clone();
+            child.deployments = self.deployments.clone();
+            child.reentrancy_guards = self.reentrancy_guards.clone();
+            child.solc_version = self.solc_version;
+            child.references = self.references.clone();
+        }
+        child
+    }
+
+    /// Like `child`, but for the next node of the traversal, which comes
+    /// after every deployment traversed so far.
+    fn traversed_child(&self, v: Value) -> Self {
+        let mut child = self.child(v);
+        child.deployments.seen = child.deployments.names.borrow().len();
+        child
+    }
+
     /// Return the `element` field of a `SolAST` struct.
     pub fn get_object(&self) -> Option<Value> {
         self.element.clone()
@@ -69,6 +326,101 @@ impl SolAST {
         self.contract.clone()
     }
 
+    /// Return the `function` field of a `SolAST` struct.
+    pub fn get_function(&self) -> Option<String> {
+        self.function.clone()
+    }
+
+    /// Return the `function_signature` field of a `SolAST` struct.
+    pub fn get_function_signature(&self) -> Option<String> {
+        self.function_signature.clone()
+    }
+
+    /// The name (or kind, for constructors, `fallback` and `receive`) and
+    /// parameter types of the function or modifier definition `self`, e.g.,
+    /// `transfer(address,uint256)`, which tell overloads apart.
+    pub fn signature(&self) -> String {
+        let name = self
+            .name()
+            .filter(|n| !n.is_empty())
+            .or_else(|| self.kind())
+            .unwrap_or_default();
+        let parameters = self.get_node("parameters").get_node("parameters");
+        let types: Vec<String> = parameters
+            .get_object()
+            .and_then(|p| p.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .map(|p| {
+                p["typeDescriptions"]["typeString"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .collect();
+        format!("{}({})", name, types.join(","))
+    }
+
+    /// Return the `function_mutability` field of a `SolAST` struct.
+    pub fn get_function_mutability(&self) -> Option<String> {
+        self.function_mutability.clone()
+    }
+
+    /// Return the `statement` field of a `SolAST` struct.
+    pub fn get_statement(&self) -> Option<(usize, usize)> {
+        self.statement
+    }
+
+    /// Return the `contract_info` field of a `SolAST` struct.
+    pub fn get_contract_info(&self) -> Option<Rc<ContractInfo>> {
+        self.contract_info.clone()
+    }
+
+    /// Returns this node with `guards` as the names of the modifiers that
+    /// guard against reentrancy, for it and every node under it.
+    pub fn with_reentrancy_guards(mut self, guards: Vec<String>) -> SolAST {
+        self.reentrancy_guards = Some(Rc::new(guards));
+        self
+    }
+
+    /// Checks whether `name` is the name of a modifier that guards against
+    /// reentrancy.
+    pub fn is_reentrancy_guard(&self, name: &str) -> bool {
+        match &self.reentrancy_guards {
+            Some(guards) => guards.iter().any(|g| g == name),
+            None => name == "nonReentrant",
+        }
+    }
+
+    /// Returns this node with `version` as the version of the compiler that
+    /// the file is compiled with, for it and every node under it.
+    pub fn with_solc_version(mut self, version: Option<SolcVersion>) -> SolAST {
+        self.solc_version = version;
+        self
+    }
+
+    /// Return the `solc_version` field of a `SolAST` struct.
+    pub fn get_solc_version(&self) -> Option<SolcVersion> {
+        self.solc_version
+    }
+
+    /// Checks whether the function (or modifier) that this node belongs
+    /// to refers to the declaration with the id `id` anywhere, including
+    /// in inline assembly. Returns `true` if that is not known.
+    pub fn is_referenced(&self, id: i64) -> bool {
+        self.references
+            .as_ref()
+            .map_or_else(|| true, |ids| ids.contains(&id))
+    }
+
+    /// Checks whether the function assigns a newly deployed contract to the
+    /// variable `name`, see `variable_key`, before this node.
+    pub fn was_deployed(&self, name: &str) -> bool {
+        self.deployments.names.borrow()[..self.deployments.seen]
+            .iter()
+            .any(|n| n == name)
+    }
+
     /// Return some node of this AST that has the field name `fnm` in the json
     /// representation.
     pub fn get_node(&self, fnm: &str) -> SolAST {
@@ -76,10 +428,28 @@ impl SolAST {
             || SolAST {
                 element: None,
                 contract: self.get_contract(),
+                function: self.get_function(),
+                function_signature: self.get_function_signature(),
+                function_mutability: self.get_function_mutability(),
+                statement: self.get_statement(),
+                contract_info: self.get_contract_info(),
+                deployments: self.deployments.clone(),
+                reentrancy_guards: self.reentrancy_guards.clone(),
+                solc_version: self.solc_version,
+                references: self.references.clone(),
             },
             |v| SolAST {
                 element: Some(v[fnm].clone()),
                 contract: self.get_contract(),
+                function: self.get_function(),
+                function_signature: self.get_function_signature(),
+                function_mutability: self.get_function_mutability(),
+                statement: self.get_statement(),
+                contract_info: self.get_contract_info(),
+                deployments: self.deployments.clone(),
+                reentrancy_guards: self.reentrancy_guards.clone(),
+                solc_version: self.solc_version,
+                references: self.references.clone(),
             },
         );
         node
@@ -123,6 +493,21 @@ impl SolAST {
         self.get_string("operator")
     }
 
+    /// Returns the `kind` field.
+    pub fn kind(&self) -> Option<String> {
+        self.get_string("kind")
+    }
+
+    /// Returns the `value` field.
+    pub fn value(&self) -> Option<String> {
+        self.get_string("value")
+    }
+
+    /// Returns the `typeString` of the `typeDescriptions` field.
+    pub fn type_string(&self) -> Option<String> {
+        self.get_node("typeDescriptions").get_string("typeString")
+    }
+
     /// Returns the `leftExpression` field.
     pub fn left_expression(&self) -> SolAST {
         self.get_node("leftExpression")
@@ -133,6 +518,16 @@ impl SolAST {
         self.get_node("rightExpression")
     }
 
+    /// Returns the `baseExpression` field.
+    pub fn base_expression(&self) -> SolAST {
+        self.get_node("baseExpression")
+    }
+
+    /// Returns the `indexExpression` field.
+    pub fn index_expression(&self) -> SolAST {
+        self.get_node("indexExpression")
+    }
+
     /// Returns the `leftHandSide` field.
     pub fn left_hand_side(&self) -> SolAST {
         self.get_node("leftHandSide")
@@ -151,10 +546,7 @@ impl SolAST {
             Some(v) => {
                 let arg = &v["arguments"].as_array();
                 match arg {
-                    Some(lst) => lst
-                        .iter()
-                        .map(|e| Self::new(e.clone(), self.contract.clone()))
-                        .collect(),
+                    Some(lst) => lst.iter().map(|e| self.child(e.clone())).collect(),
                     None => vec![],
                 }
             }
@@ -169,10 +561,22 @@ impl SolAST {
             Some(v) => {
                 let arg = &v["statements"].as_array();
                 match arg {
-                    Some(lst) => lst
-                        .iter()
-                        .map(|e| Self::new(e.clone(), self.contract.clone()))
-                        .collect(),
+                    Some(lst) => lst.iter().map(|e| self.child(e.clone())).collect(),
+                    None => vec![],
+                }
+            }
+        }
+    }
+
+    /// Returns the `components` of a tuple expression.
+    pub fn components(&self) -> Vec<SolAST> {
+        let o = self.get_object();
+        match o {
+            None => vec![],
+            Some(v) => {
+                let arg = &v["components"].as_array();
+                match arg {
+                    Some(lst) => lst.iter().map(|e| self.child(e.clone())).collect(),
                     None => vec![],
                 }
             }
@@ -200,6 +604,91 @@ impl SolAST {
             .map(|obj| TypeDescriptions::new(obj["typeDescriptions"].clone()))
     }
 
+    /// A rough measure of the complexity of the code under this node:
+    /// the number of statements, and the number of branches among them,
+    /// i.e., `if`s, loops, and calls to `require`.
+    pub fn complexity(&self) -> (usize, usize) {
+        let kinds = self.clone().traverse(
+            |n| {
+                let node_type = n.node_type()?;
+                let is_require = node_type == "FunctionCall"
+                    && n.expression().name().as_deref() == Some("require");
+                let is_branch = is_require
+                    || matches!(
+                        node_type.as_str(),
+                        "IfStatement" | "ForStatement" | "WhileStatement" | "DoWhileStatement"
+                    );
+                Some((is_statement(&node_type), is_branch))
+            },
+            |_| false,
+            |_| true,
+        );
+        (
+            kinds.iter().filter(|(s, _)| *s).count(),
+            kinds.iter().filter(|(_, b)| *b).count(),
+        )
+    }
+
+    /// Collects the function and modifier definitions under this node,
+    /// with their contract filled in, without visiting their bodies.
+    pub fn definitions(self) -> Vec<SolAST> {
+        let mut definitions = vec![];
+        self.traverse_internal(
+            false,
+            &mut |_| None::<()>,
+            &mut |n| {
+                let is_definition = matches!(
+                    n.node_type().as_deref(),
+                    Some("FunctionDefinition") | Some("ModifierDefinition")
+                );
+                if is_definition {
+                    definitions.push(n.clone());
+                }
+                is_definition
+            },
+            &mut |_| false,
+            false,
+            &mut vec![],
+        );
+        definitions
+    }
+
+    /// Returns this node with every offset in it moved by `delta` bytes,
+    /// for when text is inserted or removed before it.
+    pub fn shifted(&self, delta: isize) -> SolAST {
+        fn shift(v: &mut Value, delta: isize) {
+            match v {
+                Value::Object(obj) => {
+                    for (k, v) in obj.iter_mut() {
+                        match v {
+                            Value::String(src) if k == "src" => {
+                                if let Some((start, rest)) = src.split_once(':') {
+                                    if let Ok(start) = start.parse::<isize>() {
+                                        *src = format!("{}:{}", start + delta, rest);
+                                    }
+                                }
+                            }
+                            _ => shift(v, delta),
+                        }
+                    }
+                }
+                Value::Array(arr) => arr.iter_mut().for_each(|v| shift(v, delta)),
+                _ => {}
+            }
+        }
+        let mut node = self.clone();
+        if let Some(e) = node.element.as_mut() {
+            shift(e, delta);
+        }
+        node.statement = node.statement.map(|(start, end)| {
+            (
+                (start as isize + delta) as usize,
+                (end as isize + delta) as usize,
+            )
+        });
+        node
+    }
+
     /// Recursively traverses the AST.
     /// This is how
     /// Gambit determines what nodes can be mutated
@@ -215,12 +704,20 @@ impl SolAST {
         F: FnMut(&SolAST) -> Option<T>,
     {
         let mut result: Vec<T> = vec![];
-        self.traverse_internal(&mut visitor, &mut skip, &mut accept, false, &mut result);
+        self.traverse_internal(
+            false,
+            &mut visitor,
+            &mut skip,
+            &mut accept,
+            false,
+            &mut result,
+        );
         result
     }
 
     fn traverse_internal<T>(
         mut self,
+        for_header: bool,
         visitor: &mut impl FnMut(&SolAST) -> Option<T>,
         skip: &mut impl FnMut(&SolAST) -> bool,
         accept: &mut impl FnMut(&SolAST) -> bool,
@@ -228,6 +725,31 @@ impl SolAST {
         acc: &mut Vec<T>,
     ) {
         let mut new_accepted = accepted;
+        if self.node_type().map_or_else(
+            || false,
+            |n| n == "FunctionDefinition" || n == "ModifierDefinition",
+        ) {
+            self.function = self.name();
+            self.function_signature = Some(self.signature());
+            self.function_mutability = self.get_string("stateMutability");
+            self.deployments = Deployments::default();
+            let mut ids = vec![];
+            if let Some(e) = &self.element {
+                referenced_declarations(e, &mut ids);
+            }
+            self.references = Some(Rc::new(ids));
+        }
+        match self.node_type().as_deref() {
+            Some("Block") | Some("UncheckedBlock") => self.statement = None,
+            Some(n) if self.src().is_some() && is_statement(n) && !for_header => {
+                self.statement = Some(self.get_bounds())
+            }
+            _ => {}
+        }
+        self.deployments
+            .names
+            .borrow_mut()
+            .extend(deployed_variables(&self));
         if accept(&self) {
             new_accepted = true;
         }
@@ -242,22 +764,31 @@ impl SolAST {
                 // log::info!("no mutation points found");
             }
         }
-        if self.element.is_some() {
-            let e = self.element.unwrap();
+        let is_for = self.node_type().as_deref() == Some("ForStatement");
+        if let Some(e) = self.element.take() {
             if e.is_object() {
                 let e_obj = e.as_object().unwrap();
+                if e["nodeType"] == "SourceUnit" {
+                    self.contract_info = Some(Rc::new(ContractInfo::file(&e)));
+                }
                 if e_obj.contains_key("contractKind") {
                     self.contract = e["name"].as_str().map(|nm| nm.to_string());
+                    self.contract_info = Some(Rc::new(ContractInfo::new(
+                        &e,
+                        self.contract_info.as_deref(),
+                    )));
                 }
-                for v in e_obj.values() {
-                    let child: SolAST = SolAST::new(v.clone(), self.contract.clone());
-                    child.traverse_internal(visitor, skip, accept, new_accepted, acc);
+                for (k, v) in e_obj {
+                    let child: SolAST = self.traversed_child(v.clone());
+                    let header =
+                        is_for && (k == "initializationExpression" || k == "loopExpression");
+                    child.traverse_internal(header, visitor, skip, accept, new_accepted, acc);
                 }
             } else if e.is_array() {
                 let e_arr = e.as_array().unwrap();
                 for a in e_arr {
-                    let child: SolAST = SolAST::new(a.clone(), self.contract.clone());
-                    child.traverse_internal(visitor, skip, accept, new_accepted, acc);
+                    let child: SolAST = self.traversed_child(a.clone());
+                    child.traverse_internal(false, visitor, skip, accept, new_accepted, acc);
                 }
             }
         }
@@ -342,12 +873,11 @@ impl SolAST {
                 rest_of_str[0..m.range().last().unwrap_or_else(|| {
                     panic!("There was a match but last() still returned None.")
                 }) + 1]
-                    .as_bytes()
                     .len();
         }
         self.replace_part(
             source,
-            "/*".to_string() + &String::from_utf8(source[start..end].to_vec()).unwrap() + "*/",
+            "/*".to_string() + std::str::from_utf8(&source[start..end]).unwrap() + "*/",
             start,
             end,
         )
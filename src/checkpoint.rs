@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use crate::{
+    write_atomically, FileStats, FunctionReport, MutantEntry, MutantGenerator, MutationStats,
+    MutationType, StoppedReason,
+};
+
+/// How far generating mutants for a single file got.
+/// There is no need to save the state of the random number generator:
+/// every attempt seeds its own (see `RunMutations::attempt_rng`), so
+/// `attempts` is all it takes to continue with the same mutants.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileProgress {
+    /// Number of attempts made so far.
+    pub attempts: i64,
+    /// SHA-256 of the file and of every mutant generated so far,
+    /// to recognize duplicates.
+    pub seen: HashSet<String>,
+    /// Mutation types still to produce a mutant.
+    pub todo: VecDeque<MutationType>,
+    /// Number of distinct candidates tried so far at each mutation point
+    /// of a mutation type, in the order the points were found.
+    #[serde(default)]
+    pub tried: HashMap<MutationType, Vec<usize>>,
+    /// Mutants written so far.
+    pub mutants: Vec<MutantEntry>,
+    /// How each mutation type has fared so far.
+    pub stats: FileStats,
+}
+
+/// State of a run written with `--checkpoint`, from which `--resume`
+/// continues the run.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Checkpoint {
+    /// The generator, with the params in effect for the current file.
+    pub generator: MutantGenerator,
+    /// Index of the current file among those given with `--filename`,
+    /// or of its entry in the config file.
+    pub file_index: usize,
+    /// Mutants of the files before the current one.
+    pub mutants: Vec<MutantEntry>,
+    /// Why generating mutants stopped for the files before the current one.
+    #[serde(default)]
+    pub stopped_reason: BTreeMap<String, StoppedReason>,
+    /// How each function fared for the files before the current one.
+    #[serde(default)]
+    pub functions: BTreeMap<String, Vec<FunctionReport>>,
+    /// How the candidates of each mutation fared for the files before the
+    /// current one.
+    #[serde(default)]
+    pub stats: BTreeMap<String, BTreeMap<String, MutationStats>>,
+    /// Progress on the current file, if any was made.
+    pub progress: Option<FileProgress>,
+}
+
+impl Checkpoint {
+    /// Write the checkpoint to `path`, replacing any previous one.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        write_atomically(path, &serde_json::to_vec(self)?)
+    }
+
+    /// Read the checkpoint at `path`.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let f = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(f))?)
+    }
+}
@@ -0,0 +1,142 @@
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{invoke_command, shell_quote, write_atomically};
+
+/// Name of the checksum file written to the output directory.
+pub static CHECKSUMS: &str = "CHECKSUMS.txt";
+
+/// Returns the hex encoded SHA-256 of `contents`.
+pub fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Write `CHECKSUMS.txt` to `outdir` in the format used by `sha256sum`,
+/// i.e., one `<hash>  <path>` line per file, with paths relative to `outdir`.
+pub fn write_checksums(outdir: &Path, entries: &[(String, String)]) -> io::Result<()> {
+    let body: String = entries
+        .iter()
+        .map(|(hash, path)| format!("{}  {}\n", hash, path))
+        .collect();
+    write_atomically(&outdir.join(CHECKSUMS), body.as_bytes())
+}
+
+/// Re-hash every file listed in the `CHECKSUMS.txt` of `outdir` and
+/// return a description of each file that is missing or does not match.
+pub fn verify_checksums(outdir: &Path) -> io::Result<Vec<String>> {
+    let body = fs::read_to_string(outdir.join(CHECKSUMS))?;
+    let mut mismatches = vec![];
+    for line in body.lines().filter(|l| !l.trim().is_empty()) {
+        let (expected, path) = line.split_once("  ").ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("malformed line in {}: {}", CHECKSUMS, line),
+            )
+        })?;
+        match fs::read(outdir.join(path)) {
+            Ok(contents) => {
+                let actual = sha256_hex(&contents);
+                if actual != expected {
+                    mismatches.push(format!(
+                        "{}: expected {} but found {}",
+                        path, expected, actual
+                    ));
+                }
+            }
+            Err(_) => mismatches.push(format!("{}: missing", path)),
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Run the user supplied `sign_command` on `path` with `sh -c`, substituting
+/// `{}` with the quoted path, e.g., `gpg --detach-sign {}`.
+pub fn sign(sign_command: &str, path: &Path) -> io::Result<()> {
+    if sign_command.trim().is_empty() {
+        return Ok(());
+    }
+    let command = sign_command.replace("{}", &shell_quote(path.to_str().unwrap()));
+    let (status, _, stderr) =
+        invoke_command("sh", vec!["-c", &command]).map_err(|e| io::Error::other(e.to_string()))?;
+    if status != Some(0) {
+        return Err(io::Error::other(format!(
+            "`{}` failed: {}",
+            sign_command,
+            String::from_utf8_lossy(&stderr)
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        let outdir = std::env::temp_dir().join("gambit_test_verify_checksums");
+        let _ = fs::remove_dir_all(&outdir);
+        fs::create_dir_all(outdir.join("src")).unwrap();
+        let files = [
+            ("src/A.sol_0.sol", "contract A {}"),
+            ("manifest.json", "{}"),
+        ];
+        let mut entries = vec![];
+        for (path, contents) in files {
+            fs::write(outdir.join(path), contents).unwrap();
+            entries.push((sha256_hex(contents.as_bytes()), path.to_string()));
+        }
+        write_checksums(&outdir, &entries).unwrap();
+        assert!(verify_checksums(&outdir).unwrap().is_empty());
+
+        let mut corrupted = fs::read(outdir.join("src/A.sol_0.sol")).unwrap();
+        corrupted[9] ^= 1;
+        fs::write(outdir.join("src/A.sol_0.sol"), corrupted).unwrap();
+        let mismatches = verify_checksums(&outdir).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].starts_with("src/A.sol_0.sol: expected"));
+
+        fs::remove_file(outdir.join("manifest.json")).unwrap();
+        let mismatches = verify_checksums(&outdir).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[1], "manifest.json: missing");
+        fs::remove_dir_all(&outdir).unwrap();
+    }
+
+    #[test]
+    fn test_sign() {
+        let outdir = std::env::temp_dir().join("gambit_test_sign");
+        let _ = fs::remove_dir_all(&outdir);
+        let dir = outdir.join("my out");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CHECKSUMS);
+        fs::write(&path, "").unwrap();
+        sign("printf '%s' 'Audit Key' > {}.sig", &path).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("CHECKSUMS.txt.sig")).unwrap(),
+            "Audit Key"
+        );
+        sign("printf '%s' --output={}.asc > {}.args", &path).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("CHECKSUMS.txt.args")).unwrap(),
+            format!("--output={}.asc", path.display())
+        );
+        assert!(sign("test ! -f {}", &path).is_err());
+        fs::remove_dir_all(&outdir).unwrap();
+    }
+}
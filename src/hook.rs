@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::shell_quote;
+
+/// How often a running hook is checked for completion.
+static POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A command run on every mutant as soon as it is written,
+/// given with `--on-mutant`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutantHook {
+    /// Shell command in which `{path}`, `{id}` and `{op}` are replaced by
+    /// the path of the mutant, its id in the manifest, and its mutation.
+    pub command: String,
+    /// How long the command may run before it is killed.
+    pub timeout: Duration,
+    /// Discard mutants for which the command does not succeed.
+    pub required: bool,
+}
+
+/// Outcome of running a `MutantHook`, recorded in the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStatus {
+    /// The command exited with the given code.
+    Exited(i32),
+    /// The command was terminated by a signal.
+    Signaled,
+    /// The command was killed for running longer than the timeout.
+    TimedOut,
+}
+
+impl HookStatus {
+    pub fn success(&self) -> bool {
+        *self == HookStatus::Exited(0)
+    }
+}
+
+impl MutantHook {
+    /// The command with its placeholders replaced, each value quoted so
+    /// that the shell sees it as a single word.
+    pub fn substitute(&self, path: &str, id: usize, op: &str) -> String {
+        self.command
+            .replace("{path}", &shell_quote(path))
+            .replace("{id}", &id.to_string())
+            .replace("{op}", &shell_quote(op))
+    }
+
+    /// Run the hook for the mutant at `path`, killing it once it exceeds
+    /// the timeout.
+    pub fn run(&self, path: &str, id: usize, op: &str) -> io::Result<HookStatus> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(self.substitute(path, id, op))
+            .stdin(Stdio::null())
+            .spawn()?;
+        let start = Instant::now();
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status
+                    .code()
+                    .map_or(HookStatus::Signaled, HookStatus::Exited));
+            }
+            if start.elapsed() >= self.timeout {
+                child.kill()?;
+                child.wait()?;
+                return Ok(HookStatus::TimedOut);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn hook(command: &str) -> MutantHook {
+        MutantHook {
+            command: command.into(),
+            timeout: Duration::from_secs(10),
+            required: false,
+        }
+    }
+
+    #[test]
+    fn test_substitute() {
+        let h = hook("notify {path} --id={id} --op {op}");
+        assert_eq!(
+            h.substitute("out/my dir/A.sol_3.sol", 4, "BinaryOpMutation"),
+            "notify 'out/my dir/A.sol_3.sol' --id=4 --op BinaryOpMutation"
+        );
+    }
+
+    #[test]
+    fn test_run_hook() {
+        let dir = std::env::temp_dir().join("gambit_test_run_hook");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("it's a \"mutant\".sol");
+        fs::write(&path, "contract A {}").unwrap();
+        let path = path.to_str().unwrap();
+        let h = hook("test -f {path} && test {id} -eq 7 && test {op} = RequireMutation");
+        assert_eq!(
+            h.run(path, 7, "RequireMutation").unwrap(),
+            HookStatus::Exited(0)
+        );
+        assert_eq!(
+            h.run(path, 7, "BinaryOpMutation").unwrap(),
+            HookStatus::Exited(1)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_hook_timeout() {
+        let mut h = hook("sleep 5");
+        h.timeout = Duration::from_millis(100);
+        let start = Instant::now();
+        assert_eq!(h.run("A.sol", 1, "op").unwrap(), HookStatus::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
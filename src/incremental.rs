@@ -0,0 +1,283 @@
+use std::rc::Rc;
+
+use crate::{
+    sha256_hex, ContractInfo, MutabilityScope, MutationType, RunMutations, SolAST, Suppressor,
+};
+
+/// The mutation points of a function (or modifier) in the last version of
+/// the file that was analyzed.
+struct CachedFunction {
+    /// Identifies the function across versions of the file, see `function_key`.
+    key: String,
+    /// Hash of the text of the function.
+    hash: String,
+    /// Where the function starts in the source.
+    start: usize,
+    /// What is defined in the contract of the function.
+    info: Option<Rc<ContractInfo>>,
+    points: Vec<(MutationType, SolAST)>,
+}
+
+/// Mutation points of a file that is edited over and over, e.g., in an
+/// editor, where only the functions whose text changed since the last
+/// version are traversed again.
+/// The points of the other functions are reused, moved by however many
+/// bytes were inserted or removed before them.
+/// Anything but the body of a function changing, e.g., a function being
+/// added, removed, renamed or moved, or the text between functions
+/// changing, falls back to analyzing the whole file.
+pub struct IncrementalPoints {
+    mutation_types: Vec<MutationType>,
+    reentrancy_guards: Vec<String>,
+    /// The source of the last version with the text of every function
+    /// removed.
+    outline: Option<Vec<u8>>,
+    functions: Vec<CachedFunction>,
+    reused: usize,
+}
+
+impl IncrementalPoints {
+    /// No points are cached until the first call to `update`.
+    pub fn new(mutation_types: Vec<MutationType>, reentrancy_guards: Vec<String>) -> Self {
+        IncrementalPoints {
+            mutation_types,
+            reentrancy_guards,
+            outline: None,
+            functions: vec![],
+            reused: 0,
+        }
+    }
+
+    /// How many functions had their points reused by the last `update`.
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// The mutation points of the new version `source` of the file, whose
+    /// AST is `ast`, in the order in which a traversal of `ast` finds them.
+    /// Suppression rules and function filters do not apply here.
+    pub fn update(&mut self, ast: SolAST, source: &[u8]) -> Vec<(MutationType, SolAST)> {
+        let ast = ast.with_reentrancy_guards(self.reentrancy_guards.clone());
+        let definitions = ast.clone().definitions();
+        let spans: Vec<(usize, usize)> = definitions.iter().map(|d| d.get_bounds()).collect();
+        let outline = outline(source, &spans);
+        let keys: Vec<String> = definitions.iter().map(function_key).collect();
+        let same_layout = self.outline.as_ref() == Some(&outline)
+            && self.functions.len() == definitions.len()
+            && self
+                .functions
+                .iter()
+                .zip(&definitions)
+                .zip(&keys)
+                .all(|((f, d), k)| {
+                    f.key == *k && f.info.as_deref() == d.get_contract_info().as_deref()
+                });
+        let cached = if same_layout {
+            std::mem::take(&mut self.functions)
+        } else {
+            vec![]
+        };
+
+        self.reused = 0;
+        let mut functions = vec![];
+        for (i, ((definition, key), (start, end))) in
+            definitions.into_iter().zip(keys).zip(&spans).enumerate()
+        {
+            let hash = sha256_hex(&source[*start..*end]);
+            let points = match cached.get(i) {
+                Some(f) if f.hash == hash => {
+                    self.reused += 1;
+                    let delta = *start as isize - f.start as isize;
+                    f.points
+                        .iter()
+                        .map(|(m, p)| (*m, p.shifted(delta)))
+                        .collect()
+                }
+                _ => self.points(definition.clone(), false),
+            };
+            functions.push(CachedFunction {
+                key,
+                hash,
+                start: *start,
+                info: definition.get_contract_info(),
+                points,
+            });
+        }
+
+        // Everything outside of functions is cheap to traverse again, and
+        // the points there are merged with those of the functions in order.
+        let mut chunks: Vec<(usize, Vec<(MutationType, SolAST)>)> = self
+            .points(ast, true)
+            .into_iter()
+            .map(|(m, p)| (p.get_bounds().0, vec![(m, p)]))
+            .collect();
+        chunks.extend(functions.iter().map(|f| (f.start, f.points.clone())));
+        chunks.sort_by_key(|(start, _)| *start);
+
+        self.outline = Some(outline);
+        self.functions = functions;
+        chunks.into_iter().flat_map(|(_, points)| points).collect()
+    }
+
+    /// Traverse `node` for mutation points, leaving out function and
+    /// modifier definitions if `skip_definitions`.
+    fn points(&self, node: SolAST, skip_definitions: bool) -> Vec<(MutationType, SolAST)> {
+        let mut suppressor = Suppressor::default();
+        let mut excluded = 0;
+        let (visitor, skip, accept) = RunMutations::mk_closures(
+            String::new(),
+            self.mutation_types.clone(),
+            None,
+            None,
+            &mut suppressor,
+            MutabilityScope::All,
+            &mut excluded,
+        );
+        let skip = |n: &SolAST| {
+            skip(n)
+                || skip_definitions
+                    && matches!(
+                        n.node_type().as_deref(),
+                        Some("FunctionDefinition") | Some("ModifierDefinition")
+                    )
+        };
+        node.traverse(visitor, skip, accept)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+/// Identifies a function by its contract and its signature, see
+/// `SolAST::signature`, so that overloads are told apart.
+fn function_key(definition: &SolAST) -> String {
+    format!(
+        "{}.{}",
+        definition.get_contract().unwrap_or_default(),
+        definition.signature()
+    )
+}
+
+/// `source` with the text in `spans`, which are sorted and do not overlap,
+/// replaced by a NUL byte each.
+fn outline(source: &[u8], spans: &[(usize, usize)]) -> Vec<u8> {
+    let mut res = vec![];
+    let mut last = 0;
+    for (start, end) in spans {
+        res.extend_from_slice(&source[last..*start]);
+        res.push(0);
+        last = *end;
+    }
+    res.extend_from_slice(&source[last..]);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+    use serde_json::Value;
+    use std::fs;
+
+    /// The AST and source of `C.sol` with `text` inserted before `before`,
+    /// as if recompiled.
+    fn edited(before: &str, text: &str) -> (SolAST, Vec<u8>) {
+        fn insert(v: &mut Value, at: usize, n: usize) {
+            match v {
+                Value::Object(obj) => {
+                    for (k, v) in obj.iter_mut() {
+                        match v {
+                            Value::String(src) if k == "src" => {
+                                let parts: Vec<usize> =
+                                    src.split(':').map(|p| p.parse().unwrap()).collect();
+                                let (start, len) = (parts[0], parts[1]);
+                                if start >= at {
+                                    *src = format!("{}:{}:{}", start + n, len, parts[2]);
+                                } else if start + len >= at {
+                                    *src = format!("{}:{}:{}", start, len + n, parts[2]);
+                                }
+                            }
+                            _ => insert(v, at, n),
+                        }
+                    }
+                }
+                Value::Array(arr) => arr.iter_mut().for_each(|v| insert(v, at, n)),
+                _ => {}
+            }
+        }
+        let mut source = fs::read_to_string("benchmarks/MultipleContracts/C.sol").unwrap();
+        let mut json: Value = serde_json::from_str(
+            &fs::read_to_string("benchmarks/MultipleContracts/C.json").unwrap(),
+        )
+        .unwrap();
+        if !text.is_empty() {
+            let at = source.find(before).unwrap();
+            source.insert_str(at, text);
+            insert(&mut json, at, text.len());
+        }
+        (SolAST::new(json, None), source.into_bytes())
+    }
+
+    /// The mutation points of `ast` from a traversal of the whole AST, as
+    /// a list of mutation types and bounds.
+    fn full(ast: SolAST) -> Vec<(MutationType, (usize, usize))> {
+        let points = IncrementalPoints::new(MutationType::value_variants().to_vec(), vec![])
+            .points(ast, false);
+        bounds(&points)
+    }
+
+    fn bounds(points: &[(MutationType, SolAST)]) -> Vec<(MutationType, (usize, usize))> {
+        points.iter().map(|(m, p)| (*m, p.get_bounds())).collect()
+    }
+
+    #[test]
+    fn test_reuse_points_of_unchanged_functions() {
+        let mut incremental =
+            IncrementalPoints::new(MutationType::value_variants().to_vec(), vec![]);
+        let (ast, source) = edited("", "");
+        let before = incremental.update(ast.clone(), &source);
+        assert_eq!(incremental.reused(), 0);
+        assert_eq!(bounds(&before), full(ast.clone()));
+
+        // Only `get10PowerDecimals` changed, all other functions are reused.
+        let indent = "\n        ";
+        let (edited_ast, edited_source) = edited("uint256 a = 10;", indent);
+        let after = incremental.update(edited_ast.clone(), &edited_source);
+        assert_eq!(incremental.reused(), ast.definitions().len() - 1);
+        assert_eq!(bounds(&after), full(edited_ast));
+
+        // Points after the edit moved by the length of the inserted text.
+        let in_function = |points: &[(MutationType, SolAST)], f: &str| {
+            points
+                .iter()
+                .filter(|(_, p)| p.get_function().as_deref() == Some(f))
+                .map(|(_, p)| p.get_bounds())
+                .collect::<Vec<_>>()
+        };
+        let moved: Vec<(usize, usize)> = in_function(&before, "callmyself")
+            .iter()
+            .map(|(s, e)| (s + indent.len(), e + indent.len()))
+            .collect();
+        assert!(!moved.is_empty());
+        assert_eq!(in_function(&after, "callmyself"), moved);
+        assert_eq!(in_function(&after, "foo"), in_function(&before, "foo"));
+        for (_, point) in &after {
+            let (start, end) = point.get_bounds();
+            assert!(std::str::from_utf8(&edited_source[start..end]).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_fall_back_when_layout_changes() {
+        let mut incremental =
+            IncrementalPoints::new(MutationType::value_variants().to_vec(), vec![]);
+        let (ast, source) = edited("", "");
+        incremental.update(ast, &source);
+        let (ast, source) = edited("function callmyself", "\n");
+        let points = incremental.update(ast.clone(), &source);
+        assert_eq!(incremental.reused(), 0);
+        assert_eq!(bounds(&points), full(ast.clone()));
+        incremental.update(ast.clone(), &source);
+        assert_eq!(incremental.reused(), ast.definitions().len());
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+use crate::{get_path_normals, sha256_hex};
+
+/// Room left after the mutant directory of a file for the `_<attempt>.sol`
+/// that names each of its mutants.
+const SUFFIX_LEN: usize = "_".len() + "18446744073709551615".len() + ".sol".len();
+
+/// The longest name of a single file or directory on most file systems.
+const NAME_MAX: usize = 255;
+
+/// Limits on the length of the paths that mutants are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimit {
+    /// Longest absolute path, in bytes.
+    pub path: usize,
+    /// Longest name of a single file or directory, in bytes.
+    pub component: usize,
+}
+
+impl PathLimit {
+    /// The limits of the platform Gambit runs on: `PATH_MAX` on unix, and
+    /// `MAX_PATH` on Windows unless long paths are enabled.
+    pub fn host() -> Self {
+        let path = if cfg!(windows) && !windows_long_paths() {
+            259
+        } else if cfg!(windows) {
+            32_767
+        } else {
+            4095
+        };
+        PathLimit {
+            path,
+            component: NAME_MAX,
+        }
+    }
+
+    /// The limits with the absolute path limited to `path` bytes.
+    pub fn with_path(path: usize) -> Self {
+        PathLimit {
+            path,
+            component: NAME_MAX,
+        }
+    }
+
+    /// Whether `path`, with the suffix of a mutant appended, is within
+    /// the limits.
+    fn admits(&self, path: &Path) -> bool {
+        let absolute = match path.is_absolute() {
+            true => path.to_path_buf(),
+            false => std::env::current_dir().unwrap_or_default().join(path),
+        };
+        let name = path.file_name().map_or(0, |n| n.len());
+        absolute.as_os_str().len() + SUFFIX_LEN <= self.path
+            && name + SUFFIX_LEN <= self.component
+            && path.components().all(|c| match c {
+                Component::Normal(n) => n.len() <= self.component,
+                _ => true,
+            })
+    }
+}
+
+/// Whether Windows was set up to allow paths longer than `MAX_PATH`.
+fn windows_long_paths() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        Command::new("reg")
+            .args([
+                "query",
+                r"HKLM\SYSTEM\CurrentControlSet\Control\FileSystem",
+                "/v",
+                "LongPathsEnabled",
+            ])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains("0x1"))
+            .unwrap_or(false)
+    })
+}
+
+/// Where the mutants of `fnm` are written in `outdir`, as the prefix that
+/// `_<attempt>.sol` is appended to, and whether it was shortened.
+/// The mutants mirror the path of the file, e.g., `out/src/A.sol_3.sol`,
+/// unless that gets too long for `limit`, in which case they go to a
+/// directory named after the hash of the path, e.g.,
+/// `out/~a1b2c3d4/A.sol_3.sol`, or even `out/~a1b2c3d4/mutant_3.sol` if
+/// the name of the file itself is too long.
+pub fn mutant_dir(outdir: &Path, fnm: &str, limit: PathLimit) -> (PathBuf, bool) {
+    let normals = get_path_normals(fnm).unwrap();
+    let mirrored = outdir.join(&normals);
+    if limit.admits(&mirrored) {
+        return (mirrored, false);
+    }
+    let hashed = outdir.join(format!(
+        "~{}",
+        &sha256_hex(normals.to_string_lossy().as_bytes())[..8]
+    ));
+    let named = hashed.join(normals.file_name().unwrap());
+    if limit.admits(&named) {
+        (named, true)
+    } else {
+        (hashed.join("mutant"), true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorten_mutant_dirs_that_are_too_long() {
+        let outdir = Path::new("/tmp/out");
+        let limit = PathLimit::with_path(120);
+        let (dir, shortened) = mutant_dir(outdir, "src/A.sol", limit);
+        assert_eq!(dir, Path::new("/tmp/out/src/A.sol"));
+        assert!(!shortened);
+
+        let deep = format!("{}A.sol", "very/deep/path/".repeat(8));
+        let (dir, shortened) = mutant_dir(outdir, &deep, limit);
+        assert!(shortened);
+        assert_eq!(dir.parent().unwrap().parent().unwrap(), outdir);
+        assert!(dir
+            .parent()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("/tmp/out/~"));
+        assert_eq!(dir.file_name().unwrap(), "A.sol");
+        assert_eq!(mutant_dir(outdir, &deep, limit), (dir.clone(), true));
+        let other = format!("{}B.sol", "very/deep/path/".repeat(8));
+        assert_ne!(mutant_dir(outdir, &other, limit).0.parent(), dir.parent());
+
+        let long = format!("src/{}.sol", "A".repeat(300));
+        let (dir, shortened) = mutant_dir(outdir, &long, PathLimit::host());
+        assert!(shortened);
+        assert_eq!(dir.file_name().unwrap(), "mutant");
+    }
+}
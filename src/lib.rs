@@ -1,12 +1,11 @@
 use clap::{Parser, ValueEnum};
 use core::panic;
-use rand::SeedableRng;
-use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
 use std::io::BufReader;
+use std::time::Duration;
 use std::{fs, io};
 use std::{
     fs::File,
@@ -15,50 +14,138 @@ use std::{
 
 mod ast;
 pub use ast::*;
+mod checkpoint;
+pub use checkpoint::*;
+mod checksum;
+pub use checksum::*;
+mod hook;
+pub use hook::*;
+mod incremental;
+pub use incremental::*;
+mod layout;
+pub use layout::*;
+mod manifest;
+pub use manifest::*;
 mod mutation;
 pub use mutation::*;
+mod observer;
+pub use observer::*;
 mod run;
 pub use run::*;
+mod sparse;
+pub use sparse::*;
+mod strategy;
+pub use strategy::*;
+mod suppress;
+pub use suppress::*;
 mod util;
 pub use util::*;
+mod verify;
+pub use verify::*;
+
+/// What `run_one` yields for a file: its mutants, why generating them
+/// stopped, how each function fared and how each mutation did.
+type FileOutcome = (
+    Vec<MutantEntry>,
+    StoppedReason,
+    Vec<FunctionReport>,
+    BTreeMap<String, MutationStats>,
+);
 
 /// temporary paths for compiling mutants.
 static TMP: &str = "tmp.sol";
 
-#[derive(Debug, Clone)]
+/// Where the ASTs of the mutated files are kept, relative to the output
+/// directory, each in a directory named after the hash of its source.
+static AST_CACHE: &str = ".gambit_cache/ast";
+
+/// Where older versions of Gambit kept the ASTs, by the path of the file.
+static LEGACY_AST_DIR: &str = "input_json";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MutantGenerator {
     /// Params for controlling the mutants.
     pub params: MutationParams,
-    /// will need this for randomization
-    pub rng: Pcg64,
+    /// Directory of the config file, which relative paths in it are
+    /// resolved against.
+    pub config_dir: PathBuf,
+    /// The `solc` of the config entry being run, if it has one.
+    #[serde(default)]
+    pub entry_solc: Option<String>,
+    /// Told about the progress of the run, see `set_observer`.
+    #[serde(skip)]
+    observer: Observer,
+    /// The mutants generated so far, which are not generated again for
+    /// another file of the run, rebuilt from the mutants of the checkpoint
+    /// on `--resume`.
+    #[serde(skip)]
+    claims: Claims,
+    /// The sampling strategy given with `set_strategy`, if any, which takes
+    /// precedence over `--strategy`.
+    #[serde(skip)]
+    sampler: Option<Sampler>,
 }
 
 impl MutantGenerator {
     /// Initialize the MutantGenerator
     pub fn new(params: MutationParams) -> Self {
         MutantGenerator {
-            rng: rand_pcg::Pcg64::seed_from_u64(params.seed),
             params,
+            config_dir: PathBuf::new(),
+            entry_solc: None,
+            observer: Observer::default(),
+            claims: Claims::default(),
+            sampler: None,
         }
     }
 
+    /// Report the progress of the run to `observer`, see `GambitObserver`.
+    pub fn set_observer(&mut self, observer: Box<dyn GambitObserver>) {
+        self.observer = Observer::new(observer);
+    }
+
+    /// Use `strategy` to decide which mutation to try next instead of the
+    /// one given with `--strategy`, see `SamplingStrategy`.
+    pub fn set_strategy(&mut self, strategy: Box<dyn SamplingStrategy>) {
+        self.sampler = Some(Sampler::new(strategy));
+    }
+
+    /// The Solidity compiler for `sol`: the `solc` of the config entry,
+    /// or else the first match in `--solc-map`, or else `--solc`.
+    fn solc_for(&self, sol: &str) -> String {
+        self.entry_solc.clone().unwrap_or_else(|| {
+            self.params
+                .solc_map
+                .as_ref()
+                .and_then(|m| m.solc_for(sol))
+                .unwrap_or(&self.params.solc)
+                .to_string()
+        })
+    }
+
     /// A helper function to create the directory where the
     /// AST (.ast) and it's json representation (.ast.json)
     /// are stored.
+    /// The directory is named after the hash of everything the AST depends
+    /// on: the path of `sol`, the compiler and the flags it is run with,
+    /// and the contents of `sol`, so that an AST is never reused for a
+    /// file it was not produced for.
     /// This returns the directory, and both the path to the .ast and the .ast.json.
-    fn mk_ast_dir(&self, sol: &String, out: PathBuf) -> (PathBuf, PathBuf, PathBuf) {
+    fn mk_ast_dir(&self, sol: &String, out: PathBuf) -> io::Result<(PathBuf, PathBuf, PathBuf)> {
         let norms_of_path =
             get_path_normals(sol).unwrap_or_else(|| panic!("Path to sol file is broken"));
         let extension = norms_of_path.extension();
         if extension.is_none() || !extension.unwrap().eq("sol") {
             panic!("{} is not a solidity source file.", sol);
         }
-        let sol_ast_dir = out.join(
-            "input_json/".to_owned()
-                + norms_of_path
-                    .to_str()
-                    .unwrap_or_else(|| panic!("Path is not valid.")),
-        );
+        let mut key = vec![fs::canonicalize(sol)?.to_string_lossy().to_string()];
+        key.push(self.solc_for(sol));
+        key.extend(self.params.solc_basepath.iter().cloned());
+        key.extend(self.params.solc_remapping.iter().flatten().cloned());
+        let mut key = key.join("\0").into_bytes();
+        key.push(0);
+        key.extend(fs::read(sol)?);
+        let sol_ast_dir = out.join(AST_CACHE).join(sha256_hex(&key));
         let ast_fnm = Path::new(sol)
             .file_name()
             .unwrap()
@@ -68,7 +155,42 @@ impl MutantGenerator {
             + "_json.ast";
         let ast_path = sol_ast_dir.join(&ast_fnm);
         let json_path = sol_ast_dir.join(ast_fnm + ".json");
-        (sol_ast_dir, ast_path, json_path)
+        Ok((sol_ast_dir, ast_path, json_path))
+    }
+
+    /// Delete the directory in which older versions of Gambit kept the
+    /// ASTs, which is easily mistaken for mutants, when migrating an output
+    /// directory written by one of them, i.e., one without an AST cache yet
+    /// whose `input_json` holds nothing but ASTs.
+    fn remove_legacy_ast_dir(&self) -> io::Result<()> {
+        fn only_asts(dir: &Path) -> io::Result<bool> {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                let is_ast = if entry.file_type()?.is_dir() {
+                    only_asts(&entry.path())?
+                } else {
+                    name.ends_with("_json.ast") || name.ends_with("_json.ast.json")
+                };
+                if !is_ast {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        let outdir = Path::new(&self.params.outdir);
+        let legacy = outdir.join(LEGACY_AST_DIR);
+        let cache = outdir.join(AST_CACHE.split('/').next().unwrap());
+        if legacy.is_dir() && !cache.exists() && only_asts(&legacy)? {
+            let message = format!(
+                "migrating {}: removing {}, where older versions of Gambit kept the ASTs",
+                outdir.display(),
+                legacy.display()
+            );
+            self.observer.notify(|o| o.on_warning(&message));
+            fs::remove_dir_all(legacy)?;
+        }
+        Ok(())
     }
 
     /// This method compiles an input solc file to get the json AST.
@@ -82,7 +204,8 @@ impl MutantGenerator {
         sol: &String,
         out: PathBuf,
     ) -> Result<SolAST, Box<dyn std::error::Error>> {
-        let (sol_ast_dir, ast_path, json_path) = self.mk_ast_dir(sol, out);
+        let (sol_ast_dir, ast_path, json_path) = self.mk_ast_dir(sol, out)?;
+        let solc = self.solc_for(sol);
         if !ast_path.exists() || !json_path.exists() {
             std::fs::create_dir_all(sol_ast_dir.parent().unwrap())?;
             log::info!(
@@ -97,9 +220,9 @@ impl MutantGenerator {
                 "--overwrite",
             ];
 
-            if self.params.solc_basepath.is_some() {
+            if let Some(bp) = &self.params.solc_basepath {
                 flags.push("--base-path");
-                flags.push(self.params.solc_basepath.as_ref().unwrap());
+                flags.push(bp);
             }
 
             if let Some(remaps) = &self.params.solc_remapping {
@@ -108,7 +231,7 @@ impl MutantGenerator {
                 }
             }
 
-            if invoke_command(&self.params.solc, flags)?
+            if invoke_command(&solc, flags)?
                 .0
                 .unwrap_or_else(|| panic!("solc terminated with a signal."))
                 != 0
@@ -129,15 +252,50 @@ impl MutantGenerator {
         Ok(SolAST {
             element: Some(ast_json),
             contract: None,
+            function: None,
+            function_signature: None,
+            function_mutability: None,
+            statement: None,
+            contract_info: None,
+            deployments: Default::default(),
+            reentrancy_guards: None,
+            solc_version: None,
+            references: None,
         })
     }
 
+    /// The limits on the paths of mutants: `--max-path-len`, if given,
+    /// or else those of the platform.
+    fn path_limit(&self) -> PathLimit {
+        self.params
+            .max_path_len
+            .map_or_else(PathLimit::host, PathLimit::with_path)
+    }
+
+    /// Warn about the files in `files` whose mutants go to a shortened
+    /// directory, since mirroring their path would be too long.
+    fn warn_shortened<'a>(&self, files: impl IntoIterator<Item = &'a String>) {
+        let outdir = Path::new(&self.params.outdir);
+        let shortened: Vec<String> = files
+            .into_iter()
+            .filter_map(|f| match mutant_dir(outdir, f, self.path_limit()) {
+                (dir, true) => Some(format!("  {} -> {}", f, dir.display())),
+                _ => None,
+            })
+            .collect();
+        if !shortened.is_empty() {
+            let message = format!(
+                "paths too long, writing the mutants of these files to shortened directories:\n{}",
+                shortened.join("\n")
+            );
+            self.observer.notify(|o| o.on_warning(&message));
+        }
+    }
+
     /// Create a directory for saving the mutants for a given
     /// file `fnm`. All mutant files will be dumped here.
     fn mk_mutant_dir(&self, fnm: &str) -> io::Result<()> {
-        let norm_path = get_path_normals(fnm);
-        assert!(norm_path.is_some());
-        let mut_dir = PathBuf::from(&self.params.outdir).join(norm_path.unwrap());
+        let (mut_dir, _) = mutant_dir(Path::new(&self.params.outdir), fnm, self.path_limit());
         if let Some(pd) = mut_dir.parent() {
             if pd.is_dir() {
                 fs::remove_dir_all(pd)?;
@@ -147,67 +305,246 @@ impl MutantGenerator {
         Ok(())
     }
 
+    /// Resolve a `path` given in the config file relative to the
+    /// directory of the config file.
+    fn config_path(&self, path: &str) -> String {
+        normalize_path(&self.config_dir.join(path))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
     /// Create directories for mutants from a json config file.
     /// This is used when Gambit is run using a config file as opposed
     /// to individual solidity files using the `-f` flag.
     fn mutant_dirs_from_json(&self) -> io::Result<()> {
-        let f = File::open(&self.params.json.as_ref().unwrap())?;
+        let f = File::open(self.params.json.as_ref().unwrap())?;
         let config: Value = serde_json::from_reader(BufReader::new(f))?;
         match config {
             Value::Array(elems) => {
                 let mut paths = HashSet::new();
                 for e in elems {
-                    paths.insert(e["filename"].as_str().unwrap().to_string());
+                    paths.insert(self.config_path(e["filename"].as_str().unwrap()));
                 }
+                self.warn_shortened(&paths);
                 paths.iter().for_each(|p| {
                     self.mk_mutant_dir(p).ok();
                 });
             }
             Value::Object(o) => {
-                self.mk_mutant_dir(o["filename"].as_str().unwrap())?;
+                let path = self.config_path(o["filename"].as_str().unwrap());
+                self.warn_shortened([&path]);
+                self.mk_mutant_dir(&path)?;
             }
             _ => panic!("Ill-formed json."),
         }
         Ok(())
     }
 
+    /// The `gambit mutate` command line that reproduces `run_one` with these
+    /// arguments under the current (post-config) params, up to the choice of
+    /// mutation and attempt, which are appended per mutant.
+    /// Empty if the strategy of the run cannot be reproduced that way,
+    /// see `SamplingStrategy::is_reproducible`.
+    fn repro_base(
+        &self,
+        file_to_mutate: &str,
+        funcs: &Option<Vec<String>>,
+        contract: &Option<String>,
+        suppressor: &Suppressor,
+        num_mutants: i64,
+    ) -> String {
+        let reproducible = self
+            .sampler
+            .as_ref()
+            .map_or_else(|| true, |s| s.with(|s| s.is_reproducible()));
+        if !reproducible {
+            return String::new();
+        }
+        let p = &self.params;
+        let mut args: Vec<String> = vec![
+            "gambit".into(),
+            "mutate".into(),
+            "--filename".into(),
+            file_to_mutate.into(),
+            "--outdir".into(),
+            p.outdir.clone(),
+            "--seed".into(),
+            p.seed.to_string(),
+            "--num-mutants".into(),
+            num_mutants.to_string(),
+            "--solc".into(),
+            self.solc_for(file_to_mutate),
+            "--comment-excerpt-len".into(),
+            p.comment_excerpt_len.to_string(),
+        ];
+        if let Some(bp) = &p.solc_basepath {
+            args.extend(["--solc-basepath".into(), bp.clone()]);
+        }
+        for r in p.solc_remapping.iter().flatten() {
+            args.extend(["--solc-remapping".into(), r.clone()]);
+        }
+        for g in &p.reentrancy_guard {
+            args.extend(["--reentrancy-guard".into(), g.clone()]);
+        }
+        if let Some(c) = contract {
+            args.extend(["--contract".into(), c.clone()]);
+        }
+        for f in funcs.iter().flatten() {
+            args.extend(["--functions".into(), f.clone()]);
+        }
+        if p.state_changing_only {
+            args.push("--state-changing-only".into());
+        }
+        if p.view_only {
+            args.push("--view-only".into());
+        }
+        if let Some(len) = p.max_path_len {
+            args.extend(["--max-path-len".into(), len.to_string()]);
+        }
+        if p.strategy != Strategy::RoundRobin {
+            let strategy = p.strategy.to_possible_value().unwrap();
+            args.extend(["--strategy".into(), strategy.get_name().into()]);
+        }
+        let rules = suppressor.rules();
+        if !rules.is_empty() {
+            args.extend(["--suppress".into(), serde_json::to_string(&rules).unwrap()]);
+        }
+        args.iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The checkpoint for starting on the file at `file_index`, after the
+    /// previous files yielded `done`.
+    /// When resuming, this is the checkpoint being resumed from for the
+    /// file it was written on.
+    fn checkpoint_for(
+        &self,
+        file_index: usize,
+        done: &Manifest,
+        resume: &mut Option<Checkpoint>,
+    ) -> Checkpoint {
+        match resume {
+            Some(ckpt) if ckpt.file_index == file_index => resume.take().unwrap(),
+            _ => Checkpoint {
+                generator: self.clone(),
+                file_index,
+                mutants: done.mutants.clone(),
+                stopped_reason: done.stopped_reason.clone(),
+                functions: done.functions.clone(),
+                stats: done.stats.clone(),
+                progress: None,
+            },
+        }
+    }
+
+    /// What the files before the one being resumed yielded, if any.
+    fn resumed_manifest(resume: &Option<Checkpoint>) -> Manifest {
+        resume
+            .as_ref()
+            .map_or_else(Manifest::default, |ckpt| Manifest {
+                mutants: ckpt.mutants.clone(),
+                stopped_reason: ckpt.stopped_reason.clone(),
+                functions: ckpt.functions.clone(),
+                stats: ckpt.stats.clone(),
+                ..Default::default()
+            })
+    }
+
     /// Generate mutations for a single file.
     /// Irrespective of how Gambit is used,
     /// this is the method which performs mutations
     /// on a single solidity file.
+    /// The file continues from the progress in `checkpoint`, if any,
+    /// which is written to `--checkpoint` after every mutant.
+    /// Returns the mutants along with why generating them stopped, how each
+    /// function fared and how the candidates of each mutation did.
     fn run_one(
         &self,
         file_to_mutate: &String,
-        muts: Option<Vec<String>>,
+        muts: Option<Vec<MutationType>>,
         funcs: Option<Vec<String>>,
         contract: Option<String>,
-    ) -> io::Result<()> {
-        let rand = self.rng.clone();
+        suppressor: Suppressor,
+        mut checkpoint: Checkpoint,
+    ) -> io::Result<FileOutcome> {
         let outdir = Path::new(&self.params.outdir);
+        // A preview must leave the output directory alone, AST included.
+        let ast_dir = match self.params.preview {
+            Some(_) => std::env::temp_dir().join(format!("gambit-preview-{}", std::process::id())),
+            None => outdir.to_path_buf(),
+        };
         let ast = self
-            .compile_solc(file_to_mutate, outdir.to_path_buf())
+            .compile_solc(file_to_mutate, ast_dir.clone())
             .ok()
             .unwrap();
-        let mut_types = muts.map_or(MutationType::value_variants().to_vec(), |ms| {
-            ms.iter()
-                .map(|m| {
-                    MutationType::from_str(m, true)
-                        .unwrap_or_else(|_| panic!("Could not generate mutant type from {}.", m))
-                })
-                .collect()
-        });
+        let mut_types = muts
+            .clone()
+            .unwrap_or_else(|| MutationType::value_variants().to_vec());
 
+        // Only what is left of `--max-total-mutants` after the files before.
+        let num_mutants = match self.params.max_total_mutants {
+            Some(cap) => {
+                let left = cap.saturating_sub(checkpoint.mutants.len());
+                self.params.num_mutants.min(left as i64)
+            }
+            None => self.params.num_mutants,
+        };
+        let repro_base =
+            self.repro_base(file_to_mutate, &funcs, &contract, &suppressor, num_mutants);
+        let solc = self.solc_for(file_to_mutate);
         let run_mutation = RunMutations {
             fnm: file_to_mutate.into(),
             node: ast,
-            num_mutants: self.params.num_mutants,
-            rand,
+            num_mutants,
+            seed: self.params.seed,
             out: outdir.to_path_buf(),
             mutation_types: mut_types,
             funcs_to_mutate: funcs,
             contract,
+            comment_excerpt_len: self.params.comment_excerpt_len,
+            checksum: self.params.checksum,
+            suppressor,
+            only_attempt: self.params.only_attempt,
+            only_point: self.params.only_point,
+            repro_base,
+            on_mutant: self.params.on_mutant.as_ref().map(|command| MutantHook {
+                command: command.clone(),
+                timeout: Duration::from_secs(self.params.on_mutant_timeout),
+                required: self.params.on_mutant_required,
+            }),
+            first_id: checkpoint.mutants.len() + 1,
+            resume: checkpoint.progress.take(),
+            checkpoint: self
+                .params
+                .checkpoint
+                .as_ref()
+                .map(|path| (PathBuf::from(path), checkpoint)),
+            emit: self.params.emit,
+            solc_version: solc_version(&solc),
+            solc: solc.clone(),
+            scope: if self.params.state_changing_only {
+                MutabilityScope::StateChanging
+            } else if self.params.view_only {
+                MutabilityScope::ReadOnly
+            } else {
+                MutabilityScope::All
+            },
+            reentrancy_guards: self.params.reentrancy_guard.clone(),
+            observer: self.observer.clone(),
+            claims: self.claims.clone(),
+            sampler: self.sampler.clone().unwrap_or_default(),
+            path_limit: self.path_limit(),
         };
+        if let Some(n) = self.params.preview {
+            print!("{}", run_mutation.preview(n)?);
+            fs::remove_dir_all(ast_dir)?;
+            return Ok((vec![], StoppedReason::Completed, vec![], BTreeMap::new()));
+        }
         log::info!("running mutations on file: {}", file_to_mutate);
+        self.observer.notify(|o| o.on_file_start(file_to_mutate));
 
         // This closure checks whether a mutant is valid
         // by invoking the solidity compiler on it.
@@ -231,14 +568,14 @@ impl MutantGenerator {
                         flags.push(r);
                     }
                 }
-                (valid, _, _) = invoke_command(&self.params.solc, flags)?;
+                (valid, _, _) = invoke_command(&solc, flags)?;
                 if tmp.exists() {
                     let _ = std::fs::remove_file(tmp);
                 }
             } else {
-                std::fs::write(&TMP, mutant)?;
+                std::fs::write(TMP, mutant)?;
                 flags.push(TMP);
-                (valid, _, _) = invoke_command(&self.params.solc, flags)?;
+                (valid, _, _) = invoke_command(&solc, flags)?;
                 std::fs::remove_file(TMP)?;
             }
             match valid {
@@ -247,37 +584,108 @@ impl MutantGenerator {
             }
         };
         match run_mutation.get_mutations(is_valid) {
-            Ok(_) => (),
+            Ok((mutants, stats, mut stopped)) => {
+                if num_mutants < self.params.num_mutants && stopped == StoppedReason::Completed {
+                    stopped = StoppedReason::MutantCap;
+                }
+                let report = FileReport {
+                    file: file_to_mutate.clone(),
+                    requested: muts,
+                    num_mutants: self.params.num_mutants,
+                    mutants: mutants.len(),
+                    stopped,
+                    stats,
+                };
+                self.observer.notify(|o| o.on_file_done(&report));
+                let functions = run_mutation.function_report(&mutants)?;
+                let stats = report
+                    .stats
+                    .into_iter()
+                    .map(|(m, s)| (m.to_string(), s))
+                    .collect();
+                Ok((mutants, stopped, functions, stats))
+            }
             Err(_) => panic!("Mutation generation failed."),
+        }
+    }
+
+    /// Parse the "mutations" (or "profile") of a config entry,
+    /// rejecting unknown names.
+    fn config_mutations(v: &Value) -> io::Result<Option<Vec<MutationType>>> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+        if let Some(profile) = v.get("profile") {
+            if v.get("mutations").is_some() {
+                return Err(invalid(
+                    "a config entry cannot have both \"mutations\" and \"profile\"".into(),
+                ));
+            }
+            let name = profile.as_str().unwrap();
+            let profile = Profile::from_str(name, true)
+                .map_err(|_| invalid(format!("unknown profile `{}`", name)))?;
+            return Ok(Some(profile.mutations()));
+        }
+        let Some(muts) = v.get("mutations") else {
+            return Ok(None);
         };
-        Ok(())
+        let mutts: Vec<MutationType> = muts
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| MutationType::parse_name(v.as_str().unwrap()).map_err(invalid))
+            .collect::<io::Result<_>>()?;
+        Ok(if mutts.is_empty() { None } else { Some(mutts) })
     }
 
     /// Run Gambit from a json config file.
     /// You can find examples of config files under `benchmarks/config-jsons/`.
     /// A configuration allows the user to have more control on
     /// which contracts and functions to mutate and using which kinds of mutations.
-    fn run_from_config(&mut self, cfg: &String) -> io::Result<()> {
+    /// When resuming, entries before the one `resume` was written on are
+    /// skipped, and the output directories are kept as they are.
+    fn run_from_config(
+        &mut self,
+        cfg: &String,
+        mut resume: Option<Checkpoint>,
+    ) -> io::Result<Manifest> {
         let cfg = Path::new(cfg);
         if !cfg.is_file() || !cfg.extension().unwrap().eq("json") {
             panic!("Must pass a .json config file with the --json argument or gambit-cfg alias. You can use the gambit alias instead!");
         }
-        self.mutant_dirs_from_json()?;
+        self.config_dir = cfg.parent().unwrap_or(Path::new("")).to_path_buf();
+        if resume.is_none() && self.params.preview.is_none() {
+            self.mutant_dirs_from_json()?;
+        }
         let f = File::open(cfg)?;
         let config: Value = serde_json::from_reader(BufReader::new(f))?;
-        let mut process_single_file = |v: &Value| -> io::Result<()> {
+        // Reject unknown mutations before spending any time on mutating.
+        match &config {
+            Value::Array(elems) => {
+                for elem in elems {
+                    Self::config_mutations(elem)?;
+                }
+            }
+            _ => {
+                Self::config_mutations(&config)?;
+            }
+        }
+        let mut manifest = Self::resumed_manifest(&resume);
+        let mut process_single_file = |i: usize, v: &Value| -> io::Result<()> {
+            if resume.as_ref().is_some_and(|ckpt| i < ckpt.file_index) {
+                return Ok(());
+            }
             if let Some(filename) = &v.get("filename") {
                 let mut funcs_to_mutate: Option<Vec<String>> = None;
-                let mut selected_muts: Option<Vec<String>> = None;
-                let fnm = filename.as_str().unwrap();
+                let selected_muts = Self::config_mutations(v)?;
+                let fnm = self.config_path(filename.as_str().unwrap());
                 if let Some(num) = &v.get("num-mutants") {
                     self.params.num_mutants = num.as_i64().unwrap();
                 }
-                if let Some(solc) = &v.get("solc") {
-                    self.params.solc = solc.as_str().unwrap().to_string();
-                }
+                // Unlike the other settings, the compiler of an entry
+                // does not carry over to later entries.
+                self.entry_solc = v.get("solc").map(|s| s.as_str().unwrap().to_string());
                 if let Some(solc_basepath) = &v.get("solc-basepath") {
-                    self.params.solc_basepath = solc_basepath.as_str().unwrap().to_string().into();
+                    self.params.solc_basepath =
+                        Some(self.config_path(solc_basepath.as_str().unwrap()));
                 }
                 if let Some(remap_args) = &v.get("remappings") {
                     let remaps: Vec<String> = remap_args
@@ -295,18 +703,13 @@ impl MutantGenerator {
                 }
                 let contract: Option<String> =
                     v.get("contract").map(|v| v.as_str().unwrap().to_string());
-
-                if let Some(muts) = &v.get("mutations") {
-                    let mutts: Vec<String> = muts
-                        .as_array()
-                        .unwrap()
-                        .iter()
-                        .map(|v| v.as_str().unwrap().to_string())
-                        .collect();
-                    if !mutts.is_empty() {
-                        selected_muts = mutts.into();
-                    }
+                let mut suppress_rules: Vec<SuppressRule> = vec![];
+                if let Some(rules) = v.get("suppress") {
+                    suppress_rules = serde_json::from_value(rules.clone())?;
                 }
+                let suppressor = Suppressor::new(suppress_rules)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
                 if let Some(funcs) = &v.get("functions") {
                     let fs: Vec<String> = funcs
                         .as_array()
@@ -318,21 +721,67 @@ impl MutantGenerator {
                         funcs_to_mutate = fs.into();
                     }
                 }
-                self.run_one(&fnm.to_string(), selected_muts, funcs_to_mutate, contract)?;
+                let checkpoint = self.checkpoint_for(i, &manifest, &mut resume);
+                let (file_mutants, stopped, functions, stats) = self.run_one(
+                    &fnm,
+                    selected_muts,
+                    funcs_to_mutate,
+                    contract,
+                    suppressor,
+                    checkpoint,
+                )?;
+                manifest.mutants.extend(file_mutants);
+                manifest.stopped_reason.insert(fnm.clone(), stopped);
+                manifest.functions.insert(fnm.clone(), functions);
+                manifest.stats.insert(fnm, stats);
             }
             Ok(())
         };
         match config {
             Value::Array(elems) => {
-                for elem in elems {
-                    process_single_file(&elem)?;
+                for (i, elem) in elems.iter().enumerate() {
+                    process_single_file(i, elem)?;
                 }
             }
             Value::Object(_) => {
-                process_single_file(&config)?;
+                process_single_file(0, &config)?;
             }
             _ => panic!("Ill-formed json."),
         }
+        Ok(manifest)
+    }
+
+    /// Write the manifest of all the mutants generated in this run, of why
+    /// generating them stopped and how each function fared for each file,
+    /// to the output directory. With `--checksum`, also write `CHECKSUMS.txt`
+    /// covering every mutant and the manifest itself, and sign it with
+    /// `--sign-command` if one is given.
+    fn write_manifest(&self, run: Manifest) -> io::Result<()> {
+        let outdir = Path::new(&self.params.outdir);
+        std::fs::create_dir_all(outdir)?;
+        let mut manifest = Manifest {
+            mutants: vec![],
+            ..run
+        };
+        for m in run.mutants {
+            manifest.push(m);
+        }
+        let body = manifest.write(outdir)?;
+        if self.params.emit == Emit::Sparse {
+            write_atomically(&outdir.join(SPARSE_MARKER), b"")?;
+        }
+        if self.params.checksum {
+            let mut checksums: Vec<(String, String)> = manifest
+                .mutants
+                .iter()
+                .map(|m| (m.sha256.clone().unwrap(), m.path.clone()))
+                .collect();
+            checksums.push((sha256_hex(&body), MANIFEST.to_string()));
+            write_checksums(outdir, &checksums)?;
+            if let Some(cmd) = &self.params.sign_command {
+                sign(cmd, &outdir.join(CHECKSUMS))?;
+            }
+        }
         Ok(())
     }
 
@@ -341,20 +790,152 @@ impl MutantGenerator {
     /// or using a config file (see examples under `benchmarks/config-jsons/`).
     pub fn run(&mut self) -> io::Result<()> {
         log::info!("starting run()");
-        let files = &self.params.filename;
+        let mut resume = None;
+        if let Some(path) = self.params.resume.clone() {
+            let ckpt = Checkpoint::read(Path::new(&path))?;
+            let checkpoint = self.params.checkpoint.clone();
+            let (observer, sampler) = (self.observer.clone(), self.sampler.clone());
+            *self = ckpt.generator.clone();
+            self.observer = observer;
+            self.sampler = sampler;
+            if checkpoint.is_some() {
+                self.params.checkpoint = checkpoint;
+            }
+            log::info!("resuming from {} at file {}", path, ckpt.file_index);
+            // What the run generated so far is not generated again.
+            let mut done = ckpt.mutants.clone();
+            done.extend(ckpt.progress.iter().flat_map(|p| p.mutants.clone()));
+            self.claims = Claims::resumed(Path::new(&self.params.outdir), &done);
+            resume = Some(ckpt);
+        }
+        if self.params.preview.is_none() {
+            self.remove_legacy_ast_dir()?;
+        }
+        // The files of the run share the strategy and its history.
+        if self.sampler.is_none() {
+            self.sampler = Some(Sampler::new(self.params.strategy.build()));
+        }
+        let files = &self.params.filename.clone();
         let json = &self.params.json.clone();
+        let mut manifest = Self::resumed_manifest(&resume);
         if files.is_some() {
-            for f in files.as_ref().unwrap() {
-                self.mk_mutant_dir(&f.to_string())?;
-                self.run_one(f, None, None, None)?;
+            let rules: Vec<SuppressRule> = match &self.params.suppress {
+                Some(s) => serde_json::from_str(s)?,
+                None => vec![],
+            };
+            let suppressor = Suppressor::new(rules)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            if resume.is_none() && self.params.preview.is_none() {
+                self.warn_shortened(files.as_ref().unwrap());
+                // Before any of them is mutated, since files in the same
+                // directory share their mutant directory.
+                if self.params.only_attempt.is_none() {
+                    for f in files.as_ref().unwrap() {
+                        self.mk_mutant_dir(f)?;
+                    }
+                }
+            }
+            for (i, f) in files.as_ref().unwrap().iter().enumerate() {
+                let resumed = resume.as_ref().map(|ckpt| ckpt.file_index);
+                if resumed.is_some_and(|j| i < j) {
+                    continue;
+                }
+                if self.params.preview.is_none() {
+                    // Keep the other mutants of the run around.
+                    let (mut_dir, _) =
+                        mutant_dir(Path::new(&self.params.outdir), f, self.path_limit());
+                    fs::create_dir_all(mut_dir.parent().unwrap())?;
+                }
+                let (file_mutants, stopped, functions, stats) = self.run_one(
+                    f,
+                    self.params
+                        .mutations
+                        .clone()
+                        .or_else(|| self.params.profile.map(|p| p.mutations())),
+                    self.params.functions.clone(),
+                    self.params.contract.clone(),
+                    suppressor.clone(),
+                    self.checkpoint_for(i, &manifest, &mut resume),
+                )?;
+                manifest.mutants.extend(file_mutants);
+                manifest.stopped_reason.insert(f.clone(), stopped);
+                manifest.functions.insert(f.clone(), functions);
+                manifest.stats.insert(f.clone(), stats);
             }
         } else if json.is_some() {
-            self.run_from_config(json.as_ref().unwrap())?;
+            manifest = self.run_from_config(json.as_ref().unwrap(), resume)?;
         } else {
             panic!("Must provide either --filename file.sol or --json config.json.")
         }
+        if self.params.only_attempt.is_some() || self.params.preview.is_some() {
+            // The manifest of the original run still describes the mutant,
+            // and a preview does not produce one.
+            return Ok(());
+        }
+        if let Some(cap) = self.params.max_total_mutants {
+            let capped = manifest
+                .stopped_reason
+                .values()
+                .filter(|s| **s == StoppedReason::MutantCap)
+                .count();
+            if capped > 0 {
+                let message = format!(
+                    "reached --max-total-mutants {}: {} of {} files got fewer mutants than requested",
+                    cap,
+                    capped,
+                    manifest.stopped_reason.len()
+                );
+                self.observer.notify(|o| o.on_warning(&message));
+            }
+        }
+        let outdir = Path::new(&self.params.outdir);
+        for f in manifest.stopped_reason.keys() {
+            if let (dir, true) = mutant_dir(outdir, f, self.path_limit()) {
+                let prefix = dir.strip_prefix(outdir).unwrap_or(&dir);
+                manifest
+                    .shortened
+                    .insert(f.clone(), prefix.to_string_lossy().to_string());
+            }
+        }
+        self.write_manifest(manifest)?;
         Ok(())
     }
+
+    /// The summary printed once the run is done, taking `elapsed`, see
+    /// `Manifest::render_summary`, or `None` with `--quiet` or if the run
+    /// did not write a manifest.
+    pub fn summary(&self, elapsed: Duration, color: bool) -> io::Result<Option<String>> {
+        if self.params.quiet || self.params.only_attempt.is_some() || self.params.preview.is_some()
+        {
+            return Ok(None);
+        }
+        let outdir = Path::new(&self.params.outdir);
+        let manifest = Manifest::read(outdir)?;
+        Ok(Some(manifest.render_summary(outdir, Some(elapsed), color)))
+    }
+
+    /// Every discrepancy between the output directory and the manifest
+    /// that was just written, see `verify::verify_output`, or `None`
+    /// without `--verify-output` or if the run did not write a manifest.
+    pub fn verify_output(&self) -> io::Result<Option<Vec<String>>> {
+        if !self.params.verify_output
+            || self.params.only_attempt.is_some()
+            || self.params.preview.is_some()
+        {
+            return Ok(None);
+        }
+        let outdir = Path::new(&self.params.outdir);
+        let mut skip = vec![PathBuf::from(AST_CACHE.split('/').next().unwrap())];
+        if let Some(rel) = self
+            .params
+            .checkpoint
+            .as_ref()
+            .and_then(|c| Path::new(c).strip_prefix(outdir).ok())
+        {
+            skip.push(rel.to_path_buf());
+        }
+        verify_output(outdir, &skip).map(Some)
+    }
 }
 
 ///
@@ -389,16 +970,1161 @@ pub struct MutationParams {
     /// Solidity binary name, e.g., --solc solc8.10, --solc 7.5, etc.
     #[arg(long, default_value = "solc")]
     pub solc: String,
+    /// Solidity binaries for the files matching a glob, used instead of
+    /// --solc, e.g., --solc-map 'contracts/legacy/**=solc-0.7.6,contracts/v2/**=solc-0.8.21'
+    #[arg(long, value_parser = SolcMap::parse)]
+    pub solc_map: Option<SolcMap>,
     /// Basepath argument to solc
     #[arg(long)]
     pub solc_basepath: Option<String>,
     /// Solidity remappings
     #[arg(long)]
     pub solc_remapping: Option<Vec<String>>,
+    /// Maximum number of characters of the original line quoted in the
+    /// comment added to each mutant
+    #[arg(long, default_value = "200")]
+    pub comment_excerpt_len: usize,
+    /// Record the SHA-256 of every mutant and of the manifest in CHECKSUMS.txt
+    #[arg(long)]
+    pub checksum: bool,
+    /// Shell command for signing CHECKSUMS.txt, where {} is replaced by its path,
+    /// e.g., --sign-command "gpg --detach-sign {}"
+    #[arg(long, requires = "checksum")]
+    pub sign_command: Option<String>,
+    /// Mutations to apply, e.g., --mutations binary-op-mutation (all if not given)
+    #[arg(long, conflicts_with = "json", value_parser = MutationType::parse_name)]
+    pub mutations: Option<Vec<MutationType>>,
+    /// Apply the mutations of a profile instead of all of them,
+    /// e.g., --profile fast
+    #[arg(long, conflicts_with_all = ["json", "mutations"])]
+    pub profile: Option<Profile>,
+    /// Functions to mutate (all if not given)
+    #[arg(long, conflicts_with = "json")]
+    pub functions: Option<Vec<String>>,
+    /// Contract to mutate (all if not given), either by name, e.g.,
+    /// --contract Token, or qualified by its file, e.g., --contract src/A.sol:Token
+    #[arg(long, conflicts_with = "json")]
+    pub contract: Option<String>,
+    /// Rules for suppressing mutation points, given as the json of the
+    /// "suppress" field of a config file
+    #[arg(long, conflicts_with = "json")]
+    pub suppress: Option<String>,
+    /// Only make the given attempt at generating a mutant, without clearing
+    /// the output directory or writing a manifest. This is what the
+    /// `repro_command` of a manifest entry uses to regenerate a single mutant
+    #[arg(long)]
+    pub only_attempt: Option<i64>,
+    /// Make the attempt of --only-attempt at the mutation point with this
+    /// index, as recorded in the `repro_command` of a manifest entry
+    #[arg(long, requires = "only_attempt")]
+    pub only_point: Option<usize>,
+    /// Shell command to run on every mutant as soon as it is written,
+    /// where {path}, {id} and {op} are replaced by the path of the mutant,
+    /// its id in the manifest and its mutation,
+    /// e.g., --on-mutant "slither {path}"
+    #[arg(long)]
+    pub on_mutant: Option<String>,
+    /// Seconds after which the --on-mutant command is killed
+    #[arg(long, default_value = "60")]
+    pub on_mutant_timeout: u64,
+    /// Discard mutants for which the --on-mutant command fails or times out,
+    /// and try to generate another one in their place
+    #[arg(long, requires = "on_mutant")]
+    pub on_mutant_required: bool,
+    /// File to save the state of the run to after every mutant,
+    /// from which it can be continued with --resume
+    #[arg(long, conflicts_with = "only_attempt")]
+    pub checkpoint: Option<String>,
+    /// Continue the run saved to the given --checkpoint file, with the
+    /// arguments it was started with. Must be run from the same directory
+    #[arg(long, conflicts_with_all = ["json", "filename", "only_attempt"])]
+    pub resume: Option<String>,
+    /// How to write mutants: in full, or only as edits recorded in the
+    /// manifest, which `gambit apply` turns into files when needed
+    #[arg(long, value_enum, default_value_t = Emit::Full, conflicts_with = "on_mutant")]
+    pub emit: Emit,
+    /// Do not mutate `view` and `pure` functions
+    #[arg(long, conflicts_with = "view_only")]
+    pub state_changing_only: bool,
+    /// Only mutate `view` and `pure` functions
+    #[arg(long)]
+    pub view_only: bool,
+    /// Print the diffs of up to N candidate mutants of every mutation,
+    /// without checking that they compile or writing anything to the
+    /// output directory, and exit
+    #[arg(long, value_name = "N", conflicts_with_all = ["resume", "checkpoint", "only_attempt", "on_mutant"])]
+    pub preview: Option<usize>,
+    /// Modifiers that guard against reentrancy, removed by
+    /// reentrancy-guard-mutation, e.g., --reentrancy-guard noReentrancy
+    #[arg(long, default_value = "nonReentrant")]
+    pub reentrancy_guard: Vec<String>,
+    /// Once done, check that the output directory is consistent with the
+    /// manifest and fail if it is not
+    #[arg(long)]
+    pub verify_output: bool,
+    /// How to pick the mutation to try next
+    #[arg(long, value_enum, default_value_t = Strategy::RoundRobin)]
+    pub strategy: Strategy,
+    /// Longest path of a mutant file, beyond which the mutants of a file
+    /// go to a shortened directory instead of mirroring its path
+    /// (the limit of the platform if not given)
+    #[arg(long)]
+    pub max_path_len: Option<usize>,
+    /// Stop writing mutants once this many were generated over the whole
+    /// run, unlike --num-mutants, which is per file. The files after that
+    /// are still analyzed and reported on in the manifest
+    #[arg(long)]
+    pub max_total_mutants: Option<usize>,
+    /// Do not print a summary of the mutants once done
+    #[arg(long)]
+    pub quiet: bool,
+}
+
+/// Command line arguments for verifying the checksums of generated mutants.
+#[derive(Debug, Clone, Parser, Deserialize, Serialize)]
+#[command(rename_all = "kebab-case")]
+pub struct VerifyParams {
+    /// Directory where the mutants were written
+    #[arg(long, default_value = "out")]
+    pub mutant_dir: String,
+}
+
+/// Command line arguments for writing out mutants emitted with `--emit sparse`.
+#[derive(Debug, Clone, Parser, Deserialize, Serialize)]
+#[command(rename_all = "kebab-case")]
+pub struct ApplyParams {
+    /// Directory where the mutants were written
+    #[arg(long, default_value = "out")]
+    pub mutant_dir: String,
+    /// Only write the mutant with this id
+    #[arg(long)]
+    pub id: Option<usize>,
+}
+
+/// Command line arguments for listing the mutants of a run.
+#[derive(Debug, Clone, Parser, Deserialize, Serialize)]
+#[command(rename_all = "kebab-case")]
+pub struct SummaryParams {
+    /// Directory where the mutants were written
+    #[arg(long, default_value = "out")]
+    pub mutant_dir: String,
+    /// Only show the mutant with this id
+    #[arg(long)]
+    pub id: Option<usize>,
+    /// Show the command that regenerates each mutant
+    #[arg(long)]
+    pub show_repro: bool,
 }
 
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
+#[allow(clippy::large_enum_variant)]
 pub enum Command {
     Mutate(MutationParams), // Maybe we want to do other things in the future like support checking mutants?
+    Verify(VerifyParams),
+    Summary(SummaryParams),
+    Apply(ApplyParams),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Set up `project/contracts/C.sol` under a fresh temporary directory
+    /// `name`, along with a stand-in for solc that emits the checked-in AST
+    /// and accepts every mutant. Returns the directory and the solc.
+    fn stub_project(name: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        let project = root.join("project");
+        fs::create_dir_all(project.join("contracts")).unwrap();
+        fs::copy(
+            "benchmarks/MultipleContracts/C.sol",
+            project.join("contracts/C.sol"),
+        )
+        .unwrap();
+        let solc = root.join("solc");
+        stub_solc(&solc);
+        (root, solc)
+    }
+
+    /// Write a stand-in for solc to `path` that emits the AST of
+    /// `C.sol`, accepts every mutant, reports no version, and logs its
+    /// arguments to `path.log`.
+    fn stub_solc(path: &Path) {
+        let ast = fs::canonicalize("benchmarks/MultipleContracts/C.json").unwrap();
+        fs::write(
+            path,
+            format!(
+                "#!/bin/sh\n[ \"$1\" = --version ] && exit\necho \"$@\" >> \"$0.log\"\nif [ \"$1\" = --ast-compact-json ]; then\n  mkdir -p \"$4\" && cp {} \"$4/$(basename \"$2\")_json.ast\"\nfi\n",
+                shell_quote(ast.to_str().unwrap())
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    /// Collects the warnings of a run.
+    struct Warnings(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl GambitObserver for Warnings {
+        fn on_warning(&mut self, message: &str) {
+            self.0.borrow_mut().push(message.to_string());
+        }
+    }
+
+    #[test]
+    fn test_config_paths_relative_to_config() {
+        let (root, solc) = stub_project("gambit_test_config_paths");
+        let project = root.join("project");
+        let config = project.join("gambit.json");
+        fs::write(
+            &config,
+            serde_json::json!({
+                "filename": "contracts/C.sol",
+                "solc": solc,
+                "num-mutants": 2,
+                "mutations": ["binary-op-mutation"],
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        // The working directory of the test is the root of the crate,
+        // not the directory of the config file.
+        let out = root.join("out");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--json",
+            config.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        assert!(!manifest.mutants.is_empty());
+        for m in &manifest.mutants {
+            assert_eq!(
+                m.original,
+                project.join("contracts/C.sol").to_str().unwrap()
+            );
+            assert!(out.join(&m.path).is_file());
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let (root, solc) = stub_project("gambit_test_resume");
+        let project = root.join("project");
+        let out = root.join("out");
+        let checkpoint = root.join("checkpoint.json");
+        // Just before each mutant is checkpointed, keep a copy of the
+        // checkpoint, which ends up saving the run one mutant short.
+        let hook = format!(
+            "cp {} {} || true",
+            checkpoint.to_str().unwrap(),
+            root.join("partial.json").to_str().unwrap()
+        );
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            project.join("contracts/C.sol").to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "4",
+            "--checkpoint",
+            checkpoint.to_str().unwrap(),
+            "--on-mutant",
+            &hook,
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        assert_eq!(manifest.mutants.len(), 4);
+        let last = out.join(&manifest.mutants[3].path);
+        let contents = fs::read_to_string(&last).unwrap();
+
+        let partial = Checkpoint::read(&root.join("partial.json")).unwrap();
+        assert_eq!(partial.file_index, 0);
+        assert_eq!(partial.progress.unwrap().mutants.len(), 3);
+        fs::remove_file(&last).unwrap();
+        fs::remove_file(out.join(MANIFEST)).unwrap();
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--resume",
+            root.join("partial.json").to_str().unwrap(),
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        assert_eq!(Manifest::read(&out).unwrap(), manifest);
+        assert_eq!(fs::read_to_string(&last).unwrap(), contents);
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_sparse_emit_materializes_like_full() {
+        let (root, solc) = stub_project("gambit_test_sparse_emit");
+        let project = root.join("project");
+        let run = |outdir: &Path, emit: &str| {
+            let params = MutationParams::parse_from([
+                "gambit",
+                "--filename",
+                project.join("contracts/C.sol").to_str().unwrap(),
+                "--solc",
+                solc.to_str().unwrap(),
+                "--solc-basepath",
+                project.to_str().unwrap(),
+                "--outdir",
+                outdir.to_str().unwrap(),
+                "--num-mutants",
+                "3",
+                "--mutations",
+                "binary-op-mutation",
+                "--emit",
+                emit,
+            ]);
+            MutantGenerator::new(params).run().unwrap();
+            Manifest::read(outdir).unwrap()
+        };
+        let (full_dir, sparse_dir) = (root.join("full"), root.join("sparse"));
+        let full = run(&full_dir, "full");
+        let sparse = run(&sparse_dir, "sparse");
+        assert_eq!(full.mutants.len(), 3);
+        assert!(sparse_dir.join(SPARSE_MARKER).is_file());
+        assert!(!full_dir.join(SPARSE_MARKER).exists());
+        for (f, s) in full.mutants.iter().zip(&sparse.mutants) {
+            assert_eq!(f.path, s.path);
+            assert!(f.edit.is_none());
+            assert!(!sparse_dir.join(&s.path).exists());
+            materialize(&sparse_dir, s).unwrap();
+            assert_eq!(
+                fs::read(sparse_dir.join(&s.path)).unwrap(),
+                fs::read(full_dir.join(&f.path)).unwrap()
+            );
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_solc_per_file() {
+        let (root, _) = stub_project("gambit_test_solc_per_file");
+        let project = root.join("project");
+        for dir in ["legacy", "v2"] {
+            fs::create_dir_all(project.join("contracts").join(dir)).unwrap();
+            fs::copy(
+                project.join("contracts/C.sol"),
+                project.join("contracts").join(dir).join("C.sol"),
+            )
+            .unwrap();
+        }
+        let (legacy, v2) = (
+            project.join("contracts/legacy/C.sol"),
+            project.join("contracts/v2/C.sol"),
+        );
+        let (solc_a, solc_b) = (root.join("solc-0.7.6"), root.join("solc-0.8.21"));
+        stub_solc(&solc_a);
+        stub_solc(&solc_b);
+        let log = |solc: &Path| {
+            let mut log = solc.as_os_str().to_owned();
+            log.push(".log");
+            fs::read_to_string(&log).unwrap_or_default()
+        };
+        let clear_logs = || {
+            for solc in [&solc_a, &solc_b] {
+                let mut log = solc.as_os_str().to_owned();
+                log.push(".log");
+                fs::remove_file(&log).ok();
+            }
+        };
+        let check = |manifest: Manifest| {
+            let (log_a, log_b) = (log(&solc_a), log(&solc_b));
+            assert!(!log_a.is_empty() && !log_b.is_empty());
+            assert!(
+                log_a.lines().all(|l| l.contains("contracts/legacy/")),
+                "{}",
+                log_a
+            );
+            assert!(
+                log_b.lines().all(|l| l.contains("contracts/v2/")),
+                "{}",
+                log_b
+            );
+            assert_eq!(manifest.mutants.len(), 4);
+            for m in &manifest.mutants {
+                let expected = if m.original.contains("legacy") {
+                    &solc_a
+                } else {
+                    &solc_b
+                };
+                assert_eq!(m.solc, expected.to_str().unwrap());
+            }
+        };
+
+        let out = root.join("out");
+        let map = format!(
+            "contracts/legacy/**={},contracts/v2/**={}",
+            solc_a.to_str().unwrap(),
+            solc_b.to_str().unwrap()
+        );
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            legacy.to_str().unwrap(),
+            "--filename",
+            v2.to_str().unwrap(),
+            "--solc-map",
+            &map,
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "2",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        check(Manifest::read(&out).unwrap());
+
+        // The compiler of a config entry does not leak into the next one.
+        clear_logs();
+        let config = project.join("gambit.json");
+        fs::write(
+            &config,
+            serde_json::json!([
+                {"filename": "contracts/legacy/C.sol", "solc": solc_a, "solc-basepath": ".", "num-mutants": 2},
+                {"filename": "contracts/v2/C.sol", "solc-basepath": ".", "num-mutants": 2},
+            ])
+            .to_string(),
+        )
+        .unwrap();
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--json",
+            config.to_str().unwrap(),
+            "--solc",
+            solc_b.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        check(Manifest::read(&out).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_same_named_files_keep_their_asts() {
+        let root = std::env::temp_dir().join("gambit_test_ast_cache");
+        let _ = fs::remove_dir_all(&root);
+        // Both paths used to map to `input_json/.../p/x/C.sol`, and `c` has
+        // the same contents as `a`.
+        let (a, b, c) = (
+            root.join("p/x/C.sol"),
+            root.join("x/C.sol"),
+            root.join("y/C.sol"),
+        );
+        for (i, sol) in [&a, &b, &c].into_iter().enumerate() {
+            fs::create_dir_all(sol.parent().unwrap()).unwrap();
+            fs::write(sol, format!("contract C{} {{}}\n", i % 2)).unwrap();
+            let ast = serde_json::json!({"nodeType": "SourceUnit", "id": i});
+            fs::write(sol.with_extension("json"), ast.to_string()).unwrap();
+        }
+        let solc = root.join("solc");
+        fs::write(
+            &solc,
+            "#!/bin/sh\nmkdir -p \"$4\" && cp \"${2%.sol}.json\" \"$4/$(basename \"$2\")_json.ast\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&solc, fs::Permissions::from_mode(0o755)).unwrap();
+        let out = root.join("out");
+        let legacy_ast = out.join(LEGACY_AST_DIR).join("p/x/C.sol/C.sol_json.ast");
+        fs::create_dir_all(legacy_ast.parent().unwrap()).unwrap();
+        fs::write(&legacy_ast, "{}").unwrap();
+        let mut generator = MutantGenerator::new(MutationParams::parse_from([
+            "gambit",
+            "--solc",
+            solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+        ]));
+        let warnings = Default::default();
+        generator.set_observer(Box::new(Warnings(std::rc::Rc::clone(&warnings))));
+        let b = root.join("p/../x/C.sol");
+        let id = |sol: &Path| {
+            generator
+                .compile_solc(&sol.to_str().unwrap().to_string(), out.clone())
+                .unwrap()
+                .get_object()
+                .unwrap()["id"]
+                .clone()
+        };
+        // An output directory of an older version is migrated.
+        generator.remove_legacy_ast_dir().unwrap();
+        assert!(!out.join(LEGACY_AST_DIR).exists());
+        let migrated = warnings.take();
+        assert_eq!(migrated.len(), 1);
+        assert!(migrated[0].starts_with("migrating "));
+        assert_eq!(id(&a), 0);
+        assert_eq!(id(&b), 1);
+        assert_eq!(id(&c), 2);
+        assert_eq!(id(&a), 0);
+        let cached: Vec<_> = fs::read_dir(out.join(AST_CACHE)).unwrap().collect();
+        assert_eq!(cached.len(), 3);
+        // Another compiler gets an AST of its own.
+        let other_solc = root.join("solc2");
+        fs::copy(&solc, &other_solc).unwrap();
+        fs::write(
+            c.with_extension("json"),
+            r#"{"nodeType": "SourceUnit", "id": 3}"#,
+        )
+        .unwrap();
+        let other = MutantGenerator::new(MutationParams::parse_from([
+            "gambit",
+            "--solc",
+            other_solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+        ]));
+        let ast = other
+            .compile_solc(&c.to_str().unwrap().to_string(), out.clone())
+            .unwrap();
+        assert_eq!(ast.get_object().unwrap()["id"], 3);
+        assert_eq!(id(&c), 2);
+
+        // Neither once migrated, nor if there is anything but ASTs in it.
+        fs::create_dir_all(legacy_ast.parent().unwrap()).unwrap();
+        fs::write(&legacy_ast, "{}").unwrap();
+        generator.remove_legacy_ast_dir().unwrap();
+        assert!(legacy_ast.exists());
+        fs::remove_dir_all(out.join(AST_CACHE.split('/').next().unwrap())).unwrap();
+        fs::write(out.join(LEGACY_AST_DIR).join("notes.txt"), "").unwrap();
+        generator.remove_legacy_ast_dir().unwrap();
+        assert!(legacy_ast.exists());
+        assert!(warnings.take().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_observer_events() {
+        use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+        /// Records every event as a line.
+        struct Recorder(Rc<RefCell<Vec<String>>>);
+        impl GambitObserver for Recorder {
+            fn on_file_start(&mut self, file: &str) {
+                self.0.borrow_mut().push(format!("start {}", file));
+            }
+            fn on_point_collection(&mut self, counts: &HashMap<MutationType, usize>) {
+                let points: usize = counts.values().sum();
+                self.0.borrow_mut().push(format!("points {}", points));
+            }
+            fn on_candidate(
+                &mut self,
+                op: MutationType,
+                _: (usize, usize),
+                outcome: CandidateOutcome,
+            ) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("candidate {} {:?}", op, outcome));
+            }
+            fn on_mutant_accepted(&mut self, mutant: &MutantEntry) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("mutant {}", mutant.mutation));
+            }
+            fn on_file_done(&mut self, report: &FileReport) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("done {} {:?}", report.mutants, report.stopped));
+            }
+        }
+
+        let (root, solc) = stub_project("gambit_test_observer");
+        let project = root.join("project");
+        let sol = project.join("contracts/C.sol");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            sol.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            root.join("out").to_str().unwrap(),
+            "--num-mutants",
+            "2",
+            "--mutations",
+            "binary-op-mutation",
+        ]);
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut generator = MutantGenerator::new(params);
+        generator.set_observer(Box::new(Recorder(events.clone())));
+        generator.run().unwrap();
+
+        let events = events.borrow();
+        assert_eq!(events[0], format!("start {}", sol.to_str().unwrap()));
+        assert_eq!(events[1], "points 2");
+        assert_eq!(
+            events[2..],
+            [
+                "candidate BinaryOpMutation Accepted",
+                "mutant BinaryOpMutation",
+                "candidate BinaryOpMutation Accepted",
+                "mutant BinaryOpMutation",
+                "done 2 Completed",
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_same_file_under_two_paths_is_mutated_once() {
+        let (root, solc) = stub_project("gambit_test_claims");
+        let project = root.join("project");
+        let out = root.join("out");
+        let sol = project.join("contracts/C.sol");
+        let again = project.join("contracts/../contracts/C.sol");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            sol.to_str().unwrap(),
+            "--filename",
+            again.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--mutations",
+            "binary-op-mutation",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        let originals: HashSet<&str> = manifest
+            .mutants
+            .iter()
+            .map(|m| m.original.as_str())
+            .collect();
+        assert_eq!(originals.len(), 2);
+        let mutants: HashSet<String> = manifest
+            .mutants
+            .iter()
+            .map(|m| fs::read_to_string(out.join(&m.path)).unwrap())
+            .collect();
+        assert_eq!(mutants.len(), manifest.mutants.len());
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_same_file_under_two_paths_is_mutated_once_when_resumed() {
+        let (root, solc) = stub_project("gambit_test_claims_resumed");
+        let project = root.join("project");
+        let out = root.join("out");
+        let sol = project.join("contracts/C.sol");
+        let again = project.join("contracts/../contracts/C.sol");
+        let checkpoint = root.join("checkpoint.json");
+        // Keep the checkpoint from before each mutant, by its id.
+        let hook = format!(
+            "cp {} {}/partial_{{id}}.json || true",
+            checkpoint.to_str().unwrap(),
+            root.to_str().unwrap()
+        );
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            sol.to_str().unwrap(),
+            "--filename",
+            again.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--mutations",
+            "binary-op-mutation",
+            "--checkpoint",
+            checkpoint.to_str().unwrap(),
+            "--on-mutant",
+            &hook,
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        let first = manifest
+            .mutants
+            .iter()
+            .position(|m| m.original == again.to_str().unwrap())
+            .unwrap();
+        assert!(first > 0);
+
+        // Resumed after the last mutant of the first path, its mutants are
+        // still not generated again for the second path.
+        let partial = root.join(format!("partial_{}.json", first + 1));
+        let progress = Checkpoint::read(&partial).unwrap().progress.unwrap();
+        assert_eq!(progress.mutants.len(), first);
+        for m in &manifest.mutants[first..] {
+            fs::remove_file(out.join(&m.path)).unwrap();
+        }
+        fs::remove_file(out.join(MANIFEST)).unwrap();
+        let params = MutationParams::parse_from(["gambit", "--resume", partial.to_str().unwrap()]);
+        MutantGenerator::new(params).run().unwrap();
+        assert_eq!(Manifest::read(&out).unwrap(), manifest);
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_files_with_the_same_contents_are_mutated_independently() {
+        let (root, solc) = stub_project("gambit_test_claims_same_contents");
+        let project = root.join("project");
+        let out = root.join("out");
+        // Only the source unit of the file itself is mutated, so the points
+        // of `B.sol` are not reached through the import in `A.sol`. The
+        // stand-in for solc gives both files the AST of `C.sol`, so both
+        // keep its contents, and their mutants are the same but for the
+        // file they belong to, which claims tell apart.
+        let c = fs::read_to_string(project.join("contracts/C.sol")).unwrap();
+        let a = project.join("contracts/A.sol");
+        let b = project.join("contracts/B.sol");
+        fs::write(&a, format!("{}\nimport \"./B.sol\";\n", c)).unwrap();
+        fs::write(&b, format!("{}\n// B\n", c)).unwrap();
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            a.to_str().unwrap(),
+            "--filename",
+            b.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--mutations",
+            "binary-op-mutation",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        let mut mutants = HashSet::new();
+        for sol in [&a, &b] {
+            for m in manifest
+                .mutants
+                .iter()
+                .filter(|m| m.original == sol.to_str().unwrap())
+            {
+                let mutant = fs::read_to_string(out.join(&m.path)).expect(&m.path);
+                assert!(mutants.insert((m.original.clone(), mutant)), "{:?}", m);
+            }
+        }
+        assert_eq!(mutants.len(), 6);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_imported_files_are_not_mutated_twice() {
+        let (root, solc) = stub_project("gambit_test_claims_imports");
+        let project = root.join("project");
+        let out = root.join("out");
+        // `A.sol` imports `B.sol`, and both are given. The mutants of `B.sol`
+        // come from its own source unit only, never from the one of `A.sol`.
+        let c = fs::read_to_string(project.join("contracts/C.sol")).unwrap();
+        let a = project.join("contracts/A.sol");
+        let b = project.join("contracts/B.sol");
+        fs::write(&a, format!("{}\nimport \"./B.sol\";\n", c)).unwrap();
+        fs::write(&b, format!("{}\n// B\n", c)).unwrap();
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            a.to_str().unwrap(),
+            "--filename",
+            b.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        let mut of_b = HashSet::new();
+        for m in &manifest.mutants {
+            let mutant = fs::read_to_string(out.join(&m.path)).expect(&m.path);
+            if mutant.ends_with("\n// B\n") {
+                assert_eq!(m.original, b.to_str().unwrap(), "{:?}", m);
+                assert!(of_b.insert(mutant), "{:?}", m);
+            }
+        }
+        assert_eq!(of_b.len(), 3);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_shorten_paths_that_are_too_long() {
+        let (root, solc) = stub_project("gambit_test_shorten_paths");
+        let deep = root
+            .join("project")
+            .join("contracts/very/deep/path".repeat(6));
+        fs::create_dir_all(&deep).unwrap();
+        let sol = deep.join("C.sol");
+        fs::copy("benchmarks/MultipleContracts/C.sol", &sol).unwrap();
+        let out = root.join("out");
+        // Room for the hashed directory, but not for the whole path.
+        let limit = out.to_str().unwrap().len() + 60;
+        assert!(
+            out.join(sol.strip_prefix("/").unwrap())
+                .to_str()
+                .unwrap()
+                .len()
+                > limit
+        );
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            sol.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--max-path-len",
+            &limit.to_string(),
+            "--verify-output",
+        ]);
+        let warnings = Default::default();
+        let mut generator = MutantGenerator::new(params);
+        generator.set_observer(Box::new(Warnings(std::rc::Rc::clone(&warnings))));
+        generator.run().unwrap();
+        let warnings = warnings.take();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("paths too long"));
+        assert!(warnings[0].contains(sol.to_str().unwrap()));
+        let manifest = Manifest::read(&out).unwrap();
+        let prefix = &manifest.shortened[sol.to_str().unwrap()];
+        assert!(prefix.starts_with('~'));
+        assert!(prefix.ends_with("/C.sol"));
+        assert_eq!(manifest.mutants.len(), 3);
+        for m in &manifest.mutants {
+            assert!(m.path.starts_with(prefix.as_str()));
+            assert!(out.join(&m.path).to_str().unwrap().len() <= limit);
+            assert!(out.join(&m.path).is_file());
+        }
+        // Replaying a mutant writes it to the same shortened path.
+        let m = &manifest.mutants[0];
+        let path = out.join(&m.path);
+        let mutant = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let args = m.repro_command.split_whitespace().skip(2);
+        let replay = MutationParams::parse_from(["gambit"].into_iter().chain(args));
+        MutantGenerator::new(replay).run().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), mutant);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_max_total_mutants() {
+        let (root, solc) = stub_project("gambit_test_max_total_mutants");
+        let contracts = root.join("project/contracts");
+        fs::copy(contracts.join("C.sol"), contracts.join("D.sol")).unwrap();
+        let (c, d) = (contracts.join("C.sol"), contracts.join("D.sol"));
+        let out = root.join("out");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            c.to_str().unwrap(),
+            "--filename",
+            d.to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--max-total-mutants",
+            "4",
+        ]);
+        let warnings = Default::default();
+        let mut generator = MutantGenerator::new(params);
+        generator.set_observer(Box::new(Warnings(std::rc::Rc::clone(&warnings))));
+        generator.run().unwrap();
+        assert_eq!(
+            warnings.take(),
+            ["reached --max-total-mutants 4: 1 of 2 files got fewer mutants than requested"]
+        );
+        let manifest = Manifest::read(&out).unwrap();
+        let (c, d) = (c.to_str().unwrap(), d.to_str().unwrap());
+        assert_eq!(manifest.mutants.len(), 4);
+        assert_eq!(
+            manifest.mutants.iter().filter(|m| m.original == d).count(),
+            1
+        );
+        assert_eq!(manifest.stopped_reason[c], StoppedReason::Completed);
+        assert_eq!(manifest.stopped_reason[d], StoppedReason::MutantCap);
+        assert!(manifest.mutants.iter().any(|m| m
+            .repro_command
+            .contains(&format!("--filename {} --outdir", d))
+            && m.repro_command.contains("--num-mutants 1 ")));
+
+        // The points of both files are reported, even past the cap.
+        let points = |f: &str| {
+            manifest.functions[f]
+                .iter()
+                .map(|r| r.points)
+                .sum::<usize>()
+        };
+        assert!(points(c) > 0);
+        assert_eq!(points(c), points(d));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let stats = |points, attempts, accepted, duplicates, invalid| MutationStats {
+            points,
+            attempts,
+            accepted,
+            duplicates,
+            invalid,
+            ..Default::default()
+        };
+        let mutant = |original: &str| MutantEntry {
+            original: original.into(),
+            ..Default::default()
+        };
+        let manifest = Manifest {
+            mutants: vec![mutant("A.sol"), mutant("A.sol"), mutant("B.sol")],
+            stopped_reason: BTreeMap::from([
+                ("A.sol".into(), StoppedReason::Completed),
+                ("B.sol".into(), StoppedReason::AttemptsExhausted),
+            ]),
+            stats: BTreeMap::from([
+                (
+                    "A.sol".into(),
+                    BTreeMap::from([
+                        ("BinaryOpMutation".into(), stats(4, 3, 2, 0, 1)),
+                        ("RequireMutation".into(), stats(0, 0, 0, 0, 0)),
+                    ]),
+                ),
+                (
+                    "B.sol".into(),
+                    BTreeMap::from([
+                        ("AssignmentMutation".into(), stats(1, 9, 1, 3, 5)),
+                        ("IfStatementMutation".into(), stats(2, 2, 0, 2, 0)),
+                    ]),
+                ),
+            ]),
+            ..Default::default()
+        };
+        let summary =
+            manifest.render_summary(Path::new("out"), Some(Duration::from_millis(1500)), false);
+        assert_eq!(
+            summary,
+            "A.sol: 2 mutants (completed)
+  mutation          accepted  tried  invalid
+  BinaryOpMutation         2      3        1
+B.sol: 1 mutant (attempts_exhausted)
+  mutation             accepted  tried  invalid
+  AssignmentMutation          1      9        5
+  IfStatementMutation         0      2        0
+  rejected: 5 did not compile, 5 were duplicates
+output: out
+manifest: out/manifest.json
+elapsed: 1.50s
+"
+        );
+        assert!(!manifest
+            .render_summary(Path::new("out"), None, false)
+            .contains("elapsed"));
+        assert!(manifest
+            .render_summary(Path::new("out"), None, true)
+            .contains("\u{1b}[1mA.sol\u{1b}[0m"));
+
+        // A run records the stats, and --quiet leaves out the summary.
+        let (root, solc) = stub_project("gambit_test_render_summary");
+        let out = root.join("out");
+        let args = |quiet: bool| {
+            let mut args = vec![
+                "gambit".to_string(),
+                "--filename".into(),
+                root.join("project/contracts/C.sol")
+                    .to_str()
+                    .unwrap()
+                    .into(),
+                "--solc".into(),
+                solc.to_str().unwrap().into(),
+                "--outdir".into(),
+                out.to_str().unwrap().into(),
+                "--num-mutants".into(),
+                "2".into(),
+            ];
+            if quiet {
+                args.push("--quiet".into());
+            }
+            MutationParams::parse_from(args)
+        };
+        let mut generator = MutantGenerator::new(args(false));
+        generator.run().unwrap();
+        let summary = generator.summary(Duration::ZERO, false).unwrap().unwrap();
+        assert!(summary.contains(": 2 mutants (completed)\n  mutation"));
+        assert!(summary.ends_with("elapsed: 0.00s\n"));
+        assert!(MutantGenerator::new(args(true))
+            .summary(Duration::ZERO, false)
+            .unwrap()
+            .is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_repro_command_with_strategy() {
+        let (root, solc) = stub_project("gambit_test_repro_strategy");
+        let out = root.join("out");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            root.join("project/contracts/C.sol").to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "6",
+            "--strategy",
+            "weighted",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        assert_eq!(manifest.mutants.len(), 6);
+        for m in &manifest.mutants {
+            assert!(m.repro_command.contains(" --strategy weighted"));
+            let path = out.join(&m.path);
+            let mutant = fs::read_to_string(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+            let args = m.repro_command.split_whitespace().skip(2);
+            let replay = MutationParams::parse_from(["gambit"].into_iter().chain(args));
+            MutantGenerator::new(replay).run().unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), mutant);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_no_repro_command_with_history_dependent_strategy() {
+        let (root, solc) = stub_project("gambit_test_repro_epsilon_greedy");
+        let out = root.join("out");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            root.join("project/contracts/C.sol").to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--strategy",
+            "epsilon-greedy",
+        ]);
+        MutantGenerator::new(params).run().unwrap();
+        let manifest = Manifest::read(&out).unwrap();
+        assert_eq!(manifest.mutants.len(), 3);
+        assert!(manifest.mutants.iter().all(|m| m.repro_command.is_empty()));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_verify_output() {
+        let (root, solc) = stub_project("gambit_test_verify_output");
+        let project = root.join("project");
+        let out = root.join("out");
+        let params = MutationParams::parse_from([
+            "gambit",
+            "--filename",
+            project.join("contracts/C.sol").to_str().unwrap(),
+            "--solc",
+            solc.to_str().unwrap(),
+            "--solc-basepath",
+            project.to_str().unwrap(),
+            "--outdir",
+            out.to_str().unwrap(),
+            "--num-mutants",
+            "3",
+            "--checksum",
+            "--verify-output",
+        ]);
+        let generator = MutantGenerator::new(params);
+        generator.clone().run().unwrap();
+        let verify = || generator.verify_output();
+        let discrepancies = || verify_output(&out, &[PathBuf::from(".gambit_cache")]).unwrap();
+        assert_eq!(verify().unwrap(), Some(vec![]));
+        let manifest = Manifest::read(&out).unwrap();
+        assert_eq!(manifest.mutants.len(), 3);
+        let (first, second, third) = (
+            &manifest.mutants[0],
+            &manifest.mutants[1],
+            &manifest.mutants[2],
+        );
+
+        // A mutant that is missing.
+        let missing = out.join(&first.path);
+        let contents = fs::read(&missing).unwrap();
+        fs::remove_file(&missing).unwrap();
+        assert_eq!(discrepancies(), vec![format!("{}: missing", first.path)]);
+        assert_eq!(verify().unwrap(), Some(discrepancies()));
+        fs::write(&missing, &contents).unwrap();
+
+        // An original that was moved away.
+        let original = project.join("contracts/C.sol");
+        fs::rename(&original, original.with_extension("moved")).unwrap();
+        let found = discrepancies();
+        assert_eq!(found.len(), 3);
+        for (d, m) in found.iter().zip(&manifest.mutants) {
+            assert!(
+                d.starts_with(&format!("{}: cannot read the original", m.path)),
+                "{}",
+                d
+            );
+        }
+        fs::rename(original.with_extension("moved"), &original).unwrap();
+
+        // A stray file next to the mutants.
+        let stray = Path::new(&second.path).with_file_name("stray.sol");
+        fs::write(out.join(&stray), "contract Stray {}").unwrap();
+        assert_eq!(
+            discrepancies(),
+            vec![format!(
+                "{}: not a mutant in {}",
+                stray.to_str().unwrap(),
+                MANIFEST
+            )]
+        );
+        fs::remove_file(out.join(&stray)).unwrap();
+
+        // A mutant that was edited after it was written.
+        let edited = out.join(&third.path);
+        let contents = fs::read_to_string(&edited).unwrap();
+        fs::write(&edited, contents.clone() + "\n// edited\n").unwrap();
+        let found = discrepancies();
+        assert_eq!(found.len(), 2);
+        assert!(found[0].starts_with(&format!("{}: expected", third.path)));
+        assert!(found[1].starts_with(&format!("{}: changes bytes", third.path)));
+        assert!(found[1].contains("but the manifest records"));
+        fs::write(&edited, contents).unwrap();
+
+        // Ids that are not unique or sequential.
+        let mut renumbered = manifest.clone();
+        renumbered.mutants[1].id = 1;
+        renumbered.mutants[2].id = 5;
+        renumbered.write(&out).unwrap();
+        let found = discrepancies();
+        assert!(found.contains(&"id 1 is used by more than one mutant".to_string()));
+        assert!(found.contains(&"ids are not sequential: expected 3 but found 5".to_string()));
+        manifest.write(&out).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -1,7 +1,14 @@
-use std::{io, time::Instant};
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+    time::Instant,
+};
 
 use clap::Parser;
-use gambit::{Command, MutantGenerator};
+use gambit::{
+    materialize, verify_checksums, Command, FunctionReport, Manifest, MutantEntry, MutantGenerator,
+    StderrReporter,
+};
 
 /// Entry point
 fn main() -> io::Result<()> {
@@ -10,9 +17,96 @@ fn main() -> io::Result<()> {
     match Command::parse() {
         Command::Mutate(params) => {
             let mut mutant_gen = MutantGenerator::new(params);
+            mutant_gen.set_observer(Box::new(StderrReporter));
             mutant_gen.run()?;
+            if let Some(discrepancies) = mutant_gen.verify_output()? {
+                let outdir = Path::new(&mutant_gen.params.outdir);
+                if !discrepancies.is_empty() {
+                    for d in &discrepancies {
+                        eprintln!("{}", d);
+                    }
+                    eprintln!(
+                        "{} discrepancies in output directory {}",
+                        discrepancies.len(),
+                        outdir.display()
+                    );
+                    std::process::exit(1);
+                }
+                let mutants = Manifest::read(outdir)?.mutants.len();
+                println!("output verified: {} mutants consistent", mutants);
+            }
+            if let Some(summary) = mutant_gen.summary(start.elapsed(), use_color())? {
+                print!("{}", summary);
+            }
+        }
+        Command::Verify(params) => {
+            let mismatches = verify_checksums(Path::new(&params.mutant_dir))?;
+            if !mismatches.is_empty() {
+                for m in &mismatches {
+                    eprintln!("{}", m);
+                }
+                eprintln!("{} file(s) failed verification.", mismatches.len());
+                std::process::exit(1);
+            }
+            println!("All checksums match.");
+        }
+        Command::Summary(params) => {
+            let manifest = Manifest::read(Path::new(&params.mutant_dir))?;
+            for m in select(&manifest, params.id, &params.mutant_dir)? {
+                let origin = m.contract.as_ref().unwrap_or(&m.original);
+                let flag = if m.security_critical {
+                    " [security critical]"
+                } else {
+                    ""
+                };
+                println!("{}: {} {} -> {}{}", m.id, m.mutation, origin, m.path, flag);
+                if params.show_repro && m.repro_command.is_empty() {
+                    println!("    (not reproducible with this strategy)");
+                } else if params.show_repro {
+                    println!("    {}", m.repro_command);
+                }
+            }
+            if params.id.is_none() {
+                for (file, functions) in &manifest.functions {
+                    println!("\n{}:", file);
+                    print!("{}", FunctionReport::table(functions));
+                }
+                println!();
+                let outdir = Path::new(&params.mutant_dir);
+                print!("{}", manifest.render_summary(outdir, None, use_color()));
+            }
+        }
+        Command::Apply(params) => {
+            let outdir = Path::new(&params.mutant_dir);
+            let manifest = Manifest::read(outdir)?;
+            for m in select(&manifest, params.id, &params.mutant_dir)? {
+                materialize(outdir, m)?;
+            }
         }
     }
     log::info!("Running time: {}", start.elapsed().as_secs_f64());
     Ok(())
 }
+
+/// Whether to color what is printed: not if `NO_COLOR` is set or stdout is
+/// not a terminal.
+fn use_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+}
+
+/// The mutant with the given `id` in the manifest, or all of them.
+fn select<'a>(
+    manifest: &'a Manifest,
+    id: Option<usize>,
+    mutant_dir: &str,
+) -> io::Result<Vec<&'a MutantEntry>> {
+    match id {
+        Some(id) => Ok(vec![manifest.get(id).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no mutant with id {} in {}", id, mutant_dir),
+            )
+        })?]),
+        None => Ok(manifest.mutants.iter().collect()),
+    }
+}
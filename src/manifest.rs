@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{write_atomically, HookStatus, MutationStats, SparseEdit};
+
+/// Name of the manifest file written to the output directory.
+pub static MANIFEST: &str = "manifest.json";
+
+/// A single mutant written by Gambit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MutantEntry {
+    /// Identifier of the mutant, unique within a run.
+    pub id: usize,
+    /// The file that was mutated.
+    pub original: String,
+    /// Where the mutant was written, relative to the output directory.
+    pub path: String,
+    /// The kind of mutation that produced this mutant.
+    pub mutation: String,
+    /// Whether the mutation disables a security mechanism, such as a
+    /// reentrancy guard, which reports should highlight.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub security_critical: bool,
+    /// The contract that was mutated, qualified by the path of its file,
+    /// e.g., `src/A.sol:Token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// The function (or modifier) that was mutated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// The name and parameter types of the function that was mutated,
+    /// e.g., `transfer(address,uint256)`, which tell overloads apart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The Solidity compiler the mutant was compiled with.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub solc: String,
+    /// The bytes of the original file that differ in the mutant file,
+    /// including the comment added to it, when it was written in full.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<(usize, usize)>,
+    /// SHA-256 of the mutant file, when run with `--checksum`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Command line that regenerates just this mutant.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub repro_command: String,
+    /// Outcome of the `--on-mutant` hook for this mutant, if one was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_status: Option<HookStatus>,
+    /// The edit making the mutant, when it was emitted with `--emit sparse`
+    /// instead of being written to `path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit: Option<SparseEdit>,
+}
+
+/// Why Gambit stopped generating mutants for a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoppedReason {
+    /// All the requested mutants were generated.
+    #[default]
+    Completed,
+    /// The attempts ran out before all the requested mutants were generated.
+    AttemptsExhausted,
+    /// Every candidate of every mutation point was tried before all the
+    /// requested mutants were generated.
+    MutationSpaceExhausted,
+    /// The run reached `--max-total-mutants` before all the requested
+    /// mutants were generated.
+    MutantCap,
+}
+
+impl StoppedReason {
+    /// The reason as it is written in the manifest, e.g., `completed`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoppedReason::Completed => "completed",
+            StoppedReason::AttemptsExhausted => "attempts_exhausted",
+            StoppedReason::MutationSpaceExhausted => "mutation_space_exhausted",
+            StoppedReason::MutantCap => "mutant_cap",
+        }
+    }
+}
+
+/// How mutating a single function (or modifier) went, along with a rough
+/// measure of its complexity, to see whether mutants go where the risk is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FunctionReport {
+    /// The contract that the function belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// The name of the function, or its kind for a constructor,
+    /// `fallback` or `receive`.
+    pub function: String,
+    /// Number of statements in the function.
+    pub statements: usize,
+    /// Number of `if`s, loops and `require`s in the function.
+    pub branches: usize,
+    /// Number of mutation points found in the function.
+    pub points: usize,
+    /// Number of mutants generated from the function.
+    pub mutants: usize,
+    /// The mutations that generated these mutants.
+    pub mutations: Vec<String>,
+}
+
+impl FunctionReport {
+    /// Renders `reports` as a table with a row per function.
+    pub fn table(reports: &[FunctionReport]) -> String {
+        let name = |r: &FunctionReport| match &r.contract {
+            Some(c) => format!("{}.{}", c, r.function),
+            None => r.function.clone(),
+        };
+        let width = reports
+            .iter()
+            .map(|r| name(r).len())
+            .chain(["function".len()])
+            .max()
+            .unwrap();
+        let mut table = format!(
+            "{:<width$}  statements  branches  points  mutants  mutations\n",
+            "function"
+        );
+        for r in reports {
+            let row = format!(
+                "{:<width$}  {:>10}  {:>8}  {:>6}  {:>7}  {}",
+                name(r),
+                r.statements,
+                r.branches,
+                r.points,
+                r.mutants,
+                r.mutations.join(", ")
+            );
+            table += row.trim_end();
+            table += "\n";
+        }
+        table
+    }
+}
+
+/// Record of all the mutants generated in a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Manifest {
+    pub mutants: Vec<MutantEntry>,
+    /// Why generating mutants stopped, for each file that was mutated.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stopped_reason: BTreeMap<String, StoppedReason>,
+    /// How mutating each function went, for each file that was mutated.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub functions: BTreeMap<String, Vec<FunctionReport>>,
+    /// The files whose mutants did not mirror their path in the output
+    /// directory, since that would have been too long, with the prefix
+    /// of their mutants instead, e.g., `~a1b2c3d4/A.sol`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub shortened: BTreeMap<String, String>,
+    /// How the candidates of each mutation fared, for each file that was
+    /// mutated.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stats: BTreeMap<String, BTreeMap<String, MutationStats>>,
+}
+
+impl Manifest {
+    /// Renders, for each file, how many mutants it got and, per mutation,
+    /// how many of its candidates were accepted, tried and did not compile,
+    /// along with the most common reasons for rejecting candidates if the
+    /// file got fewer mutants than requested. Then where the mutants of the
+    /// run are written to, and how long it took, if `elapsed` is given.
+    /// This is what both `gambit mutate` and `gambit summary` print.
+    pub fn render_summary(&self, outdir: &Path, elapsed: Option<Duration>, color: bool) -> String {
+        let bold = |s: String| match color {
+            true => ansi_term::Style::new().bold().paint(s).to_string(),
+            false => s,
+        };
+        let mut summary = String::new();
+        for (file, stopped) in &self.stopped_reason {
+            let mutants = self.mutants.iter().filter(|m| m.original == *file).count();
+            summary += &format!(
+                "{}: {} mutant{} ({})\n",
+                bold(file.clone()),
+                mutants,
+                if mutants == 1 { "" } else { "s" },
+                stopped.name()
+            );
+            let stats: Vec<(&String, &MutationStats)> = self
+                .stats
+                .get(file)
+                .into_iter()
+                .flatten()
+                .filter(|(_, s)| s.points > 0)
+                .collect();
+            if stats.is_empty() {
+                continue;
+            }
+            let width = stats
+                .iter()
+                .map(|(m, _)| m.len())
+                .chain(["mutation".len()])
+                .max()
+                .unwrap();
+            summary += &bold(format!(
+                "  {:<width$}  accepted  tried  invalid",
+                "mutation"
+            ));
+            summary += "\n";
+            for (m, s) in &stats {
+                summary += &format!(
+                    "  {:<width$}  {:>8}  {:>5}  {:>7}\n",
+                    m, s.accepted, s.attempts, s.invalid
+                );
+            }
+            if *stopped != StoppedReason::Completed {
+                let total = |f: fn(&MutationStats) -> usize| stats.iter().map(|(_, s)| f(s)).sum();
+                let mut reasons: Vec<(usize, &str)> = vec![
+                    (total(|s| s.invalid), "did not compile"),
+                    (total(|s| s.duplicates), "were duplicates"),
+                    (
+                        total(|s| s.hook_rejected),
+                        "were rejected by the --on-mutant hook",
+                    ),
+                    (total(|s| s.out_of_span), "were out-of-span edits"),
+                ];
+                // Stable, so that ties keep the order above.
+                reasons.sort_by_key(|(n, _)| std::cmp::Reverse(*n));
+                let reasons: Vec<String> = reasons
+                    .into_iter()
+                    .filter(|(n, _)| *n > 0)
+                    .take(3)
+                    .map(|(n, reason)| format!("{} {}", n, reason))
+                    .collect();
+                if !reasons.is_empty() {
+                    summary += &format!("  rejected: {}\n", reasons.join(", "));
+                }
+            }
+        }
+        summary += &format!("output: {}\n", outdir.display());
+        summary += &format!("manifest: {}\n", outdir.join(MANIFEST).display());
+        if let Some(elapsed) = elapsed {
+            summary += &format!("elapsed: {:.2}s\n", elapsed.as_secs_f64());
+        }
+        summary
+    }
+
+    /// Add a mutant to the manifest, assigning it the next id.
+    pub fn push(&mut self, mut entry: MutantEntry) {
+        entry.id = self.mutants.len() + 1;
+        self.mutants.push(entry);
+    }
+
+    /// Serialize the manifest and write it to `outdir`.
+    /// Returns the bytes that were written.
+    pub fn write(&self, outdir: &Path) -> io::Result<Vec<u8>> {
+        let body = serde_json::to_vec_pretty(self)?;
+        write_atomically(&outdir.join(MANIFEST), &body)?;
+        Ok(body)
+    }
+
+    /// Look up the mutant with the given `id`.
+    pub fn get(&self, id: usize) -> Option<&MutantEntry> {
+        self.mutants.iter().find(|m| m.id == id)
+    }
+
+    /// Read the manifest from `outdir`.
+    pub fn read(outdir: &Path) -> io::Result<Self> {
+        let f = File::open(outdir.join(MANIFEST))?;
+        Ok(serde_json::from_reader(BufReader::new(f))?)
+    }
+}
@@ -1,8 +1,11 @@
-use crate::SolAST;
+use crate::{edit_distance, pragma_admits_at_least, variable_key, SolAST};
 use clap::ValueEnum;
-use rand::{seq::SliceRandom, RngCore};
+use num_bigint::BigUint;
+use rand::{seq::SliceRandom, Rng, RngCore};
 use rand_pcg::*;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
 /// Every kind of mutation implements this trait.
 ///
@@ -23,6 +26,11 @@ use serde::{Deserialize, Serialize};
 pub trait Mutation {
     fn is_mutation_point(&self, node: &SolAST) -> bool;
     fn mutate_randomly(&self, node: &SolAST, source: &[u8], rand: &mut Pcg64) -> String;
+    /// The number of distinct mutants that `mutate_randomly` can produce
+    /// at `node`, leaving out any that would be identical to the source,
+    /// or `None` if there is no bound on them.
+    /// It may overestimate, but must never underestimate.
+    fn mutation_count(&self, node: &SolAST, source: &[u8]) -> Option<usize>;
 }
 
 /// Kinds of mutations.
@@ -39,10 +47,35 @@ pub enum MutationType {
     SwapLinesMutation,
     UnaryOperatorMutation,
     ElimDelegateMutation,
+    SubExpressionMutation,
+    ConstantFoldMutation,
+    MappingKeyMutation,
+    DeploymentCheckMutation,
+    ExponentMutation,
+    PrecisionOrderMutation,
+    PureToViewMutation,
+    AddSubMutation,
+    FunctionStubMutation,
+    EthSendMethodMutation,
+    SliceBoundsMutation,
+    RevertMessageMutation,
+    ERC721TransferMutation,
+    VoteWeightMutation,
+    BaseFeeMutation,
+    HashCollisionMutation,
+    FlashLoanMutation,
+    MinBalanceMutation,
+    EventIndexingMutation,
+    OverrideSpecifierMutation,
+    ABICallMutation,
+    ReentrancyGuardMutation,
+    IgnoreReturnMutation,
+    ExternalCallRevertMutation,
+    TupleComponentSwapMutation,
 }
 
-impl ToString for MutationType {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for MutationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self {
             MutationType::BinaryOpMutation => "BinaryOpMutation",
             MutationType::RequireMutation => "RequireMutation",
@@ -55,8 +88,112 @@ impl ToString for MutationType {
             MutationType::SwapLinesMutation => "SwapLinesMutation",
             MutationType::UnaryOperatorMutation => "UnaryOperatorMutation",
             MutationType::ElimDelegateMutation => "ElimDelegateMutation",
+            MutationType::SubExpressionMutation => "SubExpressionMutation",
+            MutationType::ConstantFoldMutation => "ConstantFoldMutation",
+            MutationType::MappingKeyMutation => "MappingKeyMutation",
+            MutationType::DeploymentCheckMutation => "DeploymentCheckMutation",
+            MutationType::ExponentMutation => "ExponentMutation",
+            MutationType::PrecisionOrderMutation => "PrecisionOrderMutation",
+            MutationType::PureToViewMutation => "PureToViewMutation",
+            MutationType::AddSubMutation => "AddSubMutation",
+            MutationType::FunctionStubMutation => "FunctionStubMutation",
+            MutationType::EthSendMethodMutation => "EthSendMethodMutation",
+            MutationType::SliceBoundsMutation => "SliceBoundsMutation",
+            MutationType::RevertMessageMutation => "RevertMessageMutation",
+            MutationType::ERC721TransferMutation => "ERC721TransferMutation",
+            MutationType::VoteWeightMutation => "VoteWeightMutation",
+            MutationType::BaseFeeMutation => "BaseFeeMutation",
+            MutationType::HashCollisionMutation => "HashCollisionMutation",
+            MutationType::FlashLoanMutation => "FlashLoanMutation",
+            MutationType::MinBalanceMutation => "MinBalanceMutation",
+            MutationType::EventIndexingMutation => "EventIndexingMutation",
+            MutationType::OverrideSpecifierMutation => "OverrideSpecifierMutation",
+            MutationType::ABICallMutation => "ABICallMutation",
+            MutationType::ReentrancyGuardMutation => "ReentrancyGuardMutation",
+            MutationType::IgnoreReturnMutation => "IgnoreReturnMutation",
+            MutationType::ExternalCallRevertMutation => "ExternalCallRevertMutation",
+            MutationType::TupleComponentSwapMutation => "TupleComponentSwapMutation",
         };
-        str.into()
+        write!(f, "{}", str)
+    }
+}
+
+/// Named sets of mutation types, selected with `--profile`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, ValueEnum, Deserialize, Serialize)]
+pub enum Profile {
+    /// Coarse mutations that give a quick first signal about which
+    /// functions are not really tested.
+    Fast,
+}
+
+impl Profile {
+    /// The mutation types of this profile.
+    pub fn mutations(&self) -> Vec<MutationType> {
+        match self {
+            Profile::Fast => vec![
+                MutationType::FunctionStubMutation,
+                MutationType::RequireMutation,
+                MutationType::IfStatementMutation,
+            ],
+        }
+    }
+}
+
+impl MutationType {
+    /// Whether mutants of this type disable a security mechanism,
+    /// which the manifest flags so that reports can highlight them.
+    pub fn is_security_critical(&self) -> bool {
+        matches!(
+            self,
+            MutationType::ReentrancyGuardMutation | MutationType::ExternalCallRevertMutation
+        )
+    }
+
+    /// Look up a mutation type by its name, either as it is written on the
+    /// command line (e.g., `binary-op-mutation`) or as it is printed
+    /// (e.g., `BinaryOpMutation`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        MutationType::from_str(name, true).ok().or_else(|| {
+            MutationType::value_variants()
+                .iter()
+                .find(|m| m.to_string() == name)
+                .copied()
+        })
+    }
+
+    /// Like `from_name`, but explains what went wrong for an unknown name,
+    /// suggesting the closest known mutation if there is one.
+    pub fn parse_name(name: &str) -> Result<Self, String> {
+        if let Some(m) = Self::from_name(name) {
+            return Ok(m);
+        }
+        let kebab = |m: &MutationType| m.to_possible_value().unwrap().get_name().to_string();
+        let distance = |m: &MutationType| {
+            let lower = name.to_lowercase();
+            edit_distance(&lower, &kebab(m))
+                .min(edit_distance(&lower, &m.to_string().to_lowercase()))
+        };
+        let closest = MutationType::value_variants()
+            .iter()
+            .min_by_key(|m| distance(m))
+            .unwrap();
+        if distance(closest) <= std::cmp::max(2, name.len() / 3) {
+            Err(format!(
+                "unknown mutation `{}`, did you mean `{}`?",
+                name,
+                kebab(closest)
+            ))
+        } else {
+            Err(format!(
+                "unknown mutation `{}`, expected one of: {}",
+                name,
+                MutationType::value_variants()
+                    .iter()
+                    .map(kebab)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
     }
 }
 
@@ -64,22 +201,13 @@ impl Mutation for MutationType {
     fn is_mutation_point(&self, node: &SolAST) -> bool {
         match self {
             MutationType::BinaryOpMutation => {
-                if let Some(n) = node.node_type() {
-                    return n == "BinaryOperation";
-                }
+                // None of the operators apply to fixed-size byte arrays.
+                return node.node_type().as_deref() == Some("BinaryOperation")
+                    && !is_fixed_bytes(&node.left_expression())
+                    && !is_fixed_bytes(&node.right_expression());
             }
             MutationType::RequireMutation => {
-                return node.node_type().map_or_else(
-                    || false,
-                    |n| {
-                        n == "FunctionCall"
-                            && (node
-                                .expression()
-                                .name()
-                                .map_or_else(|| false, |nm| nm == "require"))
-                            && !node.arguments().is_empty()
-                    },
-                );
+                return is_require_call(node) || custom_error_guard(node).is_some();
             }
             MutationType::AssignmentMutation => {
                 if let Some(n) = node.node_type() {
@@ -87,8 +215,10 @@ impl Mutation for MutationType {
                 }
             }
             MutationType::DeleteExpressionMutation => {
+                // A guard raising a custom error is deleted as a whole, as a
+                // `require` is.
                 if let Some(n) = node.node_type() {
-                    return n == "ExpressionStatement";
+                    return n == "ExpressionStatement" || custom_error_guard(node).is_some();
                 }
             }
             MutationType::FunctionCallMutation => {
@@ -144,6 +274,199 @@ impl Mutation for MutationType {
                     },
                 );
             }
+            MutationType::SubExpressionMutation => {
+                if let Some(n) = node.node_type() {
+                    return n == "BinaryOperation";
+                }
+            }
+            MutationType::ConstantFoldMutation => {
+                return fold_constant(node).is_some();
+            }
+            MutationType::MappingKeyMutation => {
+                let is_index_access = |n: &SolAST| {
+                    n.node_type()
+                        .map_or_else(|| false, |nt| nt == "IndexAccess")
+                        && n.index_expression().src().is_some()
+                };
+                return is_index_access(node) && is_index_access(&node.base_expression());
+            }
+            MutationType::DeploymentCheckMutation => {
+                return is_require_call(node) && is_deployment_check(&node.arguments()[0]);
+            }
+            MutationType::ExponentMutation => {
+                if node.node_type().as_deref() != Some("BinaryOperation") {
+                    return false;
+                }
+                return match node.operator().as_deref() {
+                    Some("**") => true,
+                    Some("*") => u128_value(&node.right_expression())
+                        .map_or_else(|| false, |n| (2..=10).contains(&n)),
+                    _ => false,
+                };
+            }
+            MutationType::PrecisionOrderMutation => {
+                return node.node_type().as_deref() == Some("BinaryOperation")
+                    && node.operator().as_deref() == Some("*")
+                    && (division_of(&node.left_expression()).is_some()
+                        || division_of(&node.right_expression()).is_some());
+            }
+            MutationType::PureToViewMutation => {
+                return node.node_type().as_deref() == Some("FunctionDefinition")
+                    && node.get_string("stateMutability").as_deref() == Some("pure");
+            }
+            MutationType::AddSubMutation => {
+                return match node.node_type().as_deref() {
+                    Some("BinaryOperation") => {
+                        matches!(node.operator().as_deref(), Some("+") | Some("-"))
+                    }
+                    Some("Assignment") => {
+                        matches!(node.operator().as_deref(), Some("+=") | Some("-="))
+                    }
+                    _ => false,
+                };
+            }
+            MutationType::FunctionStubMutation => {
+                let body = node.get_node("body");
+                return node.node_type().as_deref() == Some("FunctionDefinition")
+                    && node.kind().as_deref() != Some("constructor")
+                    && body.src().is_some()
+                    && !is_trivial_body(&body);
+            }
+            MutationType::EthSendMethodMutation => {
+                let member = node.expression();
+                return node.node_type().as_deref() == Some("FunctionCall")
+                    && member.node_type().as_deref() == Some("MemberAccess")
+                    && matches!(
+                        member.get_string("memberName").as_deref(),
+                        Some("transfer") | Some("send")
+                    )
+                    && member
+                        .expression()
+                        .type_string()
+                        .map_or_else(|| false, |t| t.starts_with("address"));
+            }
+            MutationType::SliceBoundsMutation => {
+                return node.node_type().as_deref() == Some("IndexRangeAccess")
+                    && (node.get_node("startExpression").src().is_some()
+                        || node.get_node("endExpression").src().is_some());
+            }
+            MutationType::RevertMessageMutation => {
+                return revert_message(node).map_or_else(
+                    || !other_errors(node).is_empty(),
+                    |m| m.value().map_or_else(|| true, |v| v != REVERT_MESSAGE),
+                );
+            }
+            MutationType::ERC721TransferMutation => {
+                // Unlike ERC20's `transferFrom`, which returns a `bool`,
+                // ERC721's transfers do not return anything.
+                let member = node.expression();
+                return node.node_type().as_deref() == Some("FunctionCall")
+                    && member.node_type().as_deref() == Some("MemberAccess")
+                    && matches!(
+                        member.get_string("memberName").as_deref(),
+                        Some("safeTransferFrom") | Some("transferFrom")
+                    )
+                    && node.arguments().len() == 3
+                    && member.type_string().map_or_else(
+                        || false,
+                        |t| {
+                            t.starts_with("function (address,address,uint256)")
+                                && !t.contains("returns")
+                        },
+                    );
+            }
+            MutationType::VoteWeightMutation => {
+                let callee = node.expression();
+                let name = match callee.node_type().as_deref() {
+                    Some("MemberAccess") => callee.get_string("memberName"),
+                    Some("Identifier") => callee.name(),
+                    _ => None,
+                };
+                return node.node_type().as_deref() == Some("FunctionCall")
+                    && name.map_or_else(
+                        || false,
+                        |n| matches!(n.as_str(), "getVotes" | "balanceOfAt" | "getPastVotes"),
+                    );
+            }
+            MutationType::BaseFeeMutation => {
+                return node.node_type().as_deref() == Some("MemberAccess")
+                    && node.get_string("memberName").as_deref() == Some("basefee")
+                    && node.expression().node_type().as_deref() == Some("Identifier")
+                    && node.expression().name().as_deref() == Some("block");
+            }
+            MutationType::HashCollisionMutation => {
+                return packed_hash_arguments(node).is_some();
+            }
+            MutationType::FlashLoanMutation => {
+                let in_callback = node
+                    .get_function()
+                    .map_or_else(|| false, |f| FLASH_LOAN_CALLBACKS.contains(&f.as_str()));
+                return in_callback
+                    && if custom_error_guard(node).is_some() {
+                        is_self_sender_check(&node.condition(), "!=")
+                    } else {
+                        is_require_call(node) && is_self_sender_check(&node.arguments()[0], "==")
+                    };
+            }
+            MutationType::MinBalanceMutation => {
+                return !minimum_operands(node).is_empty();
+            }
+            MutationType::EventIndexingMutation => {
+                return node.node_type().as_deref() == Some("EventDefinition")
+                    && !indexed_parameters(node).is_empty();
+            }
+            MutationType::OverrideSpecifierMutation => {
+                return node.node_type().as_deref() == Some("FunctionDefinition")
+                    && override_list(node).len() >= 2;
+            }
+            MutationType::ABICallMutation => {
+                return !other_selectors(node).is_empty();
+            }
+            MutationType::ReentrancyGuardMutation => {
+                // Which modifiers are guards is up to the run, see
+                // `SolAST::with_reentrancy_guards`.
+                return (node.node_type().as_deref() == Some("ModifierInvocation")
+                    && node.kind().as_deref() != Some("baseConstructorSpecifier")
+                    && node
+                        .get_node("modifierName")
+                        .name()
+                        .is_some_and(|n| node.is_reentrancy_guard(&n)))
+                    || inline_guard(node).is_some();
+            }
+            MutationType::IgnoreReturnMutation => {
+                if node.node_type().as_deref() != Some("VariableDeclarationStatement") {
+                    return false;
+                }
+                let init = node.get_node("initialValue");
+                let mut callee = init.expression();
+                // E.g., `x.call{value: v}("")`.
+                if callee.node_type().as_deref() == Some("FunctionCallOptions") {
+                    callee = callee.expression();
+                }
+                // Dropping variables that are used later only gives mutants
+                // that fail to compile.
+                let unused = node
+                    .get_object()
+                    .and_then(|v| v["declarations"].as_array().cloned())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|d| d["id"].as_i64())
+                    .all(|id| !node.is_referenced(id));
+                return init.node_type().as_deref() == Some("FunctionCall")
+                    && init.kind().as_deref() == Some("functionCall")
+                    && callee.node_type().as_deref() == Some("MemberAccess")
+                    && unused;
+            }
+            MutationType::ExternalCallRevertMutation => {
+                // Not the expressions in the header of a `for`, which are
+                // not statements of their own.
+                return node.node_type().as_deref() == Some("ExpressionStatement")
+                    && node.get_statement() == Some(node.get_bounds())
+                    && is_external_call(&node.expression());
+            }
+            MutationType::TupleComponentSwapMutation => {
+                return !tuple_swaps(node).is_empty();
+            }
         }
         false
     }
@@ -152,7 +475,7 @@ impl Mutation for MutationType {
         match self {
             MutationType::BinaryOpMutation => {
                 assert!(&self.is_mutation_point(node));
-                let ops = vec!["+", "-", "*", "/", "%", "**"];
+                let ops = binary_op_candidates(node, source);
                 let (_, endl) = node.left_expression().get_bounds();
                 let (startr, _) = node.right_expression().get_bounds();
                 node.replace_part(
@@ -164,7 +487,11 @@ impl Mutation for MutationType {
             }
             MutationType::RequireMutation => {
                 assert!(&self.is_mutation_point(node));
-                let arg = &node.arguments()[0];
+                let arg = if is_require_call(node) {
+                    node.arguments()[0].clone()
+                } else {
+                    node.condition()
+                };
                 arg.replace_in_source(source, "!(".to_string() + &arg.get_text(source) + ")")
             }
             MutationType::DeleteExpressionMutation => {
@@ -182,7 +509,7 @@ impl Mutation for MutationType {
             MutationType::IfStatementMutation => {
                 assert!(&self.is_mutation_point(node));
                 let cond = node.condition();
-                let bs = vec![true, false];
+                let bs = [true, false];
                 if *bs.choose(rand).unwrap() {
                     cond.replace_in_source(source, (*bs.choose(rand).unwrap()).to_string())
                 } else {
@@ -235,15 +562,13 @@ impl Mutation for MutationType {
             }
             MutationType::UnaryOperatorMutation => {
                 assert!(&self.is_mutation_point(node));
-                let prefix_ops = vec!["++", "--", "~"];
-                let suffix_ops = vec!["++", "--"];
-                let is_prefix =
-                    |source: &[u8], op: &String| -> bool { return source[0] == op.as_bytes()[0] };
+                let prefix_ops = ["++", "--", "~"];
+                let suffix_ops = ["++", "--"];
                 let (start, end) = node.get_bounds();
                 let op = node
                     .operator()
                     .expect("Unary operation must have an operator!");
-                return if is_prefix(source, &op) {
+                if source[start..].starts_with(op.as_bytes()) {
                     node.replace_part(
                         source,
                         prefix_ops.choose(rand).unwrap().to_string(),
@@ -257,15 +582,14 @@ impl Mutation for MutationType {
                         end - op.len(),
                         end,
                     )
-                };
+                }
             }
             MutationType::AssignmentMutation => {
                 assert!(&self.is_mutation_point(node));
-                let new: Vec<String> =
-                    vec!["true", "false", "0", "1", &rand.next_u64().to_string()]
-                        .iter()
-                        .map(|e| e.to_string())
-                        .collect();
+                let new: Vec<String> = ["true", "false", "0", "1", &rand.next_u64().to_string()]
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect();
                 let rhs = node.right_hand_side();
                 match rhs.element {
                     Some(_) => rhs.replace_in_source(source, new.choose(rand).unwrap().to_string()),
@@ -278,6 +602,3322 @@ impl Mutation for MutationType {
                 let (_, endr) = node.expression().get_bounds();
                 node.replace_part(source, "call".to_string(), endl + 1, endr)
             }
+            MutationType::SubExpressionMutation => {
+                assert!(&self.is_mutation_point(node));
+                let operands = [node.left_expression(), node.right_expression()];
+                let operand = operands.choose(rand).unwrap();
+                node.replace_in_source(source, operand.get_text(source))
+            }
+            MutationType::ConstantFoldMutation => {
+                assert!(&self.is_mutation_point(node));
+                node.replace_in_source(source, fold_constant(node).unwrap())
+            }
+            MutationType::MappingKeyMutation => {
+                assert!(&self.is_mutation_point(node));
+                let outer = node.index_expression();
+                let inner = node.base_expression().index_expression();
+                node.replace_multiple(
+                    source,
+                    vec![
+                        (outer.clone(), inner.get_text(source)),
+                        (inner, outer.get_text(source)),
+                    ],
+                )
+            }
+            MutationType::DeploymentCheckMutation => {
+                assert!(&self.is_mutation_point(node));
+                node.comment_out(source)
+            }
+            MutationType::ExponentMutation => {
+                assert!(&self.is_mutation_point(node));
+                let op = if node.operator().unwrap() == "**" {
+                    "*"
+                } else {
+                    "**"
+                };
+                let (_, endl) = node.left_expression().get_bounds();
+                let (startr, _) = node.right_expression().get_bounds();
+                node.replace_part(source, " ".to_string() + op + " ", endl, startr)
+            }
+            MutationType::PrecisionOrderMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (left, right) = (node.left_expression(), node.right_expression());
+                // `(a / b) * c` becomes `(a * c) / b`, `c * (a / b)` becomes `(c * a) / b`.
+                let (product, divisor) = match division_of(&left) {
+                    Some(div) => (
+                        div.left_expression().get_text(source) + " * " + &right.get_text(source),
+                        div.right_expression(),
+                    ),
+                    None => {
+                        let div = division_of(&right).unwrap();
+                        (
+                            left.get_text(source) + " * " + &div.left_expression().get_text(source),
+                            div.right_expression(),
+                        )
+                    }
+                };
+                node.replace_in_source(
+                    source,
+                    "(".to_string() + &product + ") / " + &divisor.get_text(source),
+                )
+            }
+            MutationType::PureToViewMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (start, end) = node.get_bounds();
+                // The keyword is in the header, before the body if there is one.
+                let header_end = if node.get_node("body").src().is_some() {
+                    node.get_node("body").get_bounds().0
+                } else {
+                    end
+                };
+                let header = String::from_utf8_lossy(&source[start..header_end]);
+                static PURE: OnceLock<Regex> = OnceLock::new();
+                let pure = PURE.get_or_init(|| Regex::new(r"\bpure\b").unwrap());
+                match pure.find(&header) {
+                    Some(m) => {
+                        node.replace_part(source, "view".into(), start + m.start(), start + m.end())
+                    }
+                    None => node.get_text(source),
+                }
+            }
+            MutationType::AddSubMutation => {
+                assert!(&self.is_mutation_point(node));
+                let new_op = match node.operator().unwrap().as_str() {
+                    "+" => "-",
+                    "-" => "+",
+                    "+=" => "-=",
+                    _ => "+=",
+                };
+                let (endl, startr) = if node.node_type().unwrap() == "Assignment" {
+                    (
+                        node.left_hand_side().get_bounds().1,
+                        node.right_hand_side().get_bounds().0,
+                    )
+                } else {
+                    (
+                        node.left_expression().get_bounds().1,
+                        node.right_expression().get_bounds().0,
+                    )
+                };
+                node.replace_part(source, " ".to_string() + new_op + " ", endl, startr)
+            }
+            MutationType::FunctionStubMutation => {
+                assert!(&self.is_mutation_point(node));
+                let returns = node.get_node("returnParameters").get_node("parameters");
+                let returns: Vec<SolAST> = returns
+                    .get_object()
+                    .and_then(|v| v.as_array().cloned())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|v| SolAST::new(v, None))
+                    .collect();
+                // Named return variables already hold default values, and
+                // so do unnamed ones that are never assigned, so `{ }` is
+                // the fallback when we cannot write a default.
+                let defaults: Option<Vec<String>> = returns
+                    .iter()
+                    .map(|r| match r.name().as_deref() {
+                        Some("") | None => default_value(r, source),
+                        Some(_) => None,
+                    })
+                    .collect();
+                let stub = match defaults {
+                    Some(ds) if ds.len() == 1 => format!("{{ return {}; }}", ds[0]),
+                    Some(ds) if ds.len() > 1 => format!("{{ return ({}); }}", ds.join(", ")),
+                    _ => "{ }".to_string(),
+                };
+                node.get_node("body").replace_in_source(source, stub)
+            }
+            MutationType::EthSendMethodMutation => {
+                assert!(&self.is_mutation_point(node));
+                let member = node.expression();
+                let name = member.get_string("memberName").unwrap();
+                let (_, end) = member.get_bounds();
+                let swap = |new: &str| node.replace_part(source, new.into(), end - name.len(), end);
+                if name == "transfer" {
+                    swap("send")
+                } else if *[true, false].choose(rand).unwrap() {
+                    swap("transfer")
+                } else {
+                    node.replace_in_source(
+                        source,
+                        "require(".to_string() + &node.get_text(source) + ")",
+                    )
+                }
+            }
+            MutationType::SliceBoundsMutation => {
+                assert!(&self.is_mutation_point(node));
+                let bounds: Vec<(SolAST, bool)> =
+                    [("startExpression", true), ("endExpression", false)]
+                        .into_iter()
+                        .map(|(name, is_start)| (node.get_node(name), is_start))
+                        .filter(|(bound, _)| bound.src().is_some())
+                        .collect();
+                let (bound, is_start) = bounds.choose(rand).unwrap();
+                let new = slice_bound_replacements(bound, *is_start, source);
+                bound.replace_in_source(source, new.choose(rand).unwrap().clone())
+            }
+            MutationType::RevertMessageMutation => {
+                assert!(&self.is_mutation_point(node));
+                match revert_message(node) {
+                    Some(message) => {
+                        message.replace_in_source(source, format!("\"{}\"", REVERT_MESSAGE))
+                    }
+                    None => {
+                        let errors = other_errors(node);
+                        let error = errors.choose(rand).unwrap();
+                        node.get_node("errorCall")
+                            .expression()
+                            .replace_in_source(source, error.clone())
+                    }
+                }
+            }
+            MutationType::ERC721TransferMutation => {
+                assert!(&self.is_mutation_point(node));
+                let member = node.expression();
+                let name = member.get_string("memberName").unwrap();
+                let (_, end) = member.get_bounds();
+                let new = if name == "safeTransferFrom" {
+                    "transferFrom"
+                } else {
+                    "safeTransferFrom"
+                };
+                node.replace_part(source, new.into(), end - name.len(), end)
+            }
+            MutationType::VoteWeightMutation => {
+                assert!(&self.is_mutation_point(node));
+                node.replace_in_source(source, "0".into())
+            }
+            MutationType::BaseFeeMutation => {
+                assert!(&self.is_mutation_point(node));
+                let new = ["0", "type(uint256).max"].choose(rand).unwrap();
+                node.replace_in_source(source, new.to_string())
+            }
+            MutationType::HashCollisionMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (first, _) = packed_hash_arguments(node).unwrap();
+                let (_, end) = first.get_bounds();
+                // A bare `0` cannot be packed, it needs a type.
+                node.replace_part(source, ", uint256(0)".into(), end, end)
+            }
+            MutationType::FlashLoanMutation => {
+                assert!(&self.is_mutation_point(node));
+                node.comment_out(source)
+            }
+            MutationType::MinBalanceMutation => {
+                assert!(&self.is_mutation_point(node));
+                let mins = minimum_operands(node);
+                let min = mins.choose(rand).unwrap();
+                min.replace_in_source(source, "0".into())
+            }
+            MutationType::EventIndexingMutation => {
+                assert!(&self.is_mutation_point(node));
+                let params = indexed_parameters(node);
+                let param = params.choose(rand).unwrap();
+                // `indexed` is not a node of its own, so it has to be
+                // found in the text of the parameter.
+                let (start, _) = param.get_bounds();
+                let text = param.get_text(source);
+                static INDEXED: OnceLock<Regex> = OnceLock::new();
+                let indexed = INDEXED.get_or_init(|| Regex::new(r"\bindexed\b\s*").unwrap());
+                let m = indexed
+                    .find(&text)
+                    .expect("indexed parameter without `indexed`");
+                node.replace_part(source, "".into(), start + m.start(), start + m.end())
+            }
+            MutationType::OverrideSpecifierMutation => {
+                assert!(&self.is_mutation_point(node));
+                let list = override_list(node);
+                let i = rand.gen_range(0, list.len());
+                // Take a neighbouring comma along with the entry.
+                let (start, end) = if i + 1 < list.len() {
+                    (list[i].get_bounds().0, list[i + 1].get_bounds().0)
+                } else {
+                    (list[i - 1].get_bounds().1, list[i].get_bounds().1)
+                };
+                node.replace_part(source, "".into(), start, end)
+            }
+            MutationType::ABICallMutation => {
+                assert!(&self.is_mutation_point(node));
+                let selectors = other_selectors(node);
+                let selector = selectors.choose(rand).unwrap();
+                node.arguments()[0].replace_in_source(source, format!("bytes4(0x{})", selector))
+            }
+            MutationType::ReentrancyGuardMutation => {
+                assert!(&self.is_mutation_point(node));
+                match inline_guard(node) {
+                    Some(statements) => node.replace_multiple(
+                        source,
+                        statements
+                            .into_iter()
+                            .map(|s| {
+                                let text = format!("/*{}*/", s.get_text(source));
+                                (s, text)
+                            })
+                            .collect(),
+                    ),
+                    // Mutants must not edit outside of the node, so the
+                    // space after the invocation stays.
+                    None => node.replace_in_source(source, "".into()),
+                }
+            }
+            MutationType::IgnoreReturnMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (start, _) = node.get_bounds();
+                let (call, _) = node.get_node("initialValue").get_bounds();
+                node.replace_part(source, "".into(), start, call)
+            }
+            MutationType::ExternalCallRevertMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (start, mut end) = node.get_bounds();
+                // Most versions of solc leave the `;` out of the statement.
+                if let Some(i) = source[end..].iter().position(|c| !c.is_ascii_whitespace()) {
+                    if source[end + i] == b';' {
+                        end += i + 1;
+                    }
+                }
+                let text = String::from_utf8_lossy(&source[start..end]);
+                // Keeping the call avoids warnings about unreachable code, and
+                // the braces keep it the body of an `if` or loop without any.
+                node.replace_part(
+                    source,
+                    format!("{{ if (true) {{ revert(); }} {} }}", text),
+                    start,
+                    end,
+                )
+            }
+            MutationType::TupleComponentSwapMutation => {
+                assert!(&self.is_mutation_point(node));
+                let (a, b) = tuple_swaps(node).choose(rand).unwrap().clone();
+                node.replace_multiple(
+                    source,
+                    vec![(a.clone(), b.get_text(source)), (b, a.get_text(source))],
+                )
+            }
+        }
+    }
+
+    fn mutation_count(&self, node: &SolAST, source: &[u8]) -> Option<usize> {
+        assert!(&self.is_mutation_point(node));
+        let count = match self {
+            MutationType::BinaryOpMutation => {
+                let op = node.operator().unwrap();
+                binary_op_candidates(node, source)
+                    .into_iter()
+                    .filter(|o| *o != op)
+                    .count()
+            }
+            MutationType::FunctionCallMutation => node.arguments().len(),
+            MutationType::IfStatementMutation => 3,
+            // Only two children are ever swapped, see `mutate_randomly`.
+            MutationType::SwapArgumentsFunctionMutation => (node.arguments().len() == 2) as usize,
+            MutationType::SwapLinesMutation => (node.statements().len() == 2) as usize,
+            MutationType::UnaryOperatorMutation => {
+                let op = node.operator().unwrap();
+                if source[node.get_bounds().0..].starts_with(op.as_bytes()) {
+                    3 - ["++", "--", "~"].contains(&op.as_str()) as usize
+                } else {
+                    1
+                }
+            }
+            // Assigns a random number, among others.
+            MutationType::AssignmentMutation => return None,
+            MutationType::SubExpressionMutation | MutationType::BaseFeeMutation => 2,
+            MutationType::EthSendMethodMutation => {
+                if node.expression().get_string("memberName").as_deref() == Some("transfer") {
+                    1
+                } else {
+                    2
+                }
+            }
+            MutationType::SliceBoundsMutation => ["startExpression", "endExpression"]
+                .into_iter()
+                .map(|name| (node.get_node(name), name == "startExpression"))
+                .filter(|(bound, _)| bound.src().is_some())
+                .map(|(bound, is_start)| slice_bound_replacements(&bound, is_start, source).len())
+                .sum(),
+            MutationType::MinBalanceMutation => minimum_operands(node).len(),
+            MutationType::EventIndexingMutation => indexed_parameters(node).len(),
+            MutationType::OverrideSpecifierMutation => override_list(node).len(),
+            MutationType::ABICallMutation => other_selectors(node).len(),
+            MutationType::TupleComponentSwapMutation => tuple_swaps(node).len(),
+            MutationType::RevertMessageMutation => match revert_message(node) {
+                Some(_) => 1,
+                None => other_errors(node).len(),
+            },
+            MutationType::RequireMutation
+            | MutationType::DeleteExpressionMutation
+            | MutationType::SwapArgumentsOperatorMutation
+            | MutationType::ElimDelegateMutation
+            | MutationType::ConstantFoldMutation
+            | MutationType::MappingKeyMutation
+            | MutationType::DeploymentCheckMutation
+            | MutationType::ExponentMutation
+            | MutationType::PrecisionOrderMutation
+            | MutationType::PureToViewMutation
+            | MutationType::AddSubMutation
+            | MutationType::FunctionStubMutation
+            | MutationType::ERC721TransferMutation
+            | MutationType::ReentrancyGuardMutation
+            | MutationType::IgnoreReturnMutation
+            | MutationType::ExternalCallRevertMutation
+            | MutationType::VoteWeightMutation
+            | MutationType::HashCollisionMutation
+            | MutationType::FlashLoanMutation => 1,
+        };
+        Some(count)
+    }
+}
+
+/// The operators `BinaryOpMutation` may put in place of the operator of
+/// `node`, leaving out those that would not compile for the types of its
+/// operands and the version of the compiler, or, if it is not known, the
+/// versions that the pragmas of `source` allow:
+/// `**` is not offered for signed operands from Solidity 0.8 on, nor `%` for
+/// fixed-point numbers and user-defined value types. `**` itself is only
+/// replaced by `*` and `+`, which always compile where it does.
+fn binary_op_candidates(node: &SolAST, source: &[u8]) -> Vec<&'static str> {
+    let op = node.operator().unwrap();
+    let identities = identity_replacements(&op, &node.right_expression());
+    let operands = [node.left_expression(), node.right_expression()];
+    let from_0_8 = match node.get_solc_version() {
+        Some(version) => version >= (0, 8, 0),
+        None => pragma_admits_at_least(source, (0, 8, 0)),
+    };
+    let no_exponent = operands.iter().any(is_signed) && from_0_8;
+    let no_modulo = operands
+        .iter()
+        .any(|o| is_fixed_point(o) || is_user_defined_value_type(o));
+    let ops: &[&'static str] = if op == "**" {
+        &["*", "+"]
+    } else {
+        &["+", "-", "*", "/", "%", "**"]
+    };
+    ops.iter()
+        .copied()
+        .filter(|o| !identities.contains(o))
+        .filter(|o| !(no_exponent && *o == "**"))
+        .filter(|o| !(no_modulo && *o == "%"))
+        .collect()
+}
+
+/// Checks whether `node` is a signed integer, including negative literals.
+fn is_signed(node: &SolAST) -> bool {
+    node.type_string().map_or_else(
+        || false,
+        |t| t.starts_with("int_const -") || (t.starts_with("int") && !t.starts_with("int_const")),
+    )
+}
+
+/// Checks whether `node` is a fixed-point number.
+fn is_fixed_point(node: &SolAST) -> bool {
+    node.type_string().map_or_else(
+        || false,
+        |t| t.starts_with("fixed") || t.starts_with("ufixed"),
+    )
+}
+
+/// Checks whether `node` is of a user-defined value type.
+fn is_user_defined_value_type(node: &SolAST) -> bool {
+    node.get_node("typeDescriptions")
+        .get_string("typeIdentifier")
+        .map_or_else(|| false, |t| t.starts_with("t_userDefinedValueType"))
+}
+
+/// Checks whether `node` is a fixed-size byte array, e.g., `bytes32`.
+fn is_fixed_bytes(node: &SolAST) -> bool {
+    node.type_string().map_or_else(
+        || false,
+        |t| {
+            t.strip_prefix("bytes")
+                .map_or_else(|| false, |n| n.parse::<u8>().is_ok())
+        },
+    )
+}
+
+/// The operators that, put in place of `op` in `x <op> right`, give an
+/// expression equal to the original one because `right` is the literal
+/// `0` or `1`, e.g., `-` for `x + 0` or `/` for `x * 1`.
+fn identity_replacements(op: &str, right: &SolAST) -> Vec<&'static str> {
+    match (op, u128_value(right)) {
+        ("+" | "-", Some(0)) => vec!["+", "-"],
+        ("*" | "/" | "**", Some(1)) => vec!["*", "/", "**"],
+        _ => vec![],
+    }
+}
+
+/// The two dynamically-sized arguments `a` and `b` of a hash
+/// `keccak256(abi.encodePacked(a, b))`.
+fn packed_hash_arguments(node: &SolAST) -> Option<(SolAST, SolAST)> {
+    if node.node_type()? != "FunctionCall" || node.expression().name()? != "keccak256" {
+        return None;
+    }
+    let [packed] = node.arguments().try_into().ok()?;
+    let encode = packed.expression();
+    if packed.node_type()? != "FunctionCall"
+        || encode.get_string("memberName")? != "encodePacked"
+        || encode.expression().name()? != "abi"
+    {
+        return None;
+    }
+    let is_dynamic = |arg: &SolAST| {
+        arg.type_string().map_or_else(
+            || false,
+            |t| {
+                t == "bytes"
+                    || t.starts_with("bytes ")
+                    || t.starts_with("string")
+                    || t.starts_with("literal_string")
+            },
+        )
+    };
+    let [a, b] = packed.arguments().try_into().ok()?;
+    (is_dynamic(&a) && is_dynamic(&b)).then_some((a, b))
+}
+
+/// The reason string that `RevertMessageMutation` puts in place of
+/// the original one.
+static REVERT_MESSAGE: &str = "MUTATION";
+
+/// The string literal message of a `require(cond, message)` or
+/// `revert(message)` call.
+fn revert_message(node: &SolAST) -> Option<SolAST> {
+    if node.node_type()? != "FunctionCall" {
+        return None;
+    }
+    let args = node.arguments();
+    let message = match (node.expression().name()?.as_str(), args.as_slice()) {
+        ("require", [_, message]) | ("revert", [message]) => message.clone(),
+        _ => return None,
+    };
+    (message.node_type()? == "Literal" && message.kind()? == "string").then_some(message)
+}
+
+/// Checks whether `node` is a call to `require` with a condition.
+fn is_require_call(node: &SolAST) -> bool {
+    node.node_type().as_deref() == Some("FunctionCall")
+        && node.expression().name().as_deref() == Some("require")
+        && !node.arguments().is_empty()
+}
+
+/// If `node` guards the code after it with a custom error, i.e., it is of
+/// the form `if (cond) revert Err(...);`, the counterpart of
+/// `require(!cond, ...)`, returns the `revert` statement.
+fn custom_error_guard(node: &SolAST) -> Option<SolAST> {
+    if node.node_type()? != "IfStatement" || node.get_node("falseBody").src().is_some() {
+        return None;
+    }
+    let body = node.get_node("trueBody");
+    let revert = match body.node_type()?.as_str() {
+        "Block" => match body.statements().as_slice() {
+            [statement] => statement.clone(),
+            _ => return None,
+        },
+        _ => body,
+    };
+    (revert.node_type()? == "RevertStatement").then_some(revert)
+}
+
+/// If `node` is a statement `revert Err(...)`, returns the other custom
+/// errors of the enclosing contract that take as many arguments as `Err`.
+fn other_errors(node: &SolAST) -> Vec<String> {
+    let call = node.get_node("errorCall");
+    let name = match call.expression().name() {
+        Some(name) if node.node_type().as_deref() == Some("RevertStatement") => name,
+        _ => return vec![],
+    };
+    let arity = call.arguments().len();
+    node.get_contract_info().map_or_else(Vec::new, |info| {
+        info.errors
+            .iter()
+            .filter(|(e, n)| *e != name && *n == arity)
+            .map(|(e, _)| e.clone())
+            .collect()
+    })
+}
+
+/// The texts that can take the place of the `bound` of a slice `x[start:end]`:
+/// the bound plus or minus one, zero, and nothing at all, which makes the
+/// slice open-ended. Replacements that leave the slice as it was, like
+/// removing a start of `0`, are left out.
+fn slice_bound_replacements(bound: &SolAST, is_start: bool, source: &[u8]) -> Vec<String> {
+    let text = bound.get_text(source);
+    let mut new = vec![];
+    match u128_value(bound) {
+        Some(n) => {
+            new.push((n + 1).to_string());
+            if n > 0 {
+                new.push((n - 1).to_string());
+                new.push("0".into());
+            }
+        }
+        None => {
+            let operand = if bound.node_type().as_deref() == Some("Identifier") {
+                text
+            } else {
+                "(".to_string() + &text + ")"
+            };
+            new.push(operand.clone() + " + 1");
+            new.push(operand + " - 1");
+            new.push("0".into());
+        }
+    }
+    if !(is_start && u128_value(bound) == Some(0)) {
+        new.push("".into());
+    }
+    new
+}
+
+/// Checks whether a function `body` does nothing worth stubbing out,
+/// i.e., it is empty or just returns a literal.
+fn is_trivial_body(body: &SolAST) -> bool {
+    let stmts = body.statements();
+    match stmts.as_slice() {
+        [] => true,
+        [stmt] => {
+            let expr = stmt.expression();
+            stmt.node_type().as_deref() == Some("Return")
+                && (expr.src().is_none() || expr.node_type().as_deref() == Some("Literal"))
+        }
+        _ => false,
+    }
+}
+
+/// A default value for the type of `param`, a `VariableDeclaration`,
+/// written as Solidity source, or `None` if there is no simple one
+/// (e.g., for structs).
+fn default_value(param: &SolAST, source: &[u8]) -> Option<String> {
+    let type_string = param.type_string()?;
+    let type_string = [" memory", " calldata", " storage ref", " storage pointer"]
+        .iter()
+        .fold(type_string.as_str(), |t, loc| t.trim_end_matches(loc));
+    let type_name = param.get_node("typeName");
+    let written = || type_name.src().map(|_| type_name.get_text(source));
+    if type_string.contains('[') {
+        return type_string
+            .ends_with("[]")
+            .then(|| Some(format!("new {}(0)", written()?)))
+            .flatten();
+    }
+    let default = match type_string {
+        "bool" => "false".into(),
+        "address" => "address(0)".into(),
+        "address payable" => "payable(address(0))".into(),
+        "string" | "bytes" => "\"\"".into(),
+        t if t.starts_with("uint") || t.starts_with("int") => "0".into(),
+        t if t.starts_with("bytes") => format!("{}(0)", t),
+        t if t.starts_with("contract ") => format!("{}(address(0))", written()?),
+        t if t.starts_with("enum ") => format!("{}(0)", written()?),
+        _ => return None,
+    };
+    Some(default)
+}
+
+/// If `node` is a division, possibly in parentheses, returns the
+/// `BinaryOperation` doing the division.
+fn division_of(node: &SolAST) -> Option<SolAST> {
+    match node.node_type()?.as_str() {
+        "BinaryOperation" if node.operator()? == "/" => Some(node.clone()),
+        "TupleExpression" => {
+            let components = node.components();
+            if components.len() == 1 {
+                division_of(&components[0])
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If `node` is a conversion to `address`, e.g., `address(x)`,
+/// returns the expression being converted.
+fn address_cast_of(node: &SolAST) -> Option<SolAST> {
+    let args = node.arguments();
+    if node.node_type()? == "FunctionCall"
+        && node.kind()? == "typeConversion"
+        && node.type_string()?.starts_with("address")
+        && args.len() == 1
+    {
+        args.into_iter().next()
+    } else {
+        None
+    }
+}
+
+/// Checks whether `node` calls a function of another contract, i.e., the
+/// callee is a member of an expression of a contract (or interface) type.
+fn is_external_call(node: &SolAST) -> bool {
+    let mut callee = node.expression();
+    // E.g., `pool.deposit{value: v}()`.
+    if callee.node_type().as_deref() == Some("FunctionCallOptions") {
+        callee = callee.expression();
+    }
+    node.node_type().as_deref() == Some("FunctionCall")
+        && node.kind().as_deref() == Some("functionCall")
+        && callee.node_type().as_deref() == Some("MemberAccess")
+        && callee
+            .expression()
+            .type_string()
+            .is_some_and(|t| t.starts_with("contract "))
+}
+
+/// The pairs of components of the same type that `TupleComponentSwapMutation`
+/// may swap in the tuple assignment or declaration of several variables
+/// `node`: those of the tuple on the right, as in
+/// `(reserve0, reserve1) = (balance0, balance1);`, or else the variables on
+/// the left, as in `(uint a, uint b) = f();`.
+/// Components across from an empty slot, as in `(, uint b) = ...`, are left
+/// out.
+fn tuple_swaps(node: &SolAST) -> Vec<(SolAST, SolAST)> {
+    let (left, right) = match node.node_type().as_deref() {
+        Some("Assignment")
+            if node.left_hand_side().node_type().as_deref() == Some("TupleExpression") =>
+        {
+            (node.left_hand_side().components(), node.right_hand_side())
+        }
+        Some("VariableDeclarationStatement") => {
+            let declarations: Vec<SolAST> = node
+                .get_node("declarations")
+                .get_object()
+                .and_then(|v| v.as_array().cloned())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| SolAST::new(v, None))
+                .collect();
+            (declarations, node.get_node("initialValue"))
+        }
+        _ => return vec![],
+    };
+    if left.len() < 2 {
+        return vec![];
+    }
+    let candidates: Vec<SolAST> = match right.node_type().as_deref() {
+        Some("TupleExpression") if right.components().len() == left.len() => right
+            .components()
+            .into_iter()
+            .zip(&left)
+            .filter(|(r, l)| l.get_object().is_some() && r.get_object().is_some())
+            .map(|(r, _)| r)
+            .collect(),
+        Some("TupleExpression") => return vec![],
+        _ => left
+            .into_iter()
+            .filter(|l| l.get_object().is_some())
+            .collect(),
+    };
+    let mut swaps = vec![];
+    for (i, a) in candidates.iter().enumerate() {
+        for b in &candidates[i + 1..] {
+            if a.type_string().is_some() && a.type_string() == b.type_string() {
+                swaps.push((a.clone(), b.clone()));
+            }
+        }
+    }
+    swaps
+}
+
+/// Checks whether `cond` is of the form `address(x) != address(0)`
+/// (in either order) where `x` has a contract type and was assigned a
+/// contract deployed with `new` or `create2` earlier in the function,
+/// rather than, e.g., being a parameter.
+fn is_deployment_check(cond: &SolAST) -> bool {
+    let is_zero = |n: &SolAST| {
+        address_cast_of(n).map_or_else(
+            || false,
+            |a| a.kind().as_deref() == Some("number") && a.value().as_deref() == Some("0"),
+        )
+    };
+    let is_contract = |n: &SolAST| {
+        address_cast_of(n).map_or_else(
+            || false,
+            |a| {
+                a.type_string().is_some_and(|t| t.starts_with("contract "))
+                    && variable_key(&a).is_some_and(|x| cond.was_deployed(&x))
+            },
+        )
+    };
+    let (l, r) = (cond.left_expression(), cond.right_expression());
+    cond.node_type().as_deref() == Some("BinaryOperation")
+        && cond.operator().as_deref() == Some("!=")
+        && ((is_contract(&l) && is_zero(&r)) || (is_zero(&l) && is_contract(&r)))
+}
+
+/// Names of the callbacks through which common lenders hand out flash loans.
+static FLASH_LOAN_CALLBACKS: &[&str] = &[
+    "onFlashLoan",
+    "uniswapV2Call",
+    "uniswapV3FlashCallback",
+    "pancakeCall",
+    "executeOperation",
+    "receiveFlashLoan",
+];
+
+/// Checks whether `cond` is of the form `msg.sender == address(this)`
+/// (in either order), with `op` in place of `==`.
+fn is_self_sender_check(cond: &SolAST, op: &str) -> bool {
+    let is_sender = |n: &SolAST| {
+        n.node_type().as_deref() == Some("MemberAccess")
+            && n.get_string("memberName").as_deref() == Some("sender")
+            && n.expression().name().as_deref() == Some("msg")
+    };
+    let is_this = |n: &SolAST| {
+        address_cast_of(n).map_or_else(
+            || false,
+            |a| {
+                a.node_type().as_deref() == Some("Identifier")
+                    && a.name().as_deref() == Some("this")
+            },
+        )
+    };
+    let (l, r) = (cond.left_expression(), cond.right_expression());
+    cond.node_type().as_deref() == Some("BinaryOperation")
+        && cond.operator().as_deref() == Some(op)
+        && ((is_sender(&l) && is_this(&r)) || (is_this(&l) && is_sender(&r)))
+}
+
+/// Checks whether `name` names a minimum, e.g., `MIN_BALANCE`,
+/// `MINIMUM_LIQUIDITY` or `minDeposit`, but not `mint`, `minter`,
+/// `MINT_CAP` or `MINTER_ROLE`.
+fn is_minimum_name(name: &str) -> bool {
+    let after = |prefix: &str, camel_case: bool| {
+        name.strip_prefix(prefix).map_or_else(
+            || false,
+            |rest| {
+                rest.is_empty()
+                    || rest.starts_with('_')
+                    || (camel_case && rest.starts_with(|c: char| c.is_ascii_uppercase()))
+            },
+        )
+    };
+    after("MINIMUM", false) || after("MIN", false) || after("minimum", true) || after("min", true)
+}
+
+/// The operands of a comparison `node` that are state variables or
+/// constants named like a minimum, e.g., `MIN_BALANCE` in
+/// `balance >= MIN_BALANCE`.
+fn minimum_operands(node: &SolAST) -> Vec<SolAST> {
+    if node.node_type().as_deref() != Some("BinaryOperation")
+        || !matches!(node.operator().as_deref(), Some(">=" | ">" | "<=" | "<"))
+    {
+        return vec![];
+    }
+    [node.left_expression(), node.right_expression()]
+        .into_iter()
+        .filter(|o| {
+            let is_state = |info: std::rc::Rc<crate::ContractInfo>| {
+                o.get_object()
+                    .and_then(|v| v["referencedDeclaration"].as_i64())
+                    .is_some_and(|id| info.state_variables.contains(&id))
+            };
+            o.node_type().as_deref() == Some("Identifier")
+                && o.name().map_or_else(|| false, |n| is_minimum_name(&n))
+                && o.get_contract_info().is_some_and(is_state)
+        })
+        .collect()
+}
+
+/// The parameters of the event definition `node` that are `indexed`.
+fn indexed_parameters(node: &SolAST) -> Vec<SolAST> {
+    node.get_node("parameters")
+        .get_node("parameters")
+        .get_object()
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| SolAST::new(v, None))
+        .filter(|p| {
+            p.get_object()
+                .map_or_else(|| false, |v| v["indexed"] == true)
+        })
+        .collect()
+}
+
+/// The contracts listed in the override specifier of the function
+/// definition `node`, e.g., `A` and `B` in `override(A, B)`.
+fn override_list(node: &SolAST) -> Vec<SolAST> {
+    node.get_node("overrides")
+        .get_node("overrides")
+        .get_object()
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| SolAST::new(v, None))
+        .collect()
+}
+
+/// If `node` is a call `abi.encodeWithSelector(selector, ...)`, returns the
+/// selectors of the other functions of the enclosing contract, i.e., those
+/// that `selector` does not already name, either as `X.f.selector` or as a
+/// hex literal. Returns an empty list for anything else.
+fn other_selectors(node: &SolAST) -> Vec<String> {
+    let callee = node.expression();
+    let args = node.arguments();
+    if node.node_type().as_deref() != Some("FunctionCall")
+        || callee.node_type().as_deref() != Some("MemberAccess")
+        || callee.get_string("memberName").as_deref() != Some("encodeWithSelector")
+        || callee.expression().name().as_deref() != Some("abi")
+        || args.is_empty()
+    {
+        return vec![];
+    }
+    let selector = &args[0];
+    let named = if selector.get_string("memberName").as_deref() == Some("selector") {
+        selector.expression().get_string("memberName")
+    } else {
+        None
+    };
+    let literal = literal_selector(selector);
+    node.get_contract_info().map_or_else(Vec::new, |info| {
+        info.selectors
+            .iter()
+            .filter(|(name, sel)| named.as_ref() != Some(name) && literal.as_ref() != Some(sel))
+            .map(|(_, sel)| sel.clone())
+            .collect()
+    })
+}
+
+/// The selector written as a literal by `node`, e.g., `bytes4(0xa9059cbb)`
+/// or `hex"a9059cbb"`, as the eight lowercase hex digits that solc gives as
+/// the `functionSelector` of a function, or `None` for anything else.
+fn literal_selector(node: &SolAST) -> Option<String> {
+    let literal = match node.node_type()?.as_str() {
+        "FunctionCall" if node.get_string("kind").as_deref() == Some("typeConversion") => {
+            node.arguments().first()?.clone()
+        }
+        _ => node.clone(),
+    };
+    if literal.node_type().as_deref() != Some("Literal") {
+        return None;
+    }
+    let digits = match literal.get_string("kind")?.as_str() {
+        "number" => {
+            let value = literal.get_string("value")?.to_lowercase().replace('_', "");
+            format!("{:0>8}", value.strip_prefix("0x")?)
+        }
+        "hexString" => literal.get_string("hexValue")?.to_lowercase(),
+        _ => return None,
+    };
+    (digits.len() == 8 && digits.chars().all(|c| c.is_ascii_hexdigit())).then_some(digits)
+}
+
+/// If the function or modifier definition `node` guards its body with a
+/// boolean lock, e.g., `require(!locked); locked = true; ...; locked = false;`,
+/// returns these three statements. The lock must not be declared in the
+/// function, since only a state variable outlives the call it guards.
+fn inline_guard(node: &SolAST) -> Option<[SolAST; 3]> {
+    if !matches!(
+        node.node_type().as_deref(),
+        Some("FunctionDefinition" | "ModifierDefinition")
+    ) {
+        return None;
+    }
+    let statements = node.get_node("body").statements();
+    let expression = |s: &SolAST| {
+        Some(s.expression()).filter(|_| s.node_type().as_deref() == Some("ExpressionStatement"))
+    };
+    let check = statements.iter().position(|s| {
+        expression(s).map_or_else(
+            || false,
+            |e| is_require_call(&e) && e.arguments()[0].operator().as_deref() == Some("!"),
+        )
+    })?;
+    let lock = expression(&statements[check]).unwrap().arguments()[0].get_node("subExpression");
+    let name = lock.name()?;
+    if lock.node_type()? != "Identifier" || lock.type_string()? != "bool" {
+        return None;
+    }
+    let declared = node
+        .get_node("parameters")
+        .get_node("parameters")
+        .get_object()
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .chain(
+            statements
+                .iter()
+                .filter(|s| s.node_type().as_deref() == Some("VariableDeclarationStatement"))
+                .flat_map(|s| {
+                    s.get_node("declarations")
+                        .get_object()
+                        .and_then(|v| v.as_array().cloned())
+                        .unwrap_or_default()
+                }),
+        )
+        .any(|d| d["name"].as_str() == Some(name.as_str()));
+    if declared {
+        return None;
+    }
+    let sets = |s: &SolAST, value: &str| {
+        expression(s).map_or_else(
+            || false,
+            |e| {
+                e.node_type().as_deref() == Some("Assignment")
+                    && e.operator().as_deref() == Some("=")
+                    && e.get_node("leftHandSide").name().as_deref() == Some(name.as_str())
+                    && e.get_node("rightHandSide").value().as_deref() == Some(value)
+            },
+        )
+    };
+    let lock_at = check + statements[check..].iter().position(|s| sets(s, "true"))?;
+    let unlock_at = lock_at
+        + statements[lock_at..]
+            .iter()
+            .position(|s| sets(s, "false"))?;
+    Some([
+        statements[check].clone(),
+        statements[lock_at].clone(),
+        statements[unlock_at].clone(),
+    ])
+}
+
+/// Returns the value of a number `Literal` node, taking underscores,
+/// hex and scientific notation, and subdenominations such as `ether`
+/// or `days` into account. Returns `None` for anything else, such as
+/// fractional literals.
+fn number_value(node: &SolAST) -> Option<BigUint> {
+    if node.node_type()? != "Literal" || node.kind()? != "number" {
+        return None;
+    }
+    let value = node.value()?.replace('_', "");
+    let n = if let Some(hex) = value.strip_prefix("0x") {
+        BigUint::parse_bytes(hex.as_bytes(), 16)?
+    } else if let Some((m, e)) = value.split_once(['e', 'E']) {
+        let e: u32 = e.parse().ok()?;
+        if e > MAX_CONSTANT_BITS {
+            return None;
+        }
+        BigUint::parse_bytes(m.as_bytes(), 10)? * BigUint::from(10u32).pow(e)
+    } else {
+        BigUint::parse_bytes(value.as_bytes(), 10)?
+    };
+    let unit: u64 = match node.get_string("subdenomination").as_deref() {
+        None | Some("wei") | Some("seconds") => 1,
+        Some("gwei") => 1_000_000_000,
+        Some("ether") => 1_000_000_000_000_000_000,
+        Some("minutes") => 60,
+        Some("hours") => 3_600,
+        Some("days") => 86_400,
+        Some("weeks") => 604_800,
+        Some(_) => return None,
+    };
+    Some(n * unit)
+}
+
+/// Like `number_value`, but only for values that fit in a `u128`.
+fn u128_value(node: &SolAST) -> Option<u128> {
+    number_value(node).and_then(|n| u128::try_from(n).ok())
+}
+
+/// The number of bits past which `constant_value` gives up, so that
+/// something like `10 ** 10 ** 10` is never computed.
+const MAX_CONSTANT_BITS: u32 = 1024;
+
+/// Computes the value of a number literal, or of a (parenthesized)
+/// arithmetic expression over number literals, the way solc does for
+/// its arbitrary-precision literal types. Returns `None` if the value is
+/// negative, fractional (such as `5 / 2`), too large, or a division by zero.
+fn constant_value(node: &SolAST) -> Option<BigUint> {
+    match node.node_type()?.as_str() {
+        "Literal" => number_value(node),
+        "TupleExpression" => match node.components().as_slice() {
+            [inner] => constant_value(inner),
+            _ => None,
+        },
+        "BinaryOperation" => {
+            let l = constant_value(&node.left_expression())?;
+            let r = constant_value(&node.right_expression())?;
+            let small = |n: &BigUint| u32::try_from(n).ok().filter(|n| *n <= MAX_CONSTANT_BITS);
+            let res = match node.operator()?.as_str() {
+                "+" => l + r,
+                "-" if l >= r => l - r,
+                "*" => l * r,
+                "/" | "%" if r == BigUint::default() => return None,
+                "/" if (&l % &r) == BigUint::default() => l / r,
+                "%" => l % r,
+                "**" => {
+                    let e = small(&r)?;
+                    if l.bits() * u64::from(e) > u64::from(MAX_CONSTANT_BITS) {
+                        return None;
+                    }
+                    l.pow(e)
+                }
+                "<<" => {
+                    let e = small(&r)?;
+                    if l.bits() + u64::from(e) > u64::from(MAX_CONSTANT_BITS) {
+                        return None;
+                    }
+                    l << e
+                }
+                ">>" => l >> small(&r)?,
+                "&" => l & r,
+                "|" => l | r,
+                "^" => l ^ r,
+                _ => return None,
+            };
+            Some(res)
+        }
+        _ => None,
+    }
+}
+
+/// Computes the value of a `BinaryOperation` over number literals,
+/// e.g., `2 ** 8` folds to `256`, `2 ** 256 - 1` to the largest `uint256`
+/// and `1 < 2` to `true`. Returns `None` if the node is not of this
+/// shape or its value cannot be written as a `uint256` literal.
+fn fold_constant(node: &SolAST) -> Option<String> {
+    if node.node_type()? != "BinaryOperation" {
+        return None;
+    }
+    let compare = |cmp: fn(&BigUint, &BigUint) -> bool| {
+        let l = constant_value(&node.left_expression())?;
+        let r = constant_value(&node.right_expression())?;
+        Some(cmp(&l, &r).to_string())
+    };
+    match node.operator()?.as_str() {
+        "==" => compare(|l, r| l == r),
+        "!=" => compare(|l, r| l != r),
+        "<" => compare(|l, r| l < r),
+        ">" => compare(|l, r| l > r),
+        "<=" => compare(|l, r| l <= r),
+        ">=" => compare(|l, r| l >= r),
+        _ => constant_value(node)
+            .filter(|v| v.bits() <= 256)
+            .map(|v| v.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use serde_json::{json, Value};
+    use std::collections::HashSet;
+    use std::fs;
+
+    /// The `src` field for the first occurrence of `text` in `source`.
+    fn src(source: &str, text: &str) -> String {
+        let start = source.find(text).expect("text not in source");
+        format!("{}:{}:0", start, text.len())
+    }
+
+    /// The `src` field for `text` within the first occurrence of `context`
+    /// in `source`.
+    fn src_in(source: &str, context: &str, text: &str) -> String {
+        let start = source.find(context).expect("context not in source")
+            + context.find(text).expect("text not in context");
+        format!("{}:{}:0", start, text.len())
+    }
+
+    /// Like `number`, but for the last occurrence of `text` in `source`.
+    fn last_number(source: &str, text: &str) -> Value {
+        let start = source.rfind(text).expect("text not in source");
+        json!({"nodeType": "Literal", "kind": "number", "value": text, "src": format!("{}:{}:0", start, text.len())})
+    }
+
+    fn ident(source: &str, text: &str) -> Value {
+        json!({"nodeType": "Identifier", "name": text, "src": src(source, text)})
+    }
+
+    fn number(source: &str, text: &str) -> Value {
+        json!({"nodeType": "Literal", "kind": "number", "value": text, "src": src(source, text)})
+    }
+
+    /// Build a `BinaryOperation` node spanning `text` in `source`.
+    fn binop(source: &str, text: &str, op: &str, left: Value, right: Value) -> SolAST {
+        SolAST::new(
+            json!({
+                "nodeType": "BinaryOperation",
+                "operator": op,
+                "src": src(source, text),
+                "leftExpression": left,
+                "rightExpression": right,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_parse_name() {
+        assert_eq!(
+            MutationType::parse_name("binary-op-mutation"),
+            Ok(MutationType::BinaryOpMutation)
+        );
+        assert_eq!(
+            MutationType::parse_name("BinaryOpMutation"),
+            Ok(MutationType::BinaryOpMutation)
+        );
+        assert_eq!(
+            MutationType::parse_name("binary-op-mutaton").unwrap_err(),
+            "unknown mutation `binary-op-mutaton`, did you mean `binary-op-mutation`?"
+        );
+        assert_eq!(
+            MutationType::parse_name("RequireMutaion").unwrap_err(),
+            "unknown mutation `RequireMutaion`, did you mean `require-mutation`?"
+        );
+        let err = MutationType::parse_name("nonsense").unwrap_err();
+        assert!(
+            err.starts_with("unknown mutation `nonsense`, expected one of: binary-op-mutation,")
+        );
+    }
+
+    /// The operators `BinaryOpMutation` puts in place of `op` in `text`
+    /// over many draws.
+    fn binary_op_replacements(text: &str, op: &str, left: &str, right: &str) -> HashSet<String> {
+        let s = format!("x = {};", text);
+        let is_literal = |t: &str| t.chars().all(|c| c.is_ascii_digit());
+        let l = if is_literal(left) {
+            number(&s, left)
+        } else {
+            ident(&s, left)
+        };
+        let r = if is_literal(right) {
+            last_number(&s, right)
+        } else {
+            ident(&s, right)
+        };
+        let node = binop(&s, text, op, l, r);
+        let mut rand = Pcg64::seed_from_u64(0);
+        (0..200)
+            .map(|_| {
+                let mutant =
+                    MutationType::BinaryOpMutation.mutate_randomly(&node, s.as_bytes(), &mut rand);
+                mutant
+                    .strip_prefix(&format!("x = {} ", left))
+                    .and_then(|m| m.strip_suffix(&format!(" {};", right)))
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_binary_op_mutation_skips_identities() {
+        let all: HashSet<String> = ["+", "-", "*", "/", "%", "**"]
+            .iter()
+            .map(|o| o.to_string())
+            .collect();
+        let excluded = [
+            ("a + 0", "+", vec!["+", "-"]),
+            ("a - 0", "-", vec!["+", "-"]),
+            ("a * 1", "*", vec!["*", "/", "**"]),
+            ("a / 1", "/", vec!["*", "/", "**"]),
+        ];
+        for (text, op, identities) in excluded {
+            let (left, right) = text.split_once(&format!(" {} ", op)).unwrap();
+            let ops = binary_op_replacements(text, op, left, right);
+            let expected: HashSet<String> = all
+                .iter()
+                .filter(|o| !identities.contains(&o.as_str()))
+                .cloned()
+                .collect();
+            assert_eq!(ops, expected, "{}", text);
+        }
+        // `**` is only replaced by `*` and `+`, of which `*` is an identity here.
+        assert_eq!(
+            binary_op_replacements("a ** 1", "**", "a", "1"),
+            HashSet::from(["+".to_string()])
+        );
+        // Operands that are not such literals are unaffected.
+        for (text, op, left, right) in [
+            ("a + b", "+", "a", "b"),
+            ("a * 2", "*", "a", "2"),
+            ("0 + a", "+", "0", "a"),
+            ("1 * a", "*", "1", "a"),
+        ] {
+            assert_eq!(
+                binary_op_replacements(text, op, left, right),
+                all,
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_binary_op_mutation_respects_types() {
+        let typed = |s: &str, text: &str, type_string: &str, type_id: &str| {
+            let mut v = ident(s, text);
+            v["typeDescriptions"] = json!({"typeString": type_string, "typeIdentifier": type_id});
+            v
+        };
+        let ops = |s: &str, node: &SolAST| -> HashSet<&str> {
+            assert!(MutationType::BinaryOpMutation.is_mutation_point(node));
+            binary_op_candidates(node, s.as_bytes())
+                .into_iter()
+                .collect()
+        };
+        let all = HashSet::from(["+", "-", "*", "/", "%", "**"]);
+
+        for (pragma, exponent) in [
+            ("pragma solidity ^0.8.0;", false),
+            ("pragma solidity >0.7.0;", false),
+            ("", false),
+            ("pragma solidity ^0.7.6;", true),
+        ] {
+            let s = format!("{} int256 d = a - b;", pragma);
+            let a = typed(&s, "a", "int256", "t_int256");
+            let b = typed(&s, "b", "int256", "t_int256");
+            let node = binop(&s, "a - b", "-", a, b);
+            let mut expected = all.clone();
+            if !exponent {
+                expected.remove("**");
+            }
+            assert_eq!(ops(&s, &node), expected, "{}", pragma);
         }
+
+        // The version of the compiler, when known, goes before the pragmas.
+        for (version, exponent) in [((0, 7, 6), true), ((0, 8, 21), false)] {
+            let s = "pragma solidity >=0.7.0; int256 d = a - b;";
+            let a = typed(s, "a", "int256", "t_int256");
+            let b = typed(s, "b", "int256", "t_int256");
+            let node = binop(s, "a - b", "-", a, b).with_solc_version(Some(version));
+            assert_eq!(ops(s, &node).contains("**"), exponent, "{:?}", version);
+        }
+
+        let s = "pragma solidity ^0.8.0; uint256 d = a - b;";
+        let a = typed(s, "a", "uint256", "t_uint256");
+        let b = typed(s, "b", "uint256", "t_uint256");
+        assert_eq!(ops(s, &binop(s, "a - b", "-", a, b)), all);
+
+        let s = "pragma solidity ^0.8.0; Price d = a + b;";
+        let a = typed(s, "a", "Price", "t_userDefinedValueType$_Price_$4");
+        let b = typed(s, "b", "Price", "t_userDefinedValueType$_Price_$4");
+        let mut expected = all.clone();
+        expected.remove("%");
+        assert_eq!(ops(s, &binop(s, "a + b", "+", a, b)), expected);
+
+        let s = "pragma solidity ^0.8.0; int256 d = a ** b;";
+        let a = typed(s, "a", "int256", "t_int256");
+        let b = typed(s, "b", "uint256", "t_uint256");
+        assert_eq!(
+            ops(s, &binop(s, "a ** b", "**", a, b)),
+            HashSet::from(["*", "+"])
+        );
+
+        let s = "bool same = a == b;";
+        let a = typed(s, "a", "bytes32", "t_bytes32");
+        let b = typed(s, "b", "bytes32", "t_bytes32");
+        let node = binop(s, "a == b", "==", a, b);
+        assert!(!MutationType::BinaryOpMutation.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_unary_operator_mutation_prefix() {
+        // The file starts with an operator character, which used to make
+        // postfix operations look like prefix ones and vice versa.
+        let mt = MutationType::UnaryOperatorMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "-1; x = ++i;";
+        let node = SolAST::new(
+            json!({"nodeType": "UnaryOperation", "operator": "++", "src": src(s, "++i"), "subExpression": ident(s, "i")}),
+            None,
+        );
+        for _ in 0..10 {
+            let mutant = mt.mutate_randomly(&node, s.as_bytes(), &mut rand);
+            assert!(
+                mutant.starts_with("-1; x = ") && mutant.ends_with("i;"),
+                "{}",
+                mutant
+            );
+        }
+        let s = "+1; x = i--;";
+        let node = SolAST::new(
+            json!({"nodeType": "UnaryOperation", "operator": "--", "src": src(s, "i--"), "subExpression": ident(s, "i")}),
+            None,
+        );
+        for _ in 0..10 {
+            let mutant = mt.mutate_randomly(&node, s.as_bytes(), &mut rand);
+            assert!(mutant.starts_with("+1; x = i"), "{}", mutant);
+        }
+    }
+
+    #[test]
+    fn test_sub_expression_mutation() {
+        let source = "x = (a * b) / c;";
+        let node = binop(
+            source,
+            "(a * b) / c",
+            "/",
+            ident(source, "(a * b)"),
+            ident(source, "c"),
+        );
+        let mt = MutationType::SubExpressionMutation;
+        assert!(mt.is_mutation_point(&node));
+        let mut rand = Pcg64::seed_from_u64(0);
+        for _ in 0..10 {
+            let mutant = mt.mutate_randomly(&node, source.as_bytes(), &mut rand);
+            assert!(mutant == "x = (a * b);" || mutant == "x = c;");
+        }
+    }
+
+    #[test]
+    fn test_mapping_key_mutation() {
+        let s = "balances[from][token] += amount;";
+        let inner = json!({
+            "nodeType": "IndexAccess",
+            "src": src(s, "balances[from]"),
+            "baseExpression": ident(s, "balances"),
+            "indexExpression": ident(s, "from"),
+        });
+        let node = SolAST::new(
+            json!({
+                "nodeType": "IndexAccess",
+                "src": src(s, "balances[from][token]"),
+                "baseExpression": inner.clone(),
+                "indexExpression": ident(s, "token"),
+            }),
+            None,
+        );
+        let mt = MutationType::MappingKeyMutation;
+        assert!(mt.is_mutation_point(&node));
+        assert!(!mt.is_mutation_point(&SolAST::new(inner, None)));
+        let array_type = json!({
+            "nodeType": "IndexAccess",
+            "src": src(s, "balances[from][token]"),
+            "baseExpression": {
+                "nodeType": "IndexAccess",
+                "src": src(s, "balances[from]"),
+                "baseExpression": ident(s, "balances"),
+            },
+        });
+        assert!(!mt.is_mutation_point(&SolAST::new(array_type, None)));
+        let mut rand = Pcg64::seed_from_u64(0);
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "balances[token][from] += amount;"
+        );
+    }
+
+    /// Build `address(<arg>)` for the occurrence of `text` in `source`.
+    fn address_cast(source: &str, text: &str, arg: Value) -> Value {
+        json!({
+            "nodeType": "FunctionCall",
+            "kind": "typeConversion",
+            "src": src(source, text),
+            "typeDescriptions": {"typeString": "address"},
+            "expression": {"nodeType": "ElementaryTypeNameExpression", "src": src(source, "address")},
+            "arguments": [arg],
+        })
+    }
+
+    fn require(source: &str, text: &str, cond: Value) -> SolAST {
+        SolAST::new(
+            json!({
+                "nodeType": "FunctionCall",
+                "kind": "functionCall",
+                "src": src(source, text),
+                "expression": ident(source, "require"),
+                "arguments": [cond],
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_deployment_check_mutation() {
+        let s = "pair = new Pair(); require(address(pair) != address(0)); x = 1;";
+        let pair = json!({
+            "nodeType": "Identifier",
+            "name": "pair",
+            "src": src_in(s, "address(pair)", "pair"),
+            "typeDescriptions": {"typeString": "contract Pair"},
+        });
+        let cond = json!({
+            "nodeType": "BinaryOperation",
+            "operator": "!=",
+            "src": src(s, "address(pair) != address(0)"),
+            "leftExpression": address_cast(s, "address(pair)", pair),
+            "rightExpression": address_cast(s, "address(0)", number(s, "0")),
+        });
+        let check = |cond: Value| {
+            require(s, "require(address(pair) != address(0))", cond)
+                .element
+                .unwrap()
+        };
+        let deploy = json!({
+            "nodeType": "Assignment",
+            "operator": "=",
+            "src": src(s, "pair = new Pair()"),
+            "leftHandSide": {"nodeType": "Identifier", "name": "pair", "src": src(s, "pair")},
+            "rightHandSide": {
+                "nodeType": "FunctionCall",
+                "src": src(s, "new Pair()"),
+                "expression": {
+                    "nodeType": "NewExpression",
+                    "src": src(s, "new Pair"),
+                    "typeName": {"nodeType": "UserDefinedTypeName", "src": src(s, "Pair")},
+                },
+                "arguments": [],
+            },
+        });
+        let mt = MutationType::DeploymentCheckMutation;
+        let points = |statements: Vec<Value>| {
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionDefinition",
+                    "name": "f",
+                    "body": {"nodeType": "Block", "statements": statements},
+                }),
+                None,
+            )
+            .traverse(
+                |n| mt.is_mutation_point(n).then(|| n.clone()),
+                |_| false,
+                |_| true,
+            )
+        };
+        let found = points(vec![deploy.clone(), check(cond.clone())]);
+        assert_eq!(found.len(), 1);
+        let mut rand = Pcg64::seed_from_u64(0);
+        assert_eq!(
+            mt.mutate_randomly(&found[0], s.as_bytes(), &mut rand),
+            "pair = new Pair(); /*require(address(pair) != address(0))*/; x = 1;"
+        );
+
+        // Not a contract that the function deployed, e.g., a parameter,
+        // or one deployed only after the check.
+        let param = "require(address(token) != address(0));";
+        let token = json!({
+            "nodeType": "Identifier",
+            "name": "token",
+            "src": src(param, "token"),
+            "typeDescriptions": {"typeString": "contract IERC20"},
+        });
+        let token_check = json!({
+            "nodeType": "BinaryOperation",
+            "operator": "!=",
+            "src": src(param, "address(token) != address(0)"),
+            "leftExpression": address_cast(param, "address(token)", token),
+            "rightExpression": address_cast(param, "address(0)", number(param, "0")),
+        });
+        let token_check = require(param, "require(address(token) != address(0))", token_check);
+        assert!(points(vec![deploy.clone(), token_check.element.unwrap()]).is_empty());
+        assert!(points(vec![check(cond.clone()), deploy.clone()]).is_empty());
+        // Not a contract being checked.
+        let mut account = cond.clone();
+        account["leftExpression"]["arguments"][0]["typeDescriptions"]["typeString"] =
+            json!("address");
+        assert!(points(vec![deploy.clone(), check(account)]).is_empty());
+        // Not compared against the zero address.
+        let mut other = cond;
+        other["rightExpression"]["arguments"][0]["value"] = json!("1");
+        assert!(points(vec![deploy, check(other)]).is_empty());
+    }
+
+    #[test]
+    fn test_exponent_mutation() {
+        let mt = MutationType::ExponentMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "x = a ** 2;";
+        let node = binop(s, "a ** 2", "**", ident(s, "a"), number(s, "2"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "x = a * 2;"
+        );
+        let s = "x = a * 2;";
+        let node = binop(s, "a * 2", "*", ident(s, "a"), number(s, "2"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "x = a ** 2;"
+        );
+
+        // Only small literals are turned into exponents.
+        let s = "x = a * 11;";
+        let node = binop(s, "a * 11", "*", ident(s, "a"), number(s, "11"));
+        assert!(!mt.is_mutation_point(&node));
+        let s = "x = a * b;";
+        let node = binop(s, "a * b", "*", ident(s, "a"), ident(s, "b"));
+        assert!(!mt.is_mutation_point(&node));
+        let s = "x = 2 * a;";
+        let node = binop(s, "2 * a", "*", number(s, "2"), ident(s, "a"));
+        assert!(!mt.is_mutation_point(&node));
+        let s = "x = a + 2;";
+        let node = binop(s, "a + 2", "+", ident(s, "a"), number(s, "2"));
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    /// Build `(<inner>)` for the occurrence of `text` in `source`.
+    fn parens(source: &str, text: &str, inner: SolAST) -> Value {
+        json!({
+            "nodeType": "TupleExpression",
+            "src": src(source, text),
+            "components": [inner.element.unwrap()],
+        })
+    }
+
+    #[test]
+    fn test_precision_order_mutation() {
+        let mt = MutationType::PrecisionOrderMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "fee = (amount / 10000) * rate;";
+        let div = binop(
+            s,
+            "amount / 10000",
+            "/",
+            ident(s, "amount"),
+            number(s, "10000"),
+        );
+        let left = parens(s, "(amount / 10000)", div);
+        let node = binop(s, "(amount / 10000) * rate", "*", left, ident(s, "rate"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "fee = (amount * rate) / 10000;"
+        );
+
+        let s = "fee = rate * amount / 10000;";
+        let mul = binop(
+            s,
+            "rate * amount",
+            "*",
+            ident(s, "rate"),
+            ident(s, "amount"),
+        );
+        let node = binop(
+            s,
+            "rate * amount / 10000",
+            "/",
+            mul.element.unwrap(),
+            number(s, "10000"),
+        );
+        // Already divides last.
+        assert!(!mt.is_mutation_point(&node));
+
+        let s = "fee = rate * (amount / 10000);";
+        let div = binop(
+            s,
+            "amount / 10000",
+            "/",
+            ident(s, "amount"),
+            number(s, "10000"),
+        );
+        let right = parens(s, "(amount / 10000)", div);
+        let node = binop(s, "rate * (amount / 10000)", "*", ident(s, "rate"), right);
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "fee = (rate * amount) / 10000;"
+        );
+
+        let s = "x = a * b;";
+        let node = binop(s, "a * b", "*", ident(s, "a"), ident(s, "b"));
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_add_sub_mutation() {
+        let mt = MutationType::AddSubMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "x = a + b;";
+        let node = binop(s, "a + b", "+", ident(s, "a"), ident(s, "b"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "x = a - b;"
+        );
+        let s = "x = a - b;";
+        let node = binop(s, "a - b", "-", ident(s, "a"), ident(s, "b"));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "x = a + b;"
+        );
+        let s = "x = a * b;";
+        let node = binop(s, "a * b", "*", ident(s, "a"), ident(s, "b"));
+        assert!(!mt.is_mutation_point(&node));
+
+        let s = "balances[to] += amount;";
+        let assignment = |op: &str| {
+            SolAST::new(
+                json!({
+                    "nodeType": "Assignment",
+                    "operator": op,
+                    "src": src(s, "balances[to] += amount"),
+                    "leftHandSide": {"nodeType": "IndexAccess", "src": src(s, "balances[to]")},
+                    "rightHandSide": ident(s, "amount"),
+                }),
+                None,
+            )
+        };
+        let node = assignment("+=");
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "balances[to] -= amount;"
+        );
+        assert!(!mt.is_mutation_point(&assignment("=")));
+    }
+
+    /// Build a `FunctionDefinition` spanning all of `source`, whose return
+    /// parameters are given as `(type string, text of the type, name)`.
+    fn function(source: &str, returns: &[(&str, &str, &str)], body: &str) -> SolAST {
+        let returns_at = source.find("returns").unwrap_or(0);
+        let params: Vec<Value> = returns
+            .iter()
+            .map(|(type_string, text, name)| {
+                json!({
+                    "nodeType": "VariableDeclaration",
+                    "name": name,
+                    "typeDescriptions": {"typeString": type_string},
+                    "typeName": {"nodeType": "UserDefinedTypeName", "src": format!("{}:{}:0", returns_at + source[returns_at..].find(text).unwrap(), text.len())},
+                })
+            })
+            .collect();
+        // Stands in for the statements, which the mutation only counts.
+        let statements = if body == "{ }" {
+            json!([])
+        } else {
+            json!([{"nodeType": "ExpressionStatement"}, {"nodeType": "ExpressionStatement"}])
+        };
+        let body = json!({"nodeType": "Block", "src": src(source, body), "statements": statements});
+        SolAST::new(
+            json!({
+                "nodeType": "FunctionDefinition",
+                "kind": "function",
+                "src": src(source, source),
+                "returnParameters": {"nodeType": "ParameterList", "parameters": params},
+                "body": body,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_function_stub_mutation() {
+        let mt = MutationType::FunctionStubMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let body = "{ x = 1; emit E(x); }";
+        let mut stub = |s: &str, returns: &[(&str, &str, &str)]| {
+            let node = function(s, returns, body);
+            assert!(mt.is_mutation_point(&node));
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand)
+        };
+
+        let s = format!("function f() public {}", body);
+        assert_eq!(stub(&s, &[]), "function f() public { }");
+        let s = format!("function f() public returns (uint256) {}", body);
+        assert_eq!(
+            stub(&s, &[("uint256", "uint256", "")]),
+            "function f() public returns (uint256) { return 0; }"
+        );
+        let s = format!(
+            "function f() public returns (uint8, bool, address, address payable, string memory, bytes32, IERC20, uint256[] memory) {}",
+            body
+        );
+        let returns = [
+            ("uint8", "uint8", ""),
+            ("bool", "bool", ""),
+            ("address", "address", ""),
+            ("address payable", "address payable", ""),
+            ("string memory", "string", ""),
+            ("bytes32", "bytes32", ""),
+            ("contract IERC20", "IERC20", ""),
+            ("uint256[] memory", "uint256[]", ""),
+        ];
+        assert_eq!(
+            stub(&s, &returns),
+            "function f() public returns (uint8, bool, address, address payable, string memory, bytes32, IERC20, uint256[] memory) \
+             { return (0, false, address(0), payable(address(0)), \"\", bytes32(0), IERC20(address(0)), new uint256[](0)); }"
+        );
+        // Named returns and types without a simple default get an empty body.
+        let s = format!("function f() public returns (uint256 a, bool b) {}", body);
+        let returns = [("uint256", "uint256", "a"), ("bool", "bool", "b")];
+        assert_eq!(
+            stub(&s, &returns),
+            "function f() public returns (uint256 a, bool b) { }"
+        );
+        let s = format!("function f() public returns (uint256, S memory) {}", body);
+        let returns = [("uint256", "uint256", ""), ("struct C.S memory", "S", "")];
+        assert_eq!(
+            stub(&s, &returns),
+            "function f() public returns (uint256, S memory) { }"
+        );
+    }
+
+    #[test]
+    fn test_function_stub_mutation_skips() {
+        let mt = MutationType::FunctionStubMutation;
+        let s = "function f() public { }";
+        assert!(!mt.is_mutation_point(&function(s, &[], "{ }")));
+        let s = "constructor() { x = 1; emit E(x); }";
+        let mut node = function(s, &[], "{ x = 1; emit E(x); }");
+        node.element.as_mut().unwrap()["kind"] = json!("constructor");
+        assert!(!mt.is_mutation_point(&node));
+        let s = "function f() public returns (uint256) { return 0; }";
+        let mut node = function(s, &[("uint256", "uint256", "")], "{ return 0; }");
+        node.element.as_mut().unwrap()["body"]["statements"] = json!([{
+            "nodeType": "Return",
+            "expression": {"nodeType": "Literal", "src": src(s, "0")},
+        }]);
+        assert!(!mt.is_mutation_point(&node));
+        // Not implemented.
+        let s = "function f() external returns (uint256) { x = 1; emit E(x); }";
+        let mut node = function(s, &[("uint256", "uint256", "")], "{ x = 1; emit E(x); }");
+        node.element.as_mut().unwrap()["body"] = Value::Null;
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    /// Build `<base>.<method>(amount)` for the occurrence of `text` in `source`.
+    fn eth_call(source: &str, text: &str, base: &str, base_type: &str, method: &str) -> SolAST {
+        SolAST::new(
+            json!({
+                "nodeType": "FunctionCall",
+                "src": src(source, text),
+                "expression": {
+                    "nodeType": "MemberAccess",
+                    "memberName": method,
+                    "src": src(source, &(base.to_string() + "." + method)),
+                    "expression": {
+                        "nodeType": "FunctionCall",
+                        "src": src(source, base),
+                        "typeDescriptions": {"typeString": base_type},
+                    },
+                },
+                "arguments": [ident(source, "amount")],
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_eth_send_method_mutation() {
+        let mt = MutationType::EthSendMethodMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "balances[msg.sender] = 0; payable(msg.sender).transfer(amount);";
+        let node = eth_call(
+            s,
+            "payable(msg.sender).transfer(amount)",
+            "payable(msg.sender)",
+            "address payable",
+            "transfer",
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "balances[msg.sender] = 0; payable(msg.sender).send(amount);"
+        );
+
+        let s = "balances[msg.sender] = 0; payable(msg.sender).send(amount);";
+        let node = eth_call(
+            s,
+            "payable(msg.sender).send(amount)",
+            "payable(msg.sender)",
+            "address payable",
+            "send",
+        );
+        assert!(mt.is_mutation_point(&node));
+        let mutants: HashSet<String> = (0..20)
+            .map(|_| mt.mutate_randomly(&node, s.as_bytes(), &mut rand))
+            .collect();
+        assert_eq!(
+            mutants,
+            HashSet::from([
+                "balances[msg.sender] = 0; payable(msg.sender).transfer(amount);".to_string(),
+                "balances[msg.sender] = 0; require(payable(msg.sender).send(amount));".to_string(),
+            ])
+        );
+
+        // ERC20 transfers are left alone.
+        let s = "token.transfer(amount);";
+        let node = eth_call(
+            s,
+            "token.transfer(amount)",
+            "token",
+            "contract IERC20",
+            "transfer",
+        );
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_slice_bounds_mutation() {
+        let mt = MutationType::SliceBoundsMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let slice = |s: &str, text: &str, start: Value, end: Value| {
+            SolAST::new(
+                json!({
+                    "nodeType": "IndexRangeAccess",
+                    "src": src(s, text),
+                    "baseExpression": ident(s, "data"),
+                    "startExpression": start,
+                    "endExpression": end,
+                }),
+                None,
+            )
+        };
+        let mut mutants = |node: &SolAST, s: &str| -> HashSet<String> {
+            assert!(mt.is_mutation_point(node));
+            (0..100)
+                .map(|_| mt.mutate_randomly(node, s.as_bytes(), &mut rand))
+                .collect()
+        };
+
+        let s = "a = abi.decode(data[4:36], (address));";
+        let node = slice(s, "data[4:36]", number(s, "4"), number(s, "36"));
+        let expected: HashSet<String> =
+            ["5:36", "3:36", "0:36", ":36", "4:37", "4:35", "4:0", "4:"]
+                .iter()
+                .map(|b| format!("a = abi.decode(data[{}], (address));", b))
+                .collect();
+        assert_eq!(mutants(&node, s), expected);
+
+        let s = "b = bytes4(data[:4]);";
+        let node = slice(s, "data[:4]", Value::Null, last_number(s, "4"));
+        let expected: HashSet<String> = ["5", "3", "0", ""]
+            .iter()
+            .map(|b| format!("b = bytes4(data[:{}]);", b))
+            .collect();
+        assert_eq!(mutants(&node, s), expected);
+
+        // Removing a start of `0` would change nothing.
+        let s = "b = bytes4(data[0:4]);";
+        let node = slice(s, "data[0:4]", number(s, "0"), last_number(s, "4"));
+        let expected: HashSet<String> = ["1:4", "0:5", "0:3", "0:0", "0:"]
+            .iter()
+            .map(|b| format!("b = bytes4(data[{}]);", b))
+            .collect();
+        assert_eq!(mutants(&node, s), expected);
+
+        let s = "p = data[offset + 4:];";
+        let start = binop(s, "offset + 4", "+", ident(s, "offset"), number(s, "4"));
+        let node = slice(s, "data[offset + 4:]", start.element.unwrap(), Value::Null);
+        let expected: HashSet<String> = ["(offset + 4) + 1:", "(offset + 4) - 1:", "0:", ":"]
+            .iter()
+            .map(|b| format!("p = data[{}];", b))
+            .collect();
+        assert_eq!(mutants(&node, s), expected);
+
+        let s = "p = data[:];";
+        assert!(!mt.is_mutation_point(&slice(s, "data[:]", Value::Null, Value::Null)));
+    }
+
+    #[test]
+    fn test_revert_message_mutation() {
+        let mt = MutationType::RevertMessageMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let call = |s: &str, f: &str, args: Vec<Value>| {
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionCall",
+                    "src": src(s, s.trim_end_matches(';')),
+                    "expression": ident(s, f),
+                    "arguments": args,
+                }),
+                None,
+            )
+        };
+        let string = |s: &str, text: &str| json!({"nodeType": "Literal", "kind": "string", "value": text.trim_matches('"'), "src": src(s, text)});
+
+        let s = r#"require(amount > 0, "zero amount");"#;
+        let node = call(
+            s,
+            "require",
+            vec![ident(s, "amount"), string(s, r#""zero amount""#)],
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            r#"require(amount > 0, "MUTATION");"#
+        );
+
+        let s = r#"revert("not owner");"#;
+        let node = call(s, "revert", vec![string(s, r#""not owner""#)]);
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            r#"revert("MUTATION");"#
+        );
+
+        let s = "require(ok);";
+        assert!(!mt.is_mutation_point(&call(s, "require", vec![ident(s, "ok")])));
+        let s = "require(ok, reason);";
+        let node = call(s, "require", vec![ident(s, "ok"), ident(s, "reason")]);
+        assert!(!mt.is_mutation_point(&node));
+        let s = r#"revert("MUTATION");"#;
+        let node = call(s, "revert", vec![string(s, r#""MUTATION""#)]);
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_erc721_transfer_mutation() {
+        let mt = MutationType::ERC721TransferMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let transfer = |s: &str, method: &str, to: &str, fn_type: &str| {
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionCall",
+                    "src": src(s, s.trim_end_matches(';')),
+                    "expression": {
+                        "nodeType": "MemberAccess",
+                        "memberName": method,
+                        "src": src(s, &("nft.".to_string() + method)),
+                        "expression": ident(s, "nft"),
+                        "typeDescriptions": {"typeString": fn_type},
+                    },
+                    "arguments": [ident(s, "from"), ident(s, to), ident(s, "tokenId")],
+                }),
+                None,
+            )
+        };
+        let erc721 = "function (address,address,uint256) external";
+
+        // To a contract, which may or may not implement `onERC721Received`.
+        let s = "nft.safeTransferFrom(from, vault, tokenId);";
+        let node = transfer(s, "safeTransferFrom", "vault", erc721);
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "nft.transferFrom(from, vault, tokenId);"
+        );
+
+        // To an EOA.
+        let s = "nft.transferFrom(from, owner, tokenId);";
+        let node = transfer(s, "transferFrom", "owner", erc721);
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "nft.safeTransferFrom(from, owner, tokenId);"
+        );
+
+        // ERC20 transfers are left alone.
+        let node = transfer(
+            s,
+            "transferFrom",
+            "owner",
+            "function (address,address,uint256) external returns (bool)",
+        );
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_vote_weight_mutation() {
+        let mt = MutationType::VoteWeightMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let call = |s: &str, text: &str, callee: Value| {
+            SolAST::new(
+                json!({"nodeType": "FunctionCall", "src": src(s, text), "expression": callee}),
+                None,
+            )
+        };
+        let member = |s: &str, base: &str, name: &str| {
+            json!({
+                "nodeType": "MemberAccess",
+                "memberName": name,
+                "src": src(s, &format!("{}.{}", base, name)),
+                "expression": ident(s, base),
+            })
+        };
+
+        let s = "uint256 weight = token.getPastVotes(voter, snapshot); votes += weight;";
+        let node = call(
+            s,
+            "token.getPastVotes(voter, snapshot)",
+            member(s, "token", "getPastVotes"),
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "uint256 weight = 0; votes += weight;"
+        );
+
+        let s = "require(getVotes(msg.sender) >= threshold);";
+        let node = call(s, "getVotes(msg.sender)", ident(s, "getVotes"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "require(0 >= threshold);"
+        );
+
+        let s = "uint256 b = token.balanceOfAt(voter, id);";
+        let node = call(
+            s,
+            "token.balanceOfAt(voter, id)",
+            member(s, "token", "balanceOfAt"),
+        );
+        assert!(mt.is_mutation_point(&node));
+
+        let s = "uint256 b = token.balanceOf(voter);";
+        let node = call(s, "token.balanceOf(voter)", member(s, "token", "balanceOf"));
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_base_fee_mutation() {
+        let mt = MutationType::BaseFeeMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let member = |s: &str, text: &str, base: &str, name: &str| {
+            SolAST::new(
+                json!({
+                    "nodeType": "MemberAccess",
+                    "memberName": name,
+                    "src": src(s, text),
+                    "expression": ident(s, base),
+                }),
+                None,
+            )
+        };
+
+        let s = "uint256 priorityFee = tx.gasprice - block.basefee;";
+        let node = member(s, "block.basefee", "block", "basefee");
+        assert!(mt.is_mutation_point(&node));
+        let mutants: HashSet<String> = (0..20)
+            .map(|_| mt.mutate_randomly(&node, s.as_bytes(), &mut rand))
+            .collect();
+        assert_eq!(
+            mutants,
+            HashSet::from([
+                "uint256 priorityFee = tx.gasprice - 0;".to_string(),
+                "uint256 priorityFee = tx.gasprice - type(uint256).max;".to_string(),
+            ])
+        );
+
+        let s = "price = block.basefee * GAS_PER_CALL;";
+        let node = member(s, "block.basefee", "block", "basefee");
+        assert!(mt.is_mutation_point(&node));
+        assert!(mt
+            .mutate_randomly(&node, s.as_bytes(), &mut rand)
+            .ends_with(" * GAS_PER_CALL;"));
+
+        let s = "t = block.timestamp;";
+        assert!(!mt.is_mutation_point(&member(s, "block.timestamp", "block", "timestamp")));
+        let s = "f = oracle.basefee;";
+        assert!(!mt.is_mutation_point(&member(s, "oracle.basefee", "oracle", "basefee")));
+    }
+
+    #[test]
+    fn test_hash_collision_mutation() {
+        let mt = MutationType::HashCollisionMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let hash = |s: &str, a: (&str, &str), b: (&str, &str)| {
+            let arg = |(text, type_string): (&str, &str)| {
+                let mut v = ident(s, text);
+                v["typeDescriptions"] = json!({ "typeString": type_string });
+                v
+            };
+            let packed = format!("abi.encodePacked({}, {})", a.0, b.0);
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionCall",
+                    "src": src(s, &format!("keccak256({})", packed)),
+                    "expression": ident(s, "keccak256"),
+                    "arguments": [{
+                        "nodeType": "FunctionCall",
+                        "src": src(s, &packed),
+                        "expression": {
+                            "nodeType": "MemberAccess",
+                            "memberName": "encodePacked",
+                            "src": src(s, "abi.encodePacked"),
+                            "expression": ident(s, "abi"),
+                        },
+                        "arguments": [arg(a), arg(b)],
+                    }],
+                }),
+                None,
+            )
+        };
+
+        let s = "bytes32 id = keccak256(abi.encodePacked(name, symbol));";
+        let node = hash(s, ("name", "string memory"), ("symbol", "string memory"));
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "bytes32 id = keccak256(abi.encodePacked(name, uint256(0), symbol));"
+        );
+
+        let s = "h = keccak256(abi.encodePacked(sig, data));";
+        let node = hash(s, ("sig", "bytes calldata"), ("data", "bytes memory"));
+        assert!(mt.is_mutation_point(&node));
+
+        // Fixed-size arguments cannot be shifted into one another.
+        let s = "h = keccak256(abi.encodePacked(owner, nonce));";
+        let node = hash(s, ("owner", "address"), ("nonce", "uint256"));
+        assert!(!mt.is_mutation_point(&node));
+        let s = "h = keccak256(abi.encodePacked(root, leaf));";
+        let node = hash(s, ("root", "bytes32"), ("leaf", "bytes32"));
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_flash_loan_mutation() {
+        let mt = MutationType::FlashLoanMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "require(msg.sender == address(this)); repay();";
+        let this = ident(s, "this");
+        let cast = json!({
+            "nodeType": "FunctionCall",
+            "kind": "typeConversion",
+            "src": src(s, "address(this)"),
+            "expression": ident(s, "address"),
+            "arguments": [this],
+            "typeDescriptions": {"typeString": "address"},
+        });
+        let sender = json!({
+            "nodeType": "MemberAccess",
+            "memberName": "sender",
+            "src": src(s, "msg.sender"),
+            "expression": ident(s, "msg"),
+        });
+        let check = |l: &Value, r: &Value, function: &str| {
+            let cond = json!({
+                "nodeType": "BinaryOperation",
+                "operator": "==",
+                "src": src(s, "msg.sender == address(this)"),
+                "leftExpression": l,
+                "rightExpression": r,
+            });
+            let mut node = require(s, "require(msg.sender == address(this))", cond);
+            node.function = Some(function.into());
+            node
+        };
+
+        let node = check(&sender, &cast, "onFlashLoan");
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "/*require(msg.sender == address(this))*/; repay();"
+        );
+        assert!(mt.is_mutation_point(&check(&cast, &sender, "uniswapV2Call")));
+
+        // Not a flash loan callback.
+        assert!(!mt.is_mutation_point(&check(&sender, &cast, "withdraw")));
+        // Not checked against the contract itself.
+        let mut owner = cast;
+        owner["arguments"][0] = ident(s, "msg");
+        assert!(!mt.is_mutation_point(&check(&sender, &owner, "onFlashLoan")));
+    }
+
+    #[test]
+    fn test_min_balance_mutation() {
+        let mt = MutationType::MinBalanceMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let info = std::rc::Rc::new(crate::ContractInfo {
+            state_variables: vec![1, 2, 3],
+            ..Default::default()
+        });
+        // A reference to the declaration with id `id`.
+        let var = |s: &str, name: &str, id: i64| {
+            let mut v = ident(s, name);
+            v["referencedDeclaration"] = id.into();
+            v
+        };
+        let compare = |s: &str, text: &str, op: &str, left: Value, right: Value| {
+            let mut node = binop(s, text, op, left, right);
+            node.contract_info = Some(info.clone());
+            node
+        };
+        let s = "require(liquidity >= MINIMUM_LIQUIDITY, \"insufficient liquidity\");";
+        let node = compare(
+            s,
+            "liquidity >= MINIMUM_LIQUIDITY",
+            ">=",
+            var(s, "liquidity", 10),
+            var(s, "MINIMUM_LIQUIDITY", 1),
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "require(liquidity >= 0, \"insufficient liquidity\");"
+        );
+
+        let s = "if (minDeposit > amount) revert();";
+        let node = compare(
+            s,
+            "minDeposit > amount",
+            ">",
+            var(s, "minDeposit", 2),
+            var(s, "amount", 10),
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "if (0 > amount) revert();"
+        );
+        let s = "require(balance >= MIN_BALANCE);";
+        let node = compare(
+            s,
+            "balance >= MIN_BALANCE",
+            ">=",
+            var(s, "balance", 10),
+            var(s, "MIN_BALANCE", 3),
+        );
+        assert!(mt.is_mutation_point(&node));
+        // Not a state variable or constant, e.g., a parameter.
+        let node = compare(
+            s,
+            "balance >= MIN_BALANCE",
+            ">=",
+            var(s, "balance", 10),
+            var(s, "MIN_BALANCE", 11),
+        );
+        assert!(!mt.is_mutation_point(&node));
+
+        // Not a minimum.
+        let s = "require(minted <= cap);";
+        let node = compare(
+            s,
+            "minted <= cap",
+            "<=",
+            var(s, "minted", 1),
+            var(s, "cap", 2),
+        );
+        assert!(!mt.is_mutation_point(&node));
+        for name in [
+            "MIN",
+            "MINIMUM",
+            "MIN_BALANCE",
+            "min",
+            "minAmount",
+            "min_amount",
+        ] {
+            assert!(is_minimum_name(name), "{}", name);
+        }
+        for name in [
+            "MINT_CAP",
+            "MINTER_ROLE",
+            "MINTED",
+            "MINIMUMS",
+            "MinAmount",
+            "minter",
+        ] {
+            assert!(!is_minimum_name(name), "{}", name);
+        }
+        // Not a comparison.
+        let s = "x = MIN_BALANCE + fee;";
+        let node = compare(
+            s,
+            "MIN_BALANCE + fee",
+            "+",
+            var(s, "MIN_BALANCE", 3),
+            var(s, "fee", 2),
+        );
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_event_indexing_mutation() {
+        let mt = MutationType::EventIndexingMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "event Transfer(address indexed from, address indexed to, uint256 value);";
+        let param = |text: &str, indexed: bool| json!({"nodeType": "VariableDeclaration", "src": src(s, text), "indexed": indexed});
+        let event = |params: Vec<Value>| {
+            SolAST::new(
+                json!({
+                    "nodeType": "EventDefinition",
+                    "src": src(s, s),
+                    "parameters": {"nodeType": "ParameterList", "parameters": params},
+                }),
+                None,
+            )
+        };
+        let node = event(vec![
+            param("address indexed from", true),
+            param("address indexed to", true),
+            param("uint256 value", false),
+        ]);
+        assert!(mt.is_mutation_point(&node));
+        let mutants: HashSet<String> = (0..20)
+            .map(|_| mt.mutate_randomly(&node, s.as_bytes(), &mut rand))
+            .collect();
+        assert_eq!(
+            mutants,
+            HashSet::from([
+                "event Transfer(address from, address indexed to, uint256 value);".into(),
+                "event Transfer(address indexed from, address to, uint256 value);".into(),
+            ])
+        );
+
+        // Nothing to remove.
+        let node = event(vec![
+            param("address indexed from", false),
+            param("uint256 value", false),
+        ]);
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_mutation_count() {
+        // The count is the number of distinct mutants other than the source.
+        let distinct = |mt: MutationType, s: &str, node: &SolAST| {
+            let mut rand = Pcg64::seed_from_u64(0);
+            let mutants: HashSet<String> = (0..200)
+                .map(|_| mt.mutate_randomly(node, s.as_bytes(), &mut rand))
+                .filter(|m| m != s)
+                .collect();
+            mutants.len()
+        };
+        let cases = [
+            (
+                MutationType::BinaryOpMutation,
+                "x = a - b;",
+                binop(
+                    "x = a - b;",
+                    "a - b",
+                    "-",
+                    ident("x = a - b;", "a"),
+                    ident("x = a - b;", "b"),
+                ),
+            ),
+            (
+                MutationType::BinaryOpMutation,
+                "x = a + 0;",
+                binop(
+                    "x = a + 0;",
+                    "a + 0",
+                    "+",
+                    ident("x = a + 0;", "a"),
+                    number("x = a + 0;", "0"),
+                ),
+            ),
+            (
+                MutationType::SubExpressionMutation,
+                "x = a - b;",
+                binop(
+                    "x = a - b;",
+                    "a - b",
+                    "-",
+                    ident("x = a - b;", "a"),
+                    ident("x = a - b;", "b"),
+                ),
+            ),
+            (
+                MutationType::UnaryOperatorMutation,
+                "x = -i;",
+                SolAST::new(
+                    json!({"nodeType": "UnaryOperation", "operator": "-", "src": src("x = -i;", "-i"), "subExpression": ident("x = -i;", "i")}),
+                    None,
+                ),
+            ),
+            (
+                MutationType::UnaryOperatorMutation,
+                "x = ++i;",
+                SolAST::new(
+                    json!({"nodeType": "UnaryOperation", "operator": "++", "src": src("x = ++i;", "++i"), "subExpression": ident("x = ++i;", "i")}),
+                    None,
+                ),
+            ),
+        ];
+        for (mt, s, node) in cases {
+            assert_eq!(
+                mt.mutation_count(&node, s.as_bytes()),
+                Some(distinct(mt, s, &node)),
+                "{} of {}",
+                mt,
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_override_specifier_mutation() {
+        let mt = MutationType::OverrideSpecifierMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        // Diamond inheritance: `D is B, C` with `B is A` and `C is A`.
+        let function = |s: &str, names: &[&str]| {
+            let list = format!("override({})", names.join(", "));
+            let overrides: Vec<Value> = names
+                .iter()
+                .map(|n| json!({"nodeType": "IdentifierPath", "name": n, "src": src_in(s, &list, n)}))
+                .collect();
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionDefinition",
+                    "kind": "function",
+                    "src": src(s, s),
+                    "overrides": {"nodeType": "OverrideSpecifier", "src": src(s, &list), "overrides": overrides},
+                }),
+                None,
+            )
+        };
+        let s = "function foo() public pure override(B, C) returns (uint) { return 3; }";
+        let node = function(s, &["B", "C"]);
+        assert!(mt.is_mutation_point(&node));
+        let mutants: HashSet<String> = (0..20)
+            .map(|_| mt.mutate_randomly(&node, s.as_bytes(), &mut rand))
+            .collect();
+        assert_eq!(
+            mutants,
+            HashSet::from([
+                "function foo() public pure override(C) returns (uint) { return 3; }".into(),
+                "function foo() public pure override(B) returns (uint) { return 3; }".into(),
+            ])
+        );
+        assert_eq!(mt.mutation_count(&node, s.as_bytes()), Some(2));
+
+        let s = "function bar() public override(Base, Left, Right) {}";
+        let node = function(s, &["Base", "Left", "Right"]);
+        let mutants: HashSet<String> = (0..30)
+            .map(|_| mt.mutate_randomly(&node, s.as_bytes(), &mut rand))
+            .collect();
+        assert!(mutants.contains("function bar() public override(Base, Right) {}"));
+        assert_eq!(mutants.len(), 3);
+
+        // A single base needs no list to pick from.
+        let s = "function baz() public override(A) {}";
+        assert!(!mt.is_mutation_point(&function(s, &["A"])));
+        let s = "function qux() public override {}";
+        let node = SolAST::new(
+            json!({
+                "nodeType": "FunctionDefinition",
+                "src": src(s, s),
+                "overrides": {"nodeType": "OverrideSpecifier", "src": src(s, "override"), "overrides": []},
+            }),
+            None,
+        );
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_abi_call_mutation() {
+        let mt = MutationType::ABICallMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "contract Token { \
+                 function transfer(address to, uint amount) public {} \
+                 function approve(address to, uint amount) external {} \
+                 function forward(address to, uint amount) internal { \
+                 abi.encodeWithSelector(this.transfer.selector, to, amount); \
+                 abi.encodeWithSelector(bytes4(0x095ea7b3), to, amount); \
+                 abi.encodeWithSelector(bytes4(0x095E_A7B3), to, amount); \
+                 abi.encodeWithSelector(hex\"095ea7b3\", to, amount); \
+                 abi.encodeWithSelector(sel_095ea7b3, to, amount); \
+                 abi.encode(to, amount); } }";
+        let encode = |call: &str, args: Vec<Value>| {
+            let callee = if call.starts_with("abi.encodeWithSelector") {
+                "encodeWithSelector"
+            } else {
+                "encode"
+            };
+            json!({
+                "nodeType": "FunctionCall",
+                "src": src(s, call),
+                "expression": {
+                    "nodeType": "MemberAccess",
+                    "memberName": callee,
+                    "src": src_in(s, call, &format!("abi.{}", callee)),
+                    "expression": {"nodeType": "Identifier", "name": "abi", "src": src_in(s, call, "abi")},
+                },
+                "arguments": args,
+            })
+        };
+        let by_member = encode(
+            "abi.encodeWithSelector(this.transfer.selector, to, amount)",
+            vec![json!({
+                "nodeType": "MemberAccess",
+                "memberName": "selector",
+                "src": src(s, "this.transfer.selector"),
+                "expression": {
+                    "nodeType": "MemberAccess",
+                    "memberName": "transfer",
+                    "src": src(s, "this.transfer"),
+                    "expression": {"nodeType": "Identifier", "name": "this", "src": src(s, "this")},
+                },
+            })],
+        );
+        let by_literal = encode(
+            "abi.encodeWithSelector(bytes4(0x095ea7b3), to, amount)",
+            vec![json!({
+                "nodeType": "FunctionCall",
+                "kind": "typeConversion",
+                "src": src(s, "bytes4(0x095ea7b3)"),
+                "arguments": [number(s, "0x095ea7b3")],
+            })],
+        );
+        // Literals are compared as selectors, not as text that contains one.
+        let by_number = encode(
+            "abi.encodeWithSelector(bytes4(0x095E_A7B3), to, amount)",
+            vec![json!({
+                "nodeType": "FunctionCall",
+                "kind": "typeConversion",
+                "src": src(s, "bytes4(0x095E_A7B3)"),
+                "arguments": [number(s, "0x095E_A7B3")],
+            })],
+        );
+        let by_hex = encode(
+            "abi.encodeWithSelector(hex\"095ea7b3\", to, amount)",
+            vec![json!({
+                "nodeType": "Literal",
+                "kind": "hexString",
+                "hexValue": "095ea7b3",
+                "src": src(s, "hex\"095ea7b3\""),
+            })],
+        );
+        let by_variable = encode(
+            "abi.encodeWithSelector(sel_095ea7b3, to, amount)",
+            vec![ident(s, "sel_095ea7b3")],
+        );
+        let plain = encode("abi.encode(to, amount)", vec![ident(s, "to")]);
+        let definition = |name: &str, selector: Option<&str>, statements: Vec<Value>| {
+            json!({
+                "nodeType": "FunctionDefinition",
+                "name": name,
+                "functionSelector": selector,
+                "src": src_in(s, &format!("function {}", name), "function"),
+                "body": {"nodeType": "Block", "statements": statements},
+            })
+        };
+        let contract = SolAST::new(
+            json!({
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "Token",
+                "src": src(s, s),
+                "nodes": [
+                    definition("transfer", Some("a9059cbb"), vec![]),
+                    definition("approve", Some("095ea7b3"), vec![]),
+                    definition(
+                        "forward",
+                        None,
+                        vec![by_member, by_literal, by_number, by_hex, by_variable, plain],
+                    ),
+                ],
+            }),
+            None,
+        );
+        // Only calls inside the contract know the selectors of its functions.
+        let points = contract.traverse(
+            |n| mt.is_mutation_point(n).then(|| n.clone()),
+            |_| false,
+            |_| true,
+        );
+        assert_eq!(points.len(), 5);
+        let counts: Vec<_> = points
+            .iter()
+            .map(|p| mt.mutation_count(p, s.as_bytes()))
+            .collect();
+        assert_eq!(counts, [Some(1), Some(1), Some(1), Some(1), Some(2)]);
+        let mutants: Vec<String> = points
+            .iter()
+            .map(|p| mt.mutate_randomly(p, s.as_bytes(), &mut rand))
+            .collect();
+        assert!(mutants[0].contains(
+            "abi.encodeWithSelector(bytes4(0x095ea7b3), to, amount); \
+             abi.encodeWithSelector(bytes4(0x095ea7b3), to, amount);"
+        ));
+        assert!(mutants[1].contains(
+            "abi.encodeWithSelector(this.transfer.selector, to, amount); \
+             abi.encodeWithSelector(bytes4(0xa9059cbb), to, amount);"
+        ));
+        assert!(mutants[2].contains(
+            "abi.encodeWithSelector(bytes4(0xa9059cbb), to, amount); \
+             abi.encodeWithSelector(hex"
+        ));
+        assert!(mutants[3].contains(
+            "abi.encodeWithSelector(bytes4(0xa9059cbb), to, amount); \
+             abi.encodeWithSelector(sel_"
+        ));
+    }
+
+    #[test]
+    fn test_reentrancy_guard_mutation() {
+        let mt = MutationType::ReentrancyGuardMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "function withdraw() external nonReentrant onlyOwner {}";
+        let invocation = |name: &str| {
+            SolAST::new(
+                json!({
+                    "nodeType": "ModifierInvocation",
+                    "src": src(s, name),
+                    "modifierName": {"nodeType": "IdentifierPath", "name": name, "src": src(s, name)},
+                }),
+                None,
+            )
+        };
+        let node = invocation("nonReentrant");
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "function withdraw() external  onlyOwner {}"
+        );
+        assert!(!mt.is_mutation_point(&invocation("onlyOwner")));
+        let guards = vec!["onlyOwner".to_string()];
+        let node = invocation("onlyOwner").with_reentrancy_guards(guards.clone());
+        assert!(mt.is_mutation_point(&node));
+        let node = invocation("nonReentrant").with_reentrancy_guards(guards);
+        assert!(!mt.is_mutation_point(&node));
+
+        let s = "function withdraw(uint amount) external { \
+                 require(!locked, \"locked\"); locked = true; \
+                 balance -= amount; locked = false; }";
+        let lock = |text: &str| json!({"nodeType": "Identifier", "name": "locked", "src": src(s, text), "typeDescriptions": {"typeString": "bool"}});
+        let set = |value: &str| {
+            let text = format!("locked = {}", value);
+            json!({
+                "nodeType": "ExpressionStatement",
+                "src": src(s, &text),
+                "expression": {
+                    "nodeType": "Assignment",
+                    "operator": "=",
+                    "src": src(s, &text),
+                    "leftHandSide": lock(&text),
+                    "rightHandSide": {"nodeType": "Literal", "kind": "bool", "value": value, "src": src(s, value)},
+                },
+            })
+        };
+        let check = json!({
+            "nodeType": "ExpressionStatement",
+            "src": src(s, "require(!locked, \"locked\")"),
+            "expression": require(s, "require(!locked, \"locked\")", json!({
+                "nodeType": "UnaryOperation",
+                "operator": "!",
+                "src": src(s, "!locked"),
+                "subExpression": lock("locked"),
+            })).get_object().unwrap(),
+        });
+        let function = |parameters: Value| {
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionDefinition",
+                    "src": src(s, s),
+                    "parameters": {"nodeType": "ParameterList", "parameters": parameters},
+                    "body": {"nodeType": "Block", "statements": [check, set("true"), {"nodeType": "ExpressionStatement", "src": src(s, "balance -= amount")}, set("false")]},
+                }),
+                None,
+            )
+        };
+        let node = function(json!([{"nodeType": "VariableDeclaration", "name": "amount"}]));
+        assert!(mt.is_mutation_point(&node.clone().with_reentrancy_guards(vec![])));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "function withdraw(uint amount) external { \
+             /*require(!locked, \"locked\")*/; /*locked = true*/; \
+             balance -= amount; /*locked = false*/; }"
+        );
+        // A lock that is a parameter does not outlive the call.
+        let node = function(json!([{"nodeType": "VariableDeclaration", "name": "locked"}]));
+        assert!(!mt.is_mutation_point(&node));
+        assert!(mt.is_security_critical());
+        assert!(!MutationType::RequireMutation.is_security_critical());
+    }
+
+    #[test]
+    fn test_ignore_return_mutation() {
+        let mt = MutationType::IgnoreReturnMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let declaration = |s: &str, call: &str, callee: Value, kind: &str| {
+            SolAST::new(
+                json!({
+                    "nodeType": "VariableDeclarationStatement",
+                    "src": src(s, s),
+                    "initialValue": {"nodeType": "FunctionCall", "kind": kind, "src": src(s, call), "expression": callee},
+                }),
+                None,
+            )
+        };
+        let member = |s: &str, text: &str| json!({"nodeType": "MemberAccess", "src": src(s, text)});
+
+        let s = "bool ok = token.transfer(to, amt);";
+        let node = declaration(
+            s,
+            "token.transfer(to, amt)",
+            member(s, "token.transfer"),
+            "functionCall",
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "token.transfer(to, amt);"
+        );
+
+        let s = "(uint112 a, uint112 b, ) = pair.getReserves();";
+        let node = declaration(
+            s,
+            "pair.getReserves()",
+            member(s, "pair.getReserves"),
+            "functionCall",
+        );
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "pair.getReserves();"
+        );
+
+        let s = "(bool ok, ) = to.call{value: amt}(\"\");";
+        let options = json!({"nodeType": "FunctionCallOptions", "src": src(s, "to.call{value: amt}"), "expression": member(s, "to.call")});
+        let node = declaration(s, "to.call{value: amt}(\"\")", options, "functionCall");
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "to.call{value: amt}(\"\");"
+        );
+
+        // Neither internal calls nor conversions are results of other contracts.
+        let s = "uint x = f(y);";
+        assert!(!mt.is_mutation_point(&declaration(s, "f(y)", ident(s, "f"), "functionCall")));
+        let s = "Types.Kind k = Types.Kind(raw);";
+        let conversion = declaration(
+            s,
+            "Types.Kind(raw)",
+            member(s, "Types.Kind"),
+            "typeConversion",
+        );
+        assert!(!mt.is_mutation_point(&conversion));
+    }
+
+    #[test]
+    fn test_ignore_return_mutation_used_later() {
+        let mt = MutationType::IgnoreReturnMutation;
+        let s = "bool ok = token.transfer(to, amt); require(ok);";
+        let transfer = "token.transfer(to, amt)";
+        let declaration = json!({
+            "nodeType": "VariableDeclarationStatement",
+            "src": src(s, "bool ok = token.transfer(to, amt);"),
+            "declarations": [{"nodeType": "VariableDeclaration", "id": 7, "name": "ok", "src": src(s, "bool ok")}],
+            "initialValue": {
+                "nodeType": "FunctionCall",
+                "kind": "functionCall",
+                "src": src(s, transfer),
+                "expression": {"nodeType": "MemberAccess", "src": src(s, "token.transfer")},
+            },
+        });
+        let check = json!({
+            "nodeType": "ExpressionStatement",
+            "src": src(s, "require(ok);"),
+            "expression": {
+                "nodeType": "FunctionCall",
+                "src": src(s, "require(ok)"),
+                "expression": {"nodeType": "Identifier", "name": "require", "src": src(s, "require")},
+                "arguments": [{"nodeType": "Identifier", "name": "ok", "referencedDeclaration": 7, "src": src_in(s, "(ok)", "ok")}],
+            },
+        });
+        let points = |statements: Vec<Value>| {
+            SolAST::new(
+                json!({
+                    "nodeType": "FunctionDefinition",
+                    "name": "f",
+                    "body": {"nodeType": "Block", "statements": statements},
+                }),
+                None,
+            )
+            .traverse(
+                |n| mt.is_mutation_point(n).then(|| n.clone()),
+                |_| false,
+                |_| true,
+            )
+        };
+        assert!(points(vec![declaration.clone(), check]).is_empty());
+        let found = points(vec![declaration]);
+        assert_eq!(found.len(), 1);
+        let mut rand = Pcg64::seed_from_u64(0);
+        assert_eq!(
+            mt.mutate_randomly(&found[0], s.as_bytes(), &mut rand),
+            "token.transfer(to, amt); require(ok);"
+        );
+    }
+
+    #[test]
+    fn test_custom_error_guards() {
+        let info = std::rc::Rc::new(crate::ContractInfo {
+            selectors: vec![],
+            errors: vec![
+                ("ZeroAmount".into(), 0),
+                ("Paused".into(), 0),
+                ("TooLarge".into(), 1),
+            ],
+            state_variables: vec![],
+        });
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = "if (amount == 0) { revert ZeroAmount(); }";
+        let revert = json!({
+            "nodeType": "RevertStatement",
+            "src": src(s, "revert ZeroAmount()"),
+            "errorCall": {
+                "nodeType": "FunctionCall",
+                "src": src(s, "ZeroAmount()"),
+                "expression": ident(s, "ZeroAmount"),
+                "arguments": [],
+            },
+        });
+        let cond = binop(s, "amount == 0", "==", ident(s, "amount"), number(s, "0"));
+        let guard = |body: Value, else_body: Value| {
+            let mut node = SolAST::new(
+                json!({
+                    "nodeType": "IfStatement",
+                    "src": src(s, s),
+                    "condition": cond.element,
+                    "trueBody": body,
+                    "falseBody": else_body,
+                }),
+                None,
+            );
+            node.contract_info = Some(info.clone());
+            node
+        };
+        let block = json!({"nodeType": "Block", "src": src(s, "{ revert ZeroAmount(); }"), "statements": [revert]});
+        let node = guard(block.clone(), Value::Null);
+
+        // The guard is a `RequireMutation` point, as `require(amount != 0)` is.
+        let mt = MutationType::RequireMutation;
+        let r = "require(amount != 0);";
+        let cond_r = binop(r, "amount != 0", "!=", ident(r, "amount"), number(r, "0"));
+        let points = |n: &SolAST| {
+            n.clone().traverse(
+                |n| mt.is_mutation_point(n).then_some(()),
+                |_| false,
+                |_| true,
+            )
+        };
+        assert_eq!(
+            points(&node).len(),
+            points(&require(r, "require(amount != 0)", cond_r.element.unwrap())).len()
+        );
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "if (!(amount == 0)) { revert ZeroAmount(); }"
+        );
+        assert!(!mt.is_mutation_point(&guard(block.clone(), block)));
+        let s_call = "if (amount == 0) { emit Zero(); }";
+        let emit_block = || json!({"nodeType": "Block", "src": src(s_call, "{ emit Zero(); }"), "statements": [{"nodeType": "EmitStatement"}]});
+        assert!(!mt.is_mutation_point(&guard(emit_block(), Value::Null)));
+
+        // The guard is deleted, as a `require` is.
+        let mt = MutationType::DeleteExpressionMutation;
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "/*if (amount == 0) { revert ZeroAmount(); }*/"
+        );
+        assert!(!mt.is_mutation_point(&guard(emit_block(), Value::Null)));
+
+        // Its condition is forced to `true` or `false`, or negated.
+        let mt = MutationType::IfStatementMutation;
+        assert!(mt.is_mutation_point(&node));
+        let mutants: HashSet<String> = (0..20)
+            .map(|seed| {
+                let mut rand = Pcg64::seed_from_u64(seed);
+                mt.mutate_randomly(&node, s.as_bytes(), &mut rand)
+            })
+            .collect();
+        assert_eq!(
+            mutants,
+            HashSet::from([
+                "if (true) { revert ZeroAmount(); }".to_string(),
+                "if (false) { revert ZeroAmount(); }".to_string(),
+                "if (!(amount == 0)) { revert ZeroAmount(); }".to_string(),
+            ])
+        );
+
+        // The error raised is swapped for another one of the same arity.
+        let mt = MutationType::RevertMessageMutation;
+        let mut revert = node.get_node("trueBody").statements()[0].clone();
+        assert!(mt.is_mutation_point(&revert));
+        assert_eq!(mt.mutation_count(&revert, s.as_bytes()), Some(1));
+        assert_eq!(
+            mt.mutate_randomly(&revert, s.as_bytes(), &mut rand),
+            "if (amount == 0) { revert Paused(); }"
+        );
+        revert.contract_info = None;
+        assert!(!mt.is_mutation_point(&revert));
+
+        // Errors defined outside of the contract count, too.
+        let s = "error Paused(); contract C { error ZeroAmount(); function f() { revert ZeroAmount(); } }";
+        let error = |name: &str| json!({"nodeType": "ErrorDefinition", "name": name, "parameters": {"parameters": []}});
+        let revert = json!({
+            "nodeType": "RevertStatement",
+            "src": src(s, "revert ZeroAmount()"),
+            "errorCall": {
+                "nodeType": "FunctionCall",
+                "src": src(s, "ZeroAmount()"),
+                "expression": {"nodeType": "Identifier", "name": "ZeroAmount", "src": src_in(s, "revert ZeroAmount", "ZeroAmount")},
+                "arguments": [],
+            },
+        });
+        let unit = SolAST::new(
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [error("Paused"), {
+                    "nodeType": "ContractDefinition",
+                    "contractKind": "contract",
+                    "name": "C",
+                    "nodes": [error("ZeroAmount"), {
+                        "nodeType": "FunctionDefinition",
+                        "name": "f",
+                        "body": {"nodeType": "Block", "statements": [revert]},
+                    }],
+                }],
+            }),
+            None,
+        );
+        let mutants = unit.traverse(
+            |n| {
+                mt.is_mutation_point(n)
+                    .then(|| mt.mutate_randomly(n, s.as_bytes(), &mut rand))
+            },
+            |_| false,
+            |_| true,
+        );
+        assert_eq!(
+            mutants,
+            vec!["error Paused(); contract C { error ZeroAmount(); function f() { revert Paused(); } }"]
+        );
+    }
+
+    #[test]
+    fn test_external_call_revert_mutation() {
+        let mt = MutationType::ExternalCallRevertMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let statement = |s: &str, base_type: &str, kind: &str| {
+            let mut call = eth_call(s, "oracle.update(amount)", "oracle", base_type, "update")
+                .element
+                .unwrap();
+            call["kind"] = kind.into();
+            let mut node = SolAST::new(
+                json!({
+                    "nodeType": "ExpressionStatement",
+                    "src": src(s, "oracle.update(amount)"),
+                    "expression": call,
+                }),
+                None,
+            );
+            node.statement = Some(node.get_bounds());
+            node
+        };
+
+        let s = "oracle.update(amount); price = oracle.price();";
+        let node = statement(s, "contract IOracle", "functionCall");
+        assert!(mt.is_mutation_point(&node));
+        assert!(mt.is_security_critical());
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "{ if (true) { revert(); } oracle.update(amount); } price = oracle.price();"
+        );
+
+        // The body of an `if` without braces stays conditional.
+        let s = "if (paused) oracle.update(amount);\nprice = oracle.price();";
+        let node = statement(s, "contract IOracle", "functionCall");
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "if (paused) { if (true) { revert(); } oracle.update(amount); }\nprice = oracle.price();"
+        );
+
+        // Only the body of a `for`, not the expressions in its header.
+        let s = "for (oracle.sync(); i < n; oracle.poke()) oracle.update(amount);";
+        let expression = |text: &str, method: &str| {
+            let mut call = eth_call(s, text, "oracle", "contract IOracle", method)
+                .element
+                .unwrap();
+            call["kind"] = "functionCall".into();
+            json!({"nodeType": "ExpressionStatement", "src": src(s, text), "expression": call})
+        };
+        let for_loop = SolAST::new(
+            json!({
+                "nodeType": "ForStatement",
+                "src": src(s, s),
+                "initializationExpression": expression("oracle.sync()", "sync"),
+                "loopExpression": expression("oracle.poke()", "poke"),
+                "body": expression("oracle.update(amount)", "update"),
+            }),
+            None,
+        );
+        let points = for_loop.traverse(
+            |n| mt.is_mutation_point(n).then(|| n.get_text(s.as_bytes())),
+            |_| false,
+            |_| true,
+        );
+        assert_eq!(points, vec!["oracle.update(amount)".to_string()]);
+
+        // Neither calls of library functions nor of functions on addresses.
+        assert!(!mt.is_mutation_point(&statement(s, "type(library Oracles)", "functionCall")));
+        assert!(!mt.is_mutation_point(&statement(s, "address", "functionCall")));
+        assert!(!mt.is_mutation_point(&statement(s, "contract IOracle", "typeConversion")));
+    }
+
+    #[test]
+    fn test_tuple_component_swap_mutation() {
+        let mt = MutationType::TupleComponentSwapMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let ident = |s: &str, name: &str, type_string: &str| json!({"nodeType": "Identifier", "name": name, "src": src(s, name), "typeDescriptions": {"typeString": type_string}});
+        let tuple = |s: &str, text: &str, components: Vec<Value>| json!({"nodeType": "TupleExpression", "src": src(s, text), "components": components});
+        let assignment = |s: &str, left: Value, right: Value| {
+            SolAST::new(
+                json!({"nodeType": "Assignment", "operator": "=", "src": src(s, s.trim_end_matches(';')), "leftHandSide": left, "rightHandSide": right}),
+                None,
+            )
+        };
+
+        let s = "(reserve0, reserve1, last) = (balance0, balance1, now32);";
+        let node = assignment(
+            s,
+            tuple(
+                s,
+                "(reserve0, reserve1, last)",
+                vec![
+                    ident(s, "reserve0", "uint112"),
+                    ident(s, "reserve1", "uint112"),
+                    ident(s, "last", "uint32"),
+                ],
+            ),
+            tuple(
+                s,
+                "(balance0, balance1, now32)",
+                vec![
+                    ident(s, "balance0", "uint112"),
+                    ident(s, "balance1", "uint112"),
+                    ident(s, "now32", "uint32"),
+                ],
+            ),
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(mt.mutation_count(&node, s.as_bytes()), Some(1));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "(reserve0, reserve1, last) = (balance1, balance0, now32);"
+        );
+
+        // Nothing is swapped into or out of an empty slot.
+        let s = "(, b) = (x, y);";
+        let node = assignment(
+            s,
+            tuple(s, "(, b)", vec![Value::Null, ident(s, "b", "uint256")]),
+            tuple(
+                s,
+                "(x, y)",
+                vec![ident(s, "x", "uint256"), ident(s, "y", "uint256")],
+            ),
+        );
+        assert!(!mt.is_mutation_point(&node));
+
+        // Without a tuple on the right, the variables declared are swapped.
+        let declaration = |s: &str, text: &str, type_string: &str| json!({"nodeType": "VariableDeclaration", "src": src(s, text), "typeDescriptions": {"typeString": type_string}});
+        let s = "(uint a, uint b, bool ok) = pair.get();";
+        let mut node = SolAST::new(
+            json!({
+                "nodeType": "VariableDeclarationStatement",
+                "src": src(s, s),
+                "declarations": [
+                    declaration(s, "uint a", "uint256"),
+                    declaration(s, "uint b", "uint256"),
+                    declaration(s, "bool ok", "bool"),
+                ],
+                "initialValue": {"nodeType": "FunctionCall", "src": src(s, "pair.get()")},
+            }),
+            None,
+        );
+        assert!(mt.is_mutation_point(&node));
+        assert_eq!(mt.mutation_count(&node, s.as_bytes()), Some(1));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "(uint b, uint a, bool ok) = pair.get();"
+        );
+        node.element.as_mut().unwrap()["declarations"][0] = Value::Null;
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    #[test]
+    fn test_tuple_component_swap_mutation_benchmark() {
+        let mt = MutationType::TupleComponentSwapMutation;
+        let mut rand = Pcg64::seed_from_u64(0);
+        let s = &fs::read_to_string(
+            "benchmarks/TupleComponentSwapMutation/TupleComponentSwapExample.sol",
+        )
+        .unwrap();
+        let typed = |node_type: &str, text: &str, type_string: &str| json!({"nodeType": node_type, "src": src(s, text), "typeDescriptions": {"typeString": type_string}});
+
+        // The reserves update swaps the two `uint112` balances.
+        let node = SolAST::new(
+            json!({
+                "nodeType": "Assignment",
+                "operator": "=",
+                "src": src(s, "(reserve0, reserve1, blockTimestampLast) = (uint112(balance0), uint112(balance1), uint32(block.timestamp))"),
+                "leftHandSide": {
+                    "nodeType": "TupleExpression",
+                    "src": src(s, "(reserve0, reserve1, blockTimestampLast)"),
+                    "components": [
+                        typed("Identifier", "reserve0", "uint112"),
+                        typed("Identifier", "reserve1", "uint112"),
+                        typed("Identifier", "blockTimestampLast", "uint32"),
+                    ],
+                },
+                "rightHandSide": {
+                    "nodeType": "TupleExpression",
+                    "src": src(s, "(uint112(balance0), uint112(balance1), uint32(block.timestamp))"),
+                    "components": [
+                        typed("FunctionCall", "uint112(balance0)", "uint112"),
+                        typed("FunctionCall", "uint112(balance1)", "uint112"),
+                        typed("FunctionCall", "uint32(block.timestamp)", "uint32"),
+                    ],
+                },
+            }),
+            None,
+        );
+        assert_eq!(mt.mutation_count(&node, s.as_bytes()), Some(1));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            s.replace(
+                "(uint112(balance0), uint112(balance1),",
+                "(uint112(balance1), uint112(balance0),"
+            )
+        );
+
+        // The destructuring of `getReserves()` swaps the two reserves and
+        // leaves the empty slot alone.
+        let node = SolAST::new(
+            json!({
+                "nodeType": "VariableDeclarationStatement",
+                "src": src(s, "(uint112 _reserve0, uint112 _reserve1, ) = getReserves()"),
+                "declarations": [
+                    typed("VariableDeclaration", "uint112 _reserve0", "uint112"),
+                    typed("VariableDeclaration", "uint112 _reserve1", "uint112"),
+                    null,
+                ],
+                "initialValue": typed("FunctionCall", "getReserves()", "tuple(uint112,uint112,uint32)"),
+            }),
+            None,
+        );
+        assert_eq!(mt.mutation_count(&node, s.as_bytes()), Some(1));
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            s.replace(
+                "(uint112 _reserve0, uint112 _reserve1, )",
+                "(uint112 _reserve1, uint112 _reserve0, )"
+            )
+        );
+    }
+
+    #[test]
+    fn test_pure_to_view_mutation() {
+        let mt = MutationType::PureToViewMutation;
+        let s = "function fee(uint256 x) public pure returns (uint256) { return x; /* pure */ }";
+        let node = SolAST::new(
+            json!({
+                "nodeType": "FunctionDefinition",
+                "name": "fee",
+                "stateMutability": "pure",
+                "src": src(s, s),
+                "body": {"nodeType": "Block", "src": src(s, "{ return x; /* pure */ }")},
+            }),
+            None,
+        );
+        assert!(mt.is_mutation_point(&node));
+        let mut rand = Pcg64::seed_from_u64(0);
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "function fee(uint256 x) public view returns (uint256) { return x; /* pure */ }"
+        );
+
+        let s = "function fee(uint256 x) external pure returns (uint256);";
+        let node = SolAST::new(
+            json!({
+                "nodeType": "FunctionDefinition",
+                "name": "fee",
+                "stateMutability": "pure",
+                "src": src(s, s),
+                "body": null,
+            }),
+            None,
+        );
+        assert_eq!(
+            mt.mutate_randomly(&node, s.as_bytes(), &mut rand),
+            "function fee(uint256 x) external view returns (uint256);"
+        );
+
+        let s = "function fee() public view returns (uint256) { return f; }";
+        let node = SolAST::new(
+            json!({"nodeType": "FunctionDefinition", "stateMutability": "view", "src": src(s, s)}),
+            None,
+        );
+        assert!(!mt.is_mutation_point(&node));
+    }
+
+    fn fold(source: &str, text: &str, op: &str, left: Value, right: Value) -> Option<String> {
+        let node = binop(source, text, op, left, right);
+        let mt = MutationType::ConstantFoldMutation;
+        if !mt.is_mutation_point(&node) {
+            return None;
+        }
+        let mut rand = Pcg64::seed_from_u64(0);
+        Some(mt.mutate_randomly(&node, source.as_bytes(), &mut rand))
+    }
+
+    #[test]
+    fn test_constant_fold_mutation() {
+        let s = "x = 1 + 1;";
+        let res = fold(s, "1 + 1", "+", number(s, "1"), last_number(s, "1"));
+        assert_eq!(res.unwrap(), "x = 2;");
+        let s = "x = 2 ** 8;";
+        let res = fold(s, "2 ** 8", "**", number(s, "2"), number(s, "8"));
+        assert_eq!(res.unwrap(), "x = 256;");
+        let s = "x = 0x10 * 1_000;";
+        let res = fold(
+            s,
+            "0x10 * 1_000",
+            "*",
+            number(s, "0x10"),
+            number(s, "1_000"),
+        );
+        assert_eq!(res.unwrap(), "x = 16000;");
+        let s = "x = 2e3 < 1000;";
+        let res = fold(s, "2e3 < 1000", "<", number(s, "2e3"), number(s, "1000"));
+        assert_eq!(res.unwrap(), "x = false;");
+    }
+
+    #[test]
+    fn test_constant_fold_mutation_nested() {
+        let s = "x = 2 ** 256 - 1;";
+        let pow = binop(s, "2 ** 256", "**", number(s, "2"), number(s, "256"));
+        let res = fold(
+            s,
+            "2 ** 256 - 1",
+            "-",
+            pow.element.unwrap(),
+            last_number(s, "1"),
+        );
+        assert_eq!(
+            res.unwrap(),
+            "x = 115792089237316195423570985008687907853269984665640564039457584007913129639935;"
+        );
+        let s = "x = (10 / 4) * 2 == 5;";
+        let quarter = binop(s, "10 / 4", "/", number(s, "10"), number(s, "4"));
+        let tuple = json!({"nodeType": "TupleExpression", "src": src(s, "(10 / 4)"), "components": [quarter.element.unwrap()]});
+        let prod = binop(s, "(10 / 4) * 2", "*", tuple, last_number(s, "2"));
+        assert!(fold(
+            s,
+            "(10 / 4) * 2 == 5",
+            "==",
+            prod.element.unwrap(),
+            number(s, "5")
+        )
+        .is_none());
+        let s = "x = 10 / 2 + 10 % 4;";
+        let div = binop(s, "10 / 2", "/", number(s, "10"), number(s, "2"));
+        let rem = binop(s, "10 % 4", "%", last_number(s, "10"), number(s, "4"));
+        let res = fold(
+            s,
+            "10 / 2 + 10 % 4",
+            "+",
+            div.element.unwrap(),
+            rem.element.unwrap(),
+        );
+        assert_eq!(res.unwrap(), "x = 7;");
+    }
+
+    #[test]
+    fn test_constant_fold_mutation_subdenomination() {
+        let s = "x = 1 ether + 1;";
+        let mut ether = number(s, "1 ether");
+        ether["value"] = json!("1");
+        ether["subdenomination"] = json!("ether");
+        let res = fold(s, "1 ether + 1", "+", ether, last_number(s, "1"));
+        assert_eq!(res.unwrap(), "x = 1000000000000000001;");
+    }
+
+    #[test]
+    fn test_constant_fold_mutation_not_foldable() {
+        let s = "x = 1 - 2;";
+        assert!(fold(s, "1 - 2", "-", number(s, "1"), number(s, "2")).is_none());
+        let s = "x = 1 / 0;";
+        assert!(fold(s, "1 / 0", "/", number(s, "1"), number(s, "0")).is_none());
+        let s = "x = 2 ** 256;";
+        assert!(fold(s, "2 ** 256", "**", number(s, "2"), number(s, "256")).is_none());
+        let s = "x = 5 / 2;";
+        assert!(fold(s, "5 / 2", "/", number(s, "5"), number(s, "2")).is_none());
+        let s = "x = (5 / 2) * 10;";
+        let half = json!({
+            "nodeType": "TupleExpression",
+            "src": src(s, "(5 / 2)"),
+            "components": [binop(s, "5 / 2", "/", number(s, "5"), number(s, "2")).element.unwrap()],
+        });
+        assert!(fold(s, "(5 / 2) * 10", "*", half, number(s, "10")).is_none());
+        let s = "x = a + 1;";
+        assert!(fold(s, "a + 1", "+", ident(s, "a"), number(s, "1")).is_none());
     }
 }
@@ -0,0 +1,120 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use crate::{zero_contribution_report, FileStats, MutantEntry, MutationType, StoppedReason};
+
+/// What became of a candidate mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateOutcome {
+    /// It changed code outside of the node it mutates.
+    OutOfSpan,
+    /// It was generated before.
+    Duplicate,
+    /// It does not compile.
+    Invalid,
+    /// The `--on-mutant-required` hook failed on it.
+    HookRejected,
+    /// It was written to the output directory.
+    Accepted,
+}
+
+/// How mutating a single file went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    pub file: String,
+    /// The mutation types that were asked for explicitly, if any.
+    pub requested: Option<Vec<MutationType>>,
+    /// Number of mutants asked for.
+    pub num_mutants: i64,
+    /// Number of mutants generated.
+    pub mutants: usize,
+    pub stopped: StoppedReason,
+    pub stats: FileStats,
+}
+
+/// Callbacks on the progress of a run, for embedding Gambit in other tools
+/// without parsing its logs.
+/// They are called synchronously, on the thread that runs the generator,
+/// and should return quickly. If files are ever mutated in parallel, the
+/// calls will still be made one at a time.
+/// Every callback does nothing unless implemented.
+pub trait GambitObserver {
+    /// Mutating `file` starts.
+    fn on_file_start(&mut self, _file: &str) {}
+    /// The mutation points of the file were collected, with how many
+    /// there are of each mutation type that has any.
+    fn on_point_collection(&mut self, _counts: &HashMap<MutationType, usize>) {}
+    /// A candidate mutant was generated by `op` on the node spanning `span`.
+    fn on_candidate(
+        &mut self,
+        _op: MutationType,
+        _span: (usize, usize),
+        _outcome: CandidateOutcome,
+    ) {
+    }
+    /// A mutant was accepted, right after its candidate.
+    fn on_mutant_accepted(&mut self, _mutant: &MutantEntry) {}
+    /// Mutating the file is done.
+    fn on_file_done(&mut self, _report: &FileReport) {}
+    /// Something about the run that the user should know, such as which
+    /// files get their mutants in shortened directories.
+    fn on_warning(&mut self, _message: &str) {}
+}
+
+/// The observer that does nothing.
+pub struct NoObserver;
+
+impl GambitObserver for NoObserver {}
+
+/// Reports on stderr which of the requested mutation types did not
+/// contribute any mutants to a file, when a file ran out of mutation
+/// points before all the requested mutants were generated, and warnings.
+/// This is what the command line uses.
+pub struct StderrReporter;
+
+impl GambitObserver for StderrReporter {
+    fn on_file_done(&mut self, report: &FileReport) {
+        if let Some(requested) = &report.requested {
+            for line in zero_contribution_report(&report.file, requested, &report.stats) {
+                eprintln!("{}", line);
+            }
+        }
+        if report.stopped == StoppedReason::MutationSpaceExhausted {
+            eprintln!(
+                "{}: mutation space exhausted: produced {} of requested {}",
+                report.file, report.mutants, report.num_mutants
+            );
+        }
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        eprintln!("warning: {}", message);
+    }
+}
+
+/// A handle to the observer of a run, shared by everything that reports
+/// to it.
+#[derive(Clone)]
+pub struct Observer(Rc<RefCell<Box<dyn GambitObserver>>>);
+
+impl Observer {
+    pub fn new(observer: Box<dyn GambitObserver>) -> Self {
+        Observer(Rc::new(RefCell::new(observer)))
+    }
+
+    /// Call `event` on the observer.
+    pub fn notify(&self, event: impl FnOnce(&mut dyn GambitObserver)) {
+        event(self.0.borrow_mut().as_mut())
+    }
+}
+
+impl Default for Observer {
+    fn default() -> Self {
+        Observer::new(Box::new(NoObserver))
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
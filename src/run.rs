@@ -1,33 +1,262 @@
+use clap::ValueEnum;
 use itertools::Itertools;
-use rand::seq::SliceRandom;
-use scanner_rust::{Scanner, ScannerError};
+use rand::{seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    cell::RefCell,
+    collections::{HashMap, HashSet},
     error::Error,
-    fs::File,
-    io::Read,
+    fs::{self, File},
+    io::{self, Read},
     path::{Path, PathBuf},
+    rc::Rc,
 };
 
 use crate::{
-    ast, get_indent, get_path_normals, invoke_command, mutation, Mutation,
+    ast, changed_range, contract_matches, excerpt, get_indent, line_end, line_start, mutant_dir,
+    mutation, qualified_contract, render_diff, sha256_hex, write_atomically, CandidateOutcome,
+    Checkpoint, Emit, FileProgress, FunctionReport, MutantEntry, MutantHook, Mutation,
     MutationType::{self},
-    SolAST,
+    Observer, PathLimit, Sampler, SolAST, SolcVersion, SparseEdit, StoppedReason, Suppressor,
+    UTF8_BOM,
 };
 
 /// How many tries for generating mutants.
 static ATTEMPTS: i64 = 50;
 
+/// Used to spread the seeds of consecutive attempts, see `attempt_rng`.
+static SEED_STRIDE: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Sets the seeds of mutations apart from those of attempts, see
+/// `mutation_rng`.
+static MUTATION_SEED_SALT: u64 = 0xD1B5_4A32_D192_ED03;
+
+/// Lines longer than this are truncated when logging the diff of a mutant.
+static DIFF_MAX_LINE_LEN: usize = 200;
+
+/// Mutation points found while visiting a single AST node.
+type NodeMutations = Option<Vec<(mutation::MutationType, ast::SolAST)>>;
+
+/// How generating mutants went for a single mutation type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MutationStats {
+    /// Number of mutation points found.
+    pub points: usize,
+    /// Number of candidate mutants generated.
+    pub attempts: usize,
+    /// Number of candidates that were new and compiled.
+    pub accepted: usize,
+    /// Number of such candidates discarded because of a failing
+    /// `--on-mutant-required` hook.
+    pub hook_rejected: usize,
+    /// Number of candidates rejected for changing code outside of the
+    /// node they mutate, which points to a bug in the mutation.
+    pub out_of_span: usize,
+    /// Number of candidates that were generated before, for this file or
+    /// under another path of it.
+    #[serde(default)]
+    pub duplicates: usize,
+    /// Number of candidates that did not compile.
+    #[serde(default)]
+    pub invalid: usize,
+}
+
+/// Mutation statistics of a file, per mutation type.
+pub type FileStats = HashMap<MutationType, MutationStats>;
+
+impl MutationStats {
+    /// Explains why a mutation type with these statistics did not
+    /// contribute any mutants, or `None` if it did.
+    pub fn zero_reason(&self) -> Option<String> {
+        if self.accepted > 0 {
+            None
+        } else if self.points == 0 {
+            Some("no mutation points".into())
+        } else if self.out_of_span > 0 {
+            Some(format!(
+                "{} of its {} candidates were out-of-span edits",
+                self.out_of_span, self.attempts
+            ))
+        } else if self.hook_rejected > 0 {
+            Some(format!(
+                "{} of its {} candidates were rejected by the --on-mutant hook",
+                self.hook_rejected, self.attempts
+            ))
+        } else if self.attempts == 0 {
+            Some(format!(
+                "{} mutation points, but lost out to the budget of mutants",
+                self.points
+            ))
+        } else {
+            Some(format!(
+                "none of its {} candidates were new and compiled",
+                self.attempts
+            ))
+        }
+    }
+}
+
+/// One line for each of the `requested` mutation types that did not
+/// contribute any mutants to `fnm`, saying why.
+pub fn zero_contribution_report(
+    fnm: &str,
+    requested: &[MutationType],
+    stats: &FileStats,
+) -> Vec<String> {
+    requested
+        .iter()
+        .unique()
+        .filter_map(|m| {
+            let reason = stats.get(m).copied().unwrap_or_default().zero_reason()?;
+            Some(format!("{}: {} produced no mutants: {}", fnm, m, reason))
+        })
+        .collect()
+}
+
+/// Which functions to mutate, by whether they may change the state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MutabilityScope {
+    #[default]
+    All,
+    /// Everything but `view` and `pure` functions, see `--state-changing-only`.
+    StateChanging,
+    /// Only `view` and `pure` functions, see `--view-only`.
+    ReadOnly,
+}
+
+impl MutabilityScope {
+    /// Checks whether `node` is within the scope. Modifiers and code outside
+    /// of functions count as state-changing.
+    pub fn contains(&self, node: &SolAST) -> bool {
+        let read_only = matches!(
+            node.get_function_mutability().as_deref(),
+            Some("view") | Some("pure")
+        );
+        match self {
+            MutabilityScope::All => true,
+            MutabilityScope::StateChanging => !read_only,
+            MutabilityScope::ReadOnly => read_only,
+        }
+    }
+}
+
+/// Identifies a mutant across the files of a run: the canonical path of the
+/// mutated file, the bytes of it that are replaced, the mutation and the
+/// replacement.
+type MutantKey = (PathBuf, (usize, usize), MutationType, String);
+
+/// The mutants generated so far in a run, each with the file it was
+/// generated for, shared by all the files of the run, so that a file that
+/// is given more than once, under different paths, is not turned into the
+/// same mutant twice.
+/// Only the source unit of a file is traversed, never the ones it imports,
+/// so a file that imports another one of the run does not claim, or
+/// generate, any mutant of that one.
+#[derive(Debug, Clone, Default)]
+pub struct Claims(Rc<RefCell<HashMap<MutantKey, String>>>);
+
+impl Claims {
+    /// The key of `mutant` of `source`, the contents of `fnm`.
+    fn key(fnm: &str, source: &[u8], mutant: &str, mut_type: MutationType) -> MutantKey {
+        let (start, end, mutant_end) =
+            changed_range(source, mutant.as_bytes()).unwrap_or((0, 0, 0));
+        (
+            fs::canonicalize(fnm).unwrap_or_else(|_| fnm.into()),
+            (start, end),
+            mut_type,
+            mutant[start..mutant_end].to_string(),
+        )
+    }
+
+    /// The file that the mutant with `key` was generated for, if any.
+    fn claimant(&self, key: &MutantKey) -> Option<String> {
+        self.0.borrow().get(key).cloned()
+    }
+
+    fn claim(&self, key: MutantKey, fnm: &str) {
+        self.0.borrow_mut().insert(key, fnm.to_string());
+    }
+
+    /// The claims of `mutants`, generated into `outdir` by the run being
+    /// resumed.
+    /// Mutants whose original or mutant file can no longer be read are
+    /// left out.
+    pub fn resumed(outdir: &Path, mutants: &[MutantEntry]) -> Self {
+        let claims = Claims::default();
+        for m in mutants {
+            let (Some(mut_type), Ok(source)) =
+                (MutationType::from_name(&m.mutation), fs::read(&m.original))
+            else {
+                continue;
+            };
+            let written = match &m.edit {
+                Some(edit) => edit.apply(&source),
+                None => fs::read(outdir.join(&m.path)),
+            };
+            let Some(mutant) = written.ok().and_then(|w| {
+                RunMutations::strip_mutant_comment(&source, &String::from_utf8_lossy(&w), &mut_type)
+            }) else {
+                log::warn!("cannot read mutant {} of {}", m.id, m.original);
+                continue;
+            };
+            claims.claim(
+                Self::key(&m.original, &source, &mutant, mut_type),
+                &m.original,
+            );
+        }
+        claims
+    }
+}
+
 /// Data structure for running mutations.
 pub struct RunMutations {
     pub fnm: String,
     pub node: SolAST,
     pub num_mutants: i64,
-    pub rand: rand_pcg::Pcg64,
+    pub seed: u64,
     pub out: PathBuf,
     pub mutation_types: Vec<MutationType>,
     pub funcs_to_mutate: Option<Vec<String>>,
     pub contract: Option<String>,
+    pub comment_excerpt_len: usize,
+    pub checksum: bool,
+    pub suppressor: Suppressor,
+    /// Only make this attempt, see `MutationParams::only_attempt`.
+    pub only_attempt: Option<i64>,
+    /// Make that attempt at this mutation point, see
+    /// `MutationParams::only_point`.
+    pub only_point: Option<usize>,
+    /// Command line that reproduces this run, to which the mutation
+    /// and attempt are appended to get the command for a single mutant,
+    /// or empty if the mutants of this run cannot be reproduced.
+    pub repro_base: String,
+    /// Hook to run on every mutant once it is written.
+    pub on_mutant: Option<MutantHook>,
+    /// The manifest id of the first mutant of this file.
+    pub first_id: usize,
+    /// Progress on this file to continue from, when resuming a run.
+    pub resume: Option<FileProgress>,
+    /// Where to write the checkpoint after every mutant, and the
+    /// checkpoint to fill in with the progress on this file.
+    pub checkpoint: Option<(PathBuf, Checkpoint)>,
+    /// Whether to write mutants in full or only record their edits.
+    pub emit: Emit,
+    /// The Solidity compiler the file is compiled with.
+    pub solc: String,
+    /// The version of `solc`, if it reports one.
+    pub solc_version: Option<SolcVersion>,
+    /// Which functions to mutate, by their state mutability.
+    pub scope: MutabilityScope,
+    /// Names of the modifiers that `ReentrancyGuardMutation` removes.
+    pub reentrancy_guards: Vec<String>,
+    /// Told about the points, candidates and mutants of the file.
+    pub observer: Observer,
+    /// The mutants generated for the files before this one.
+    pub claims: Claims,
+    /// Decides which mutation to try next.
+    pub sampler: Sampler,
+    /// Limits on the paths of the mutants, see `mutant_dir`.
+    pub path_limit: PathLimit,
 }
 
 impl RunMutations {
@@ -36,11 +265,39 @@ impl RunMutations {
         node.name().map_or_else(|| false, |n| n == "assert")
     }
 
+    /// The random number generator for the given `attempt`.
+    /// Every attempt derives its own seed from `seed` so that any single
+    /// mutant can be regenerated without replaying the attempts before it.
+    pub fn attempt_rng(seed: u64, attempt: i64) -> rand_pcg::Pcg64 {
+        rand_pcg::Pcg64::seed_from_u64(
+            seed.wrapping_add((attempt as u64).wrapping_mul(SEED_STRIDE)),
+        )
+    }
+
+    /// The random number generator that the mutation of the given `attempt`
+    /// draws from, once its point is chosen, which is independent of how
+    /// the point was chosen.
+    pub fn mutation_rng(seed: u64, attempt: i64) -> rand_pcg::Pcg64 {
+        Self::attempt_rng(seed ^ MUTATION_SEED_SALT, attempt)
+    }
+
+    /// Checks that the bytes in which `mutant` differs from `source` lie
+    /// within `span`, the bounds of the node that was mutated.
+    /// This is checked before the mutant comment is added, so there is no
+    /// need for slack around the span.
+    pub fn check_span(source: &[u8], mutant: &str, span: (usize, usize)) -> Result<(), String> {
+        match changed_range(source, mutant.as_bytes()) {
+            Some((start, orig_end, _)) if start < span.0 || orig_end > span.1 => Err(format!(
+                "changed bytes {}..{} but the mutated node spans {}..{}",
+                start, orig_end, span.0, span.1
+            )),
+            _ => Ok(()),
+        }
+    }
+
     /// Check that the path exists.
     fn lkup_mutant_dir(&self) -> Option<PathBuf> {
-        let norm_path = get_path_normals(&self.fnm);
-        assert!(norm_path.is_some());
-        let mut_dir = PathBuf::from(&self.out).join(norm_path.unwrap());
+        let (mut_dir, _) = mutant_dir(&self.out, &self.fnm, self.path_limit);
         if mut_dir.parent().is_none() {
             None
         } else {
@@ -48,23 +305,34 @@ impl RunMutations {
         }
     }
 
-    /// Returns the closures for visiting, accepting, and skipping AST nodes.
-    fn mk_closures(
+    /// Returns the closures for visiting, accepting, and skipping AST nodes
+    /// of the file `fnm`.
+    /// Mutation points outside of `scope` are counted in `excluded` and,
+    /// like those that match a rule of `suppressor`, dropped by the visitor.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn mk_closures<'a>(
+        fnm: String,
         mutation_types: Vec<MutationType>,
         funcs_to_mutate: Option<Vec<String>>,
         contract: Option<String>,
+        suppressor: &'a mut Suppressor,
+        scope: MutabilityScope,
+        excluded: &'a mut usize,
     ) -> (
-        impl FnMut(&SolAST) -> Option<Vec<(mutation::MutationType, ast::SolAST)>>,
+        impl FnMut(&SolAST) -> NodeMutations + 'a,
         impl Fn(&SolAST) -> bool,
         impl Fn(&SolAST) -> bool,
     ) {
         let visitor = move |node: &ast::SolAST| {
-            let mapping: Vec<(mutation::MutationType, ast::SolAST)> = mutation_types
-                .iter()
-                .filter(|m| m.is_mutation_point(node))
-                .map(|m| (*m, node.clone()))
-                .into_iter()
-                .collect();
+            let in_scope = scope.contains(node);
+            let mut mapping: Vec<(mutation::MutationType, ast::SolAST)> = vec![];
+            for m in mutation_types.iter().filter(|m| m.is_mutation_point(node)) {
+                if !in_scope {
+                    *excluded += 1;
+                } else if !suppressor.suppresses(m, node) {
+                    mapping.push((*m, node.clone()));
+                }
+            }
             if mapping.is_empty() {
                 None
             } else {
@@ -72,16 +340,21 @@ impl RunMutations {
             }
         };
         let skip = Self::is_assert_call;
+        let in_contract = move |node: &SolAST, c: &str| {
+            node.contract
+                .as_ref()
+                .map_or_else(|| false, |n| contract_matches(c, &fnm, n))
+        };
         let accept = move |node: &SolAST| match (&contract, &funcs_to_mutate) {
             (None, None) => true,
-            (Some(c), None) => node.contract.as_ref().map_or_else(|| false, |n| n.eq(c)),
+            (Some(c), None) => in_contract(node, c),
             (None, Some(f)) => {
                 node.node_type()
                     .map_or_else(|| false, |n| n == "FunctionDefinition")
                     && f.contains(&node.name().unwrap())
             }
             (Some(c), Some(f)) => {
-                node.contract.as_ref().map_or_else(|| false, |n| n.eq(c))
+                in_contract(node, c)
                     && node
                         .node_type()
                         .map_or_else(|| false, |n| n == "FunctionDefinition")
@@ -93,39 +366,114 @@ impl RunMutations {
 
     /// Inner loop of mutation generation that uniformly
     /// genrates mutants from each possible mutation kind.
+    /// Mutation points whose every candidate has been tried are dropped,
+    /// and so are mutation types that run out of points, which stops the
+    /// loop early once there is nothing left to try.
+    /// Continues from `progress`, which is written to the checkpoint
+    /// after every mutant, and returns it once done, along with why it stopped.
     fn inner_loop(
+        &self,
+        source: &[u8],
         mut_dir: Option<PathBuf>,
-        fnm: String,
-        num_mutants: i64,
-        mut rand: rand_pcg::Pcg64,
         mut is_valid: impl FnMut(&str) -> Result<bool, Box<dyn std::error::Error>>,
         mutation_points: HashMap<MutationType, Vec<SolAST>>,
-        mut mutation_points_todo: VecDeque<MutationType>,
-    ) -> Result<Vec<PathBuf>, Box<dyn Error>> {
-        let mut source = Vec::new();
+        mut progress: FileProgress,
+    ) -> Result<(FileProgress, StoppedReason), Box<dyn Error>> {
+        let num_mutants = self.num_mutants;
         if mut_dir.is_none() {
             panic!("Mutation directory is empty.")
         }
-        let orig_path = Path::new(&fnm);
-        let mut f = File::open(orig_path)?;
-        f.read_to_end(&mut source)?;
-        let source_to_str = std::str::from_utf8(&source)?.into();
-        let mut attempts = 0;
-        let mut mutants: Vec<PathBuf> = vec![];
-        let mut seen: HashSet<String> = HashSet::new();
-        let total_attempts = num_mutants * ATTEMPTS;
-        seen.insert(source_to_str);
-        while !mutation_points_todo.is_empty() && attempts < total_attempts {
-            let mut_type = mutation_points_todo.remove(0).unwrap();
+        std::str::from_utf8(source)?;
+        let total_attempts = self
+            .only_attempt
+            .map_or(num_mutants * ATTEMPTS, |attempt| attempt + 1);
+        progress.seen.insert(sha256_hex(source));
+        let counts: HashMap<MutationType, Vec<Option<usize>>> = mutation_points
+            .iter()
+            .map(|(m, ps)| (*m, ps.iter().map(|p| m.mutation_count(p, source)).collect()))
+            .collect();
+        while !progress.todo.is_empty() && progress.attempts < total_attempts {
+            let attempts = progress.attempts;
+            let mut rand = Self::attempt_rng(self.seed, attempts);
+            let next = self
+                .sampler
+                .with(|s| s.select(&progress.todo, &mutation_points, &mut rand));
+            let mut_type = progress.todo.remove(next).unwrap();
             let points = mutation_points
                 .get(&mut_type)
                 .expect("Found unexpected mutation.");
-            if let Some(point) = points.choose(&mut rand) {
-                let mut mutant = mut_type.mutate_randomly(point, &source, &mut rand);
-                if !seen.contains(&mutant) && is_valid(&mutant)? {
-                    if let Ok(res) = Self::add_mutant_comment(orig_path, &mutant, &mut_type) {
-                        mutant = res;
-                    }
+            let tried = progress
+                .tried
+                .entry(mut_type)
+                .or_insert_with(|| vec![0; points.len()]);
+            let live: Vec<usize> = (0..points.len())
+                .filter(|i| counts[&mut_type][*i].map_or_else(|| true, |c| tried[*i] < c))
+                .collect();
+            if live.is_empty() {
+                log::info!("every candidate of {} has been tried", mut_type);
+                progress.todo.retain(|m| *m != mut_type);
+                continue;
+            }
+            // Which points are live depends on the history, which
+            // `--only-attempt` starts without, so it is given the point with
+            // `--only-point`, and the mutation draws from a generator of its
+            // own that does not depend on how the point was chosen.
+            let index = match self.only_point {
+                Some(index) => index,
+                None => self
+                    .sampler
+                    .with(|s| s.select_point(mut_type, &live, &mut rand)),
+            };
+            let mut rand = Self::mutation_rng(self.seed, attempts);
+            if live.contains(&index) {
+                let point = &points[index];
+                let mut mutant = mut_type.mutate_randomly(point, source, &mut rand);
+                if !progress.seen.contains(&sha256_hex(mutant.as_bytes())) {
+                    tried[index] += 1;
+                }
+                let stat = progress.stats.entry(mut_type).or_default();
+                stat.attempts += 1;
+                if let Err(e) = Self::check_span(source, &mutant, point.get_bounds()) {
+                    log::error!(
+                        "internal error: {} produced an out-of-span edit of {}: {}",
+                        mut_type,
+                        self.fnm,
+                        e
+                    );
+                    stat.out_of_span += 1;
+                    self.candidate(mut_type, point, CandidateOutcome::OutOfSpan);
+                    progress.todo.push_back(mut_type);
+                    progress.seen.insert(sha256_hex(mutant.as_bytes()));
+                    progress.attempts += 1;
+                    continue;
+                }
+                let key = Claims::key(&self.fnm, source, &mutant, mut_type);
+                if let Some(claimant) = self.claims.claimant(&key) {
+                    log::info!(
+                        "merged a {} mutant of {} with the one generated for {}",
+                        mut_type,
+                        self.fnm,
+                        claimant
+                    );
+                    stat.duplicates += 1;
+                    self.candidate(mut_type, point, CandidateOutcome::Duplicate);
+                    progress.todo.push_back(mut_type);
+                    progress.seen.insert(sha256_hex(mutant.as_bytes()));
+                    progress.attempts += 1;
+                    continue;
+                }
+                let mut written = false;
+                let duplicate = progress.seen.contains(&sha256_hex(mutant.as_bytes()));
+                if !duplicate && is_valid(&mutant)? {
+                    // So that generating it again counts as a duplicate.
+                    progress.seen.insert(sha256_hex(mutant.as_bytes()));
+                    mutant = Self::add_mutant_comment(
+                        source,
+                        &mutant,
+                        &mut_type,
+                        self.comment_excerpt_len,
+                        point.get_statement(),
+                    );
                     let mut_file = mut_dir.as_ref().unwrap().to_str().unwrap().to_owned()
                         + "_"
                         + &attempts.to_string()
@@ -135,131 +483,1415 @@ impl RunMutations {
                         "Found a valid mutant of type {}",
                         ansi_term::Colour::Cyan.paint(mut_type.to_string()),
                     );
-                    std::fs::write(mut_path, &mutant)?;
+                    let edit = match self.emit {
+                        Emit::Full => {
+                            write_atomically(mut_path, mutant.as_bytes())?;
+                            log::info!(
+                                "{}: Mutant written at {:?}",
+                                ansi_term::Colour::Green.paint("SUCCESS"),
+                                mut_path
+                            );
+                            None
+                        }
+                        Emit::Sparse => Some(SparseEdit::new(source, &mutant)),
+                    };
                     log::info!(
-                        "{}: Mutant written at {:?}",
-                        ansi_term::Colour::Green.paint("SUCCESS"),
-                        mut_path
+                        "{}",
+                        render_diff(source, mutant.as_bytes(), DIFF_MAX_LINE_LEN)
                     );
-                    Self::diff_mutant(orig_path, mut_path)?;
-                    mutants.push(mut_path.to_owned());
+                    let hook_status = match &self.on_mutant {
+                        Some(hook) => Some(hook.run(
+                            &mut_file,
+                            self.first_id + progress.mutants.len(),
+                            &mut_type.to_string(),
+                        )?),
+                        None => None,
+                    };
+                    if let (Some(hook), Some(status)) = (&self.on_mutant, hook_status) {
+                        if hook.required && !status.success() {
+                            log::info!(
+                                "Discarding mutant {:?}, --on-mutant hook returned {:?}",
+                                mut_path,
+                                status
+                            );
+                            std::fs::remove_file(mut_path)?;
+                            stat.hook_rejected += 1;
+                            self.candidate(mut_type, point, CandidateOutcome::HookRejected);
+                            progress.todo.push_back(mut_type);
+                            progress.seen.insert(sha256_hex(mutant.as_bytes()));
+                            progress.attempts += 1;
+                            continue;
+                        }
+                    }
+                    stat.accepted += 1;
+                    progress.mutants.push(MutantEntry {
+                        original: self.fnm.clone(),
+                        path: mut_path
+                            .strip_prefix(&self.out)
+                            .unwrap_or(mut_path)
+                            .to_string_lossy()
+                            .to_string(),
+                        mutation: mut_type.to_string(),
+                        security_critical: mut_type.is_security_critical(),
+                        contract: point
+                            .get_contract()
+                            .map(|c| qualified_contract(&self.fnm, &c)),
+                        function: point.get_function(),
+                        signature: point.get_function_signature(),
+                        span: match edit {
+                            None => changed_range(source, mutant.as_bytes())
+                                .map(|(start, end, _)| (start, end)),
+                            Some(_) => None,
+                        },
+                        sha256: self.checksum.then(|| sha256_hex(mutant.as_bytes())),
+                        repro_command: if self.repro_base.is_empty() {
+                            String::new()
+                        } else {
+                            format!(
+                                "{} --mutations {} --only-point {} --only-attempt {}",
+                                self.repro_base,
+                                mut_type.to_possible_value().unwrap().get_name(),
+                                index,
+                                attempts
+                            )
+                        },
+                        hook_status,
+                        edit,
+                        solc: self.solc.clone(),
+                        ..Default::default()
+                    });
+                    self.claims.claim(key, &self.fnm);
+                    self.candidate(mut_type, point, CandidateOutcome::Accepted);
+                    self.observer
+                        .notify(|o| o.on_mutant_accepted(progress.mutants.last().unwrap()));
+                    written = true;
                 } else {
-                    mutation_points_todo.push_back(mut_type);
+                    let outcome = if duplicate {
+                        stat.duplicates += 1;
+                        CandidateOutcome::Duplicate
+                    } else {
+                        stat.invalid += 1;
+                        CandidateOutcome::Invalid
+                    };
+                    self.candidate(mut_type, point, outcome);
+                    progress.todo.push_back(mut_type);
+                }
+                progress.seen.insert(sha256_hex(mutant.as_bytes()));
+                progress.attempts += 1;
+                if let (true, Some((path, checkpoint))) = (written, &self.checkpoint) {
+                    Checkpoint {
+                        progress: Some(progress.clone()),
+                        ..checkpoint.clone()
+                    }
+                    .write(path)?;
                 }
-                seen.insert(mutant);
-                attempts += 1;
+            } else {
+                progress.todo.push_back(mut_type);
+                progress.attempts += 1;
             }
         }
-        if (attempts >= total_attempts) && (mutants.len() < num_mutants.try_into().unwrap()) {
+        if (progress.attempts >= total_attempts)
+            && (progress.mutants.len() < num_mutants.try_into().unwrap())
+        {
             log::info!(
                 "Found {} valid mutants in {} attempts.",
-                mutants.len(),
+                progress.mutants.len(),
                 total_attempts
             );
         }
-        Ok(mutants)
+        let stopped = if progress.mutants.len() >= num_mutants.try_into().unwrap() {
+            StoppedReason::Completed
+        } else if progress.todo.is_empty() {
+            StoppedReason::MutationSpaceExhausted
+        } else {
+            StoppedReason::AttemptsExhausted
+        };
+        Ok((progress, stopped))
     }
 
-    /// Logs the diff of the mutants w.r.t. the origin program.
-    fn diff_mutant(orig: &Path, mutant: &Path) -> Result<(), Box<dyn Error>> {
-        let (succ, diff, _) = invoke_command(
-            "diff",
-            vec![
-                orig.to_str().unwrap(),
-                mutant.to_str().unwrap(),
-                "--color=always",
-            ],
-        )?;
-        log::info!("{}", String::from_utf8(diff.to_vec()).unwrap());
-        match succ.unwrap_or_else(|| panic!("diff call terminated with a signal.")) {
-            0 => log::info!("mutant identical to original program"),
-            1 => log::info!("{}", std::str::from_utf8(&diff).unwrap()),
-            _ => log::info!("install a `diff` program to see the diff"),
-        }
-        Ok(())
+    /// Tell the observer and the sampling strategy what became of a
+    /// candidate generated by `mut_type` at `point`.
+    fn candidate(&self, mut_type: MutationType, point: &SolAST, outcome: CandidateOutcome) {
+        self.observer
+            .notify(|o| o.on_candidate(mut_type, point.get_bounds(), outcome));
+        self.sampler
+            .with(|s| s.feedback(mut_type, outcome == CandidateOutcome::Accepted));
     }
 
     /// Adds a comment to indicate what kind of mutation happened.
+    /// The comment is placed on its own line right above the first line
+    /// where `mutant` differs from `source`, even if the change starts in
+    /// the middle of that line, and quotes at most `excerpt_len` characters
+    /// of the original line. If the change lies within `statement`, the
+    /// bounds of the enclosing statement, the comment goes above the first
+    /// line of the statement instead, so that it does not split a statement
+    /// spanning several lines. The comment is indented like the line of the
+    /// mutant it precedes, which may differ from the original line, e.g.,
+    /// when lines are swapped, and uses the same line ending.
+    /// A byte order mark at the start of the file stays in front of it.
     fn add_mutant_comment(
-        src_path: &Path,
-        mutant: &String,
+        source: &[u8],
+        mutant: &str,
         mut_type: &MutationType,
-    ) -> Result<String, ScannerError> {
-        let mut scan1 = Scanner::scan_path(src_path)?;
-        let mut scan2 = Scanner::new(mutant.as_bytes());
-        let mut res = vec![];
-        loop {
-            let l1 = scan1.next_line_raw().unwrap();
-            let l2 = scan2.next_line_raw().unwrap();
-            if l1.is_none() || l2.is_none() {
-                break;
+        excerpt_len: usize,
+        statement: Option<(usize, usize)>,
+    ) -> String {
+        let diff = source
+            .iter()
+            .zip(mutant.as_bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if diff == source.len() && diff == mutant.len() {
+            return mutant.to_string();
+        }
+        let bom = if source.starts_with(UTF8_BOM) {
+            UTF8_BOM.len()
+        } else {
+            0
+        };
+        let changed = std::cmp::max(line_start(source, diff), bom);
+        let orig_line = String::from_utf8_lossy(&source[changed..line_end(source, changed)]);
+        let start = match statement {
+            Some((stmt_start, _)) if stmt_start <= diff => {
+                std::cmp::max(line_start(source, stmt_start), bom)
             }
-            let l1_to_str = String::from_utf8(l1.unwrap()).unwrap() + "\n";
-            let l2_to_str = String::from_utf8(l2.unwrap()).unwrap() + "\n";
-            if l1_to_str != l2_to_str {
-                let indent = get_indent(&l1_to_str);
-                let comment = indent + "/// " + &mut_type.to_string() + " of: " + l1_to_str.trim();
-                res.push(comment);
-                res.push("\n".to_string() + &l2_to_str);
-                break;
+            _ => changed,
+        };
+        let mutant_line = &mutant[start..line_end(mutant.as_bytes(), start)];
+        let newline = if mutant_line.ends_with('\r') {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let comment = get_indent(mutant_line)
+            + "/// "
+            + &mut_type.to_string()
+            + " of: "
+            + &excerpt(&orig_line, excerpt_len)
+            + newline;
+        [&mutant[..start], &comment, &mutant[start..]].concat()
+    }
+
+    /// The mutant that `add_mutant_comment` turned into `commented`, if
+    /// `commented` has the comment it adds.
+    fn strip_mutant_comment(
+        source: &[u8],
+        commented: &str,
+        mut_type: &MutationType,
+    ) -> Option<String> {
+        let same = source
+            .iter()
+            .zip(commented.as_bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let start = line_start(commented.as_bytes(), same);
+        let end = start + commented[start..].find('\n')? + 1;
+        commented[start..end]
+            .trim_start()
+            .starts_with(&format!("/// {} of: ", mut_type))
+            .then(|| [&commented[..start], &commented[end..]].concat())
+    }
+
+    /// Read the file being mutated.
+    /// A byte order mark is kept as part of the source, since the offsets
+    /// in the AST count its bytes too.
+    fn read_source(&self) -> io::Result<Vec<u8>> {
+        let mut source = Vec::new();
+        let mut f = File::open(Path::new(&self.fnm))?;
+        f.read_to_end(&mut source)?;
+        if source.starts_with(UTF8_BOM) {
+            log::info!("{} starts with a UTF-8 byte order mark", self.fnm);
+        }
+        Ok(source)
+    }
+
+    /// Traverse the AST and collect every mutation point, paired with
+    /// the mutation type that applies to it, logging how many points
+    /// were suppressed or left out.
+    fn find_mutation_points(&self, source: &[u8]) -> Vec<(MutationType, SolAST)> {
+        let mut suppressor = self.suppressor.clone();
+        suppressor.index_lines(source);
+        let mut excluded = 0;
+        let (visitor, skip, accept) = Self::mk_closures(
+            self.fnm.clone(),
+            self.mutation_types.clone(),
+            self.funcs_to_mutate.clone(),
+            self.contract.clone(),
+            &mut suppressor,
+            self.scope,
+            &mut excluded,
+        );
+        // each pair represents a mutation type and the AST node on which it is applicable.
+        let mutations: Vec<(MutationType, SolAST)> = self
+            .node
+            .clone()
+            .with_reentrancy_guards(self.reentrancy_guards.clone())
+            .with_solc_version(self.solc_version)
+            .traverse(visitor, skip, accept)
+            .into_iter()
+            .flatten()
+            .collect();
+        for (rule, count) in suppressor.counts() {
+            log::info!(
+                "suppress rule {} suppressed {} mutation points",
+                rule,
+                count
+            );
+        }
+        if self.scope != MutabilityScope::All {
+            log::info!(
+                "{} mutation points of {} are outside of {:?} functions",
+                excluded,
+                self.fnm,
+                self.scope
+            );
+        }
+        mutations
+    }
+
+    /// Generate up to `n` distinct candidate mutants for each mutation type,
+    /// without checking that they compile or writing them anywhere, and
+    /// render their diffs grouped by mutation type, for `--preview`.
+    pub fn preview(self, n: usize) -> io::Result<String> {
+        let source = self.read_source()?;
+        let mutation_points = self
+            .find_mutation_points(&source)
+            .into_iter()
+            .into_group_map();
+        let mut res = String::new();
+        for mut_type in self.mutation_types.iter().unique() {
+            let mut candidates: Vec<String> = vec![];
+            if let Some(points) = mutation_points.get(mut_type) {
+                let mut seen = HashSet::from([sha256_hex(&source)]);
+                for attempt in 0..(n as i64) * ATTEMPTS {
+                    if candidates.len() == n {
+                        break;
+                    }
+                    let mut rand = Self::attempt_rng(self.seed, attempt);
+                    let point = points.choose(&mut rand).unwrap();
+                    let mutant = mut_type.mutate_randomly(point, &source, &mut rand);
+                    if Self::check_span(&source, &mutant, point.get_bounds()).is_ok()
+                        && seen.insert(sha256_hex(mutant.as_bytes()))
+                    {
+                        candidates.push(mutant);
+                    }
+                }
+            }
+            res += &format!(
+                "=== {} on {}: {} unvalidated preview(s) ===\n",
+                mut_type,
+                self.fnm,
+                candidates.len()
+            );
+            for (i, mutant) in candidates.iter().enumerate() {
+                res += &format!("--- preview {} of {} (unvalidated) ---\n", i + 1, mut_type);
+                res += &render_diff(&source, mutant.as_bytes(), DIFF_MAX_LINE_LEN);
+                res += "\n";
             }
-            res.push(l2_to_str);
         }
-        loop {
-            let l2 = scan2.next_line_raw().unwrap();
-            if l2.is_none() {
-                break;
+        Ok(res)
+    }
+
+    /// Report, for every function (or modifier) of the file, how complex it
+    /// is, how many mutation points it has, and which of the `mutants`
+    /// generated from the file came from it.
+    pub fn function_report(&self, mutants: &[MutantEntry]) -> io::Result<Vec<FunctionReport>> {
+        let source = self.read_source()?;
+        let points = self.find_mutation_points(&source);
+        let reports = self.node.clone().traverse(
+            |node| {
+                let node_type = node.node_type()?;
+                let body = node.get_node("body");
+                if !matches!(
+                    node_type.as_str(),
+                    "FunctionDefinition" | "ModifierDefinition"
+                ) || body.src().is_none()
+                {
+                    return None;
+                }
+                let (contract, name) = (node.get_contract(), node.name()?);
+                let signature = node.signature();
+                let (statements, branches) = body.complexity();
+                let qualified = contract.as_ref().map(|c| qualified_contract(&self.fnm, c));
+                // Overloads are told apart by their signatures.
+                let mine: Vec<&MutantEntry> = mutants
+                    .iter()
+                    .filter(|m| m.contract == qualified && m.signature.as_ref() == Some(&signature))
+                    .collect();
+                Some(FunctionReport {
+                    points: points
+                        .iter()
+                        .filter(|(_, p)| {
+                            p.get_contract() == contract
+                                && p.get_function_signature().as_ref() == Some(&signature)
+                        })
+                        .count(),
+                    mutants: mine.len(),
+                    mutations: mine
+                        .iter()
+                        .map(|m| m.mutation.clone())
+                        .sorted()
+                        .dedup()
+                        .collect(),
+                    function: if name.is_empty() {
+                        node.kind().unwrap_or_default()
+                    } else {
+                        name
+                    },
+                    contract,
+                    statements,
+                    branches,
+                })
+            },
+            |_| false,
+            |_| true,
+        );
+        Ok(reports)
+    }
+
+    /// The progress of a file with `mutation_points` before its first
+    /// attempt, with the mutation types due in turn for every mutant.
+    fn start_progress(&self, mutation_points: &HashMap<MutationType, Vec<SolAST>>) -> FileProgress {
+        let mut progress = FileProgress {
+            attempts: self.only_attempt.unwrap_or(0),
+            ..Default::default()
+        };
+        for (m, ps) in mutation_points {
+            progress.stats.entry(*m).or_default().points = ps.len();
+        }
+        let points: Vec<&MutationType> = mutation_points.keys().collect();
+        let points_len = points.len() as i64;
+        let mut remaining = self.num_mutants;
+        while remaining > 0 {
+            let to_take = std::cmp::min(remaining, points_len);
+            let selected: Vec<&&MutationType> = points.iter().take(to_take as usize).collect();
+            for s in selected {
+                progress.todo.push_back(**s);
             }
-            let l2_to_str = String::from_utf8(l2.unwrap()).unwrap() + "\n";
-            res.push(l2_to_str);
+            remaining -= points_len;
         }
-        Ok(res.concat())
+        progress
     }
 
     /// Mutation Generator that traverses the AST and determines which points
     /// can be mutated using which mutation type,
     /// then collects all the mutations that need to be done and calls
     /// `inner_loop` where the actual mutations are done.
+    /// Also returns how each mutation type fared and why it stopped.
     pub fn get_mutations(
-        self,
+        &self,
         is_valid: impl FnMut(&str) -> Result<bool, Box<dyn std::error::Error>>,
-    ) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    ) -> Result<(Vec<MutantEntry>, FileStats, StoppedReason), Box<dyn Error>> {
         let mut_dir = self.lkup_mutant_dir();
-        let (visitor, skip, accept) =
-            Self::mk_closures(self.mutation_types, self.funcs_to_mutate, self.contract);
-        // each pair represents a mutation type and the AST node on which it is applicable.
-        let mutations: Vec<(MutationType, SolAST)> = self
-            .node
-            .traverse(visitor, skip, accept)
+        let source = self.read_source()?;
+        let mutations = self.find_mutation_points(&source);
+        let counts = mutations.iter().map(|(m, _)| *m).counts();
+        self.observer.notify(|o| o.on_point_collection(&counts));
+        if !mutations.is_empty() {
+            let mutation_points = mutations.into_iter().into_group_map();
+            let progress = match &self.resume {
+                Some(progress) => progress.clone(),
+                None => self.start_progress(&mutation_points),
+            };
+            self.inner_loop(&source, mut_dir, is_valid, mutation_points, progress)
+                .map(|(progress, stopped)| (progress.mutants, progress.stats, stopped))
+        } else {
+            log::info!("Did not find any mutations");
+            let stopped = if self.num_mutants > 0 {
+                StoppedReason::MutationSpaceExhausted
+            } else {
+                StoppedReason::Completed
+            };
+            Ok((vec![], FileStats::new(), stopped))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_path_normals, HookStatus};
+    use std::{collections::HashSet, time::Duration};
+
+    /// Collect the mutation points of the AST in `json`.
+    fn mutation_points(
+        fnm: &str,
+        json: &str,
+        mutation_types: Vec<MutationType>,
+        funcs_to_mutate: Option<Vec<String>>,
+        contract: Option<String>,
+        suppressor: &mut Suppressor,
+    ) -> Vec<(MutationType, SolAST)> {
+        let f = File::open(json).unwrap();
+        let ast = SolAST::new(serde_json::from_reader(f).unwrap(), None);
+        let mut excluded = 0;
+        let (visitor, skip, accept) = RunMutations::mk_closures(
+            fnm.into(),
+            mutation_types,
+            funcs_to_mutate,
+            contract,
+            suppressor,
+            MutabilityScope::All,
+            &mut excluded,
+        );
+        ast.traverse(visitor, skip, accept)
             .into_iter()
             .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn test_suppress_with_include_filters() {
+        let points = |rules: &str| {
+            let mut suppressor = Suppressor::new(serde_json::from_str(rules).unwrap()).unwrap();
+            let points = mutation_points(
+                "benchmarks/MultipleContracts/C.sol",
+                "benchmarks/MultipleContracts/C.json",
+                vec![MutationType::BinaryOpMutation],
+                Some(vec!["getarray".into()]),
+                Some("C".into()),
+                &mut suppressor,
+            );
+            (
+                points,
+                suppressor.counts().iter().map(|(_, c)| *c).collect_vec(),
+            )
+        };
+        let (unsuppressed, _) = points("[]");
+        assert_eq!(unsuppressed.len(), 1);
+        assert_eq!(unsuppressed[0].1.get_contract().unwrap(), "C");
+        assert_eq!(unsuppressed[0].1.get_function().unwrap(), "getarray");
+        // `Utils.getarray` also contains a binary operation, but it is
+        // excluded by the contract filter so it is not counted as suppressed.
+        let (suppressed, counts) = points(r#"[{"within_function": "getarray"}]"#);
+        assert!(suppressed.is_empty());
+        assert_eq!(counts, vec![1]);
+        let (suppressed, counts) = points(r#"[{"within_contract": "Utils"}]"#);
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(counts, vec![0]);
+    }
+
+    #[test]
+    fn test_qualified_contract_filter() {
+        // The same AST stands in for two files that both define `C`.
+        let points = |fnm: &str, contract: &str| {
+            mutation_points(
+                fnm,
+                "benchmarks/MultipleContracts/C.json",
+                vec![MutationType::BinaryOpMutation],
+                None,
+                Some(contract.into()),
+                &mut Suppressor::default(),
+            )
+            .len()
+        };
+        let (a, b) = ("src/C.sol", "test/mocks/C.sol");
+        assert_eq!(points(a, "C"), 1);
+        assert_eq!(points(b, "C"), 1);
+        assert_eq!(points(a, "src/C.sol:C"), 1);
+        assert_eq!(points(b, "src/C.sol:C"), 0);
+        assert_eq!(points(b, "mocks/C.sol:C"), 1);
+        assert_eq!(points(a, "src/C.sol:Utils"), 1);
+        assert_eq!(points(a, "src/C.sol:D"), 0);
+
+        // The manifest records the qualified name of the mutated contract.
+        let out = std::env::temp_dir().join("gambit_test_qualified_contract");
+        let (mutants, _, _) = run_c(&out, vec![MutationType::BinaryOpMutation], None);
+        assert!(!mutants.is_empty());
+        for m in &mutants {
+            assert!(matches!(
+                m.contract.as_deref(),
+                Some(
+                    "benchmarks/MultipleContracts/C.sol:C"
+                        | "benchmarks/MultipleContracts/C.sol:Utils"
+                )
+            ));
+        }
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+
+    /// Generate mutants of `benchmarks/MultipleContracts/C.sol` into `out`,
+    /// treating every candidate as valid.
+    fn run_c(
+        out: &Path,
+        mutation_types: Vec<MutationType>,
+        on_mutant: Option<MutantHook>,
+    ) -> (Vec<MutantEntry>, FileStats, StoppedReason) {
+        let fnm = "benchmarks/MultipleContracts/C.sol";
+        let _ = std::fs::remove_dir_all(out);
+        std::fs::create_dir_all(out.join("benchmarks/MultipleContracts")).unwrap();
+        let f = File::open("benchmarks/MultipleContracts/C.json").unwrap();
+        let node = SolAST::new(serde_json::from_reader(f).unwrap(), None);
+        RunMutations {
+            on_mutant,
+            ..runner(fnm, node, out, 4, mutation_types)
+        }
+        .get_mutations(|_| Ok(true))
+        .unwrap()
+    }
+
+    /// The `RunMutations` for generating `num_mutants` mutants of `fnm`,
+    /// whose AST is `node`, into `out`.
+    fn runner(
+        fnm: &str,
+        node: SolAST,
+        out: &Path,
+        num_mutants: i64,
+        mutation_types: Vec<MutationType>,
+    ) -> RunMutations {
+        RunMutations {
+            fnm: fnm.into(),
+            node,
+            num_mutants,
+            seed: 0,
+            out: out.to_path_buf(),
+            mutation_types,
+            funcs_to_mutate: None,
+            contract: None,
+            comment_excerpt_len: 200,
+            checksum: false,
+            suppressor: Suppressor::default(),
+            only_attempt: None,
+            only_point: None,
+            repro_base: "gambit mutate".into(),
+            on_mutant: None,
+            first_id: 1,
+            resume: None,
+            checkpoint: None,
+            emit: Emit::Full,
+            solc: "solc".into(),
+            solc_version: None,
+            scope: MutabilityScope::All,
+            reentrancy_guards: vec!["nonReentrant".into()],
+            observer: Observer::default(),
+            claims: Claims::default(),
+            sampler: Sampler::default(),
+            path_limit: PathLimit::host(),
+        }
+    }
+
+    #[test]
+    fn test_stop_when_mutation_space_exhausted() {
+        let root = std::env::temp_dir().join("gambit_test_exhausted");
+        let _ = std::fs::remove_dir_all(&root);
+        let (fnm, out) = (root.join("Tiny.sol"), root.join("out"));
+        std::fs::create_dir_all(out.join(get_path_normals(root.to_str().unwrap()).unwrap()))
+            .unwrap();
+        let source = "contract Tiny { function f(uint a, uint b) public { x = a - b; } }\n";
+        std::fs::write(&fnm, source).unwrap();
+        let at = |text: &str| source.find(text).unwrap();
+        let node = SolAST::new(
+            serde_json::json!({
+                "nodeType": "BinaryOperation",
+                "operator": "-",
+                "src": format!("{}:5:0", at("a - b")),
+                "leftExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at("a - b"))},
+                "rightExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at("a - b") + 4)},
+            }),
+            None,
+        );
+        // `-` can only be replaced by `+`, `*`, `/`, `%` and `**`,
+        // far fewer than the 10 mutants asked for.
+        let run = runner(
+            fnm.to_str().unwrap(),
+            node,
+            &out,
+            10,
+            vec![MutationType::BinaryOpMutation],
+        );
+        let mut compiled = 0;
+        let (mutants, stats, stopped) = run
+            .get_mutations(|mutant| {
+                compiled += 1;
+                Ok(!mutant.contains("a / b"))
+            })
+            .unwrap();
+        assert_eq!(stopped, StoppedReason::MutationSpaceExhausted);
+        assert_eq!(compiled, 5);
+        assert_eq!(mutants.len(), 4);
+        let distinct: HashSet<String> = mutants
+            .iter()
+            .map(|m| std::fs::read_to_string(out.join(&m.path)).unwrap())
             .collect();
-        if !mutations.is_empty() {
-            let mutation_points = mutations.into_iter().into_group_map();
-            let points: Vec<&MutationType> = mutation_points.keys().collect();
-            let points_len = points.len() as i64;
-            let mut mutation_points_todo: VecDeque<MutationType> = VecDeque::new();
-            let mut remaining = self.num_mutants;
-            while remaining > 0 {
-                let to_take = std::cmp::min(remaining, points_len);
-                let selected: Vec<&&MutationType> = points.iter().take(to_take as usize).collect();
-                for s in selected {
-                    mutation_points_todo.push_back(**s);
-                }
-                remaining -= points_len;
+        assert_eq!(distinct.len(), 4);
+        let stat = stats[&MutationType::BinaryOpMutation];
+        assert!(stat.attempts < 10 * ATTEMPTS as usize);
+
+        // With many points that are used up after a single candidate, each
+        // attempt goes to one of the points that are left, so there are as
+        // many attempts as there are candidates.
+        let fnm = root.join("Many.sol");
+        let source: String = (0..12).map(|i| format!("x{} = a + b;\n", i)).collect();
+        std::fs::write(&fnm, &source).unwrap();
+        let statements: Vec<serde_json::Value> = source
+            .match_indices("a + b")
+            .map(|(at, _)| {
+                serde_json::json!({
+                    "nodeType": "BinaryOperation",
+                    "operator": "+",
+                    "src": format!("{}:5:0", at),
+                    "leftExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at)},
+                    "rightExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at + 4)},
+                })
+            })
+            .collect();
+        let block = SolAST::new(
+            serde_json::json!({"nodeType": "Block", "src": format!("0:{}:0", source.len()), "statements": statements}),
+            None,
+        );
+        let run = runner(
+            fnm.to_str().unwrap(),
+            block,
+            &out,
+            20,
+            vec![MutationType::AddSubMutation],
+        );
+        let source = run.read_source().unwrap();
+        let points = run
+            .find_mutation_points(&source)
+            .into_iter()
+            .into_group_map();
+        assert_eq!(points[&MutationType::AddSubMutation].len(), 12);
+        let progress = run.start_progress(&points);
+        let (progress, stopped) = run
+            .inner_loop(
+                &source,
+                run.lkup_mutant_dir(),
+                |_| Ok(true),
+                points,
+                progress,
+            )
+            .unwrap();
+        assert_eq!(stopped, StoppedReason::MutationSpaceExhausted);
+        assert_eq!(progress.attempts, 12);
+        assert_eq!(progress.mutants.len(), 12);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_only_configured_reentrancy_guards() {
+        let root = std::env::temp_dir().join("gambit_test_reentrancy_guards");
+        let _ = std::fs::remove_dir_all(&root);
+        let (fnm, out) = (root.join("Vault.sol"), root.join("out"));
+        std::fs::create_dir_all(out.join(get_path_normals(root.to_str().unwrap()).unwrap()))
+            .unwrap();
+        let source = "contract Vault { function f() external lock onlyOwner {} }\n";
+        std::fs::write(&fnm, source).unwrap();
+        let invocation = |name: &str| {
+            let src = format!("{}:{}:0", source.find(name).unwrap(), name.len());
+            serde_json::json!({
+                "nodeType": "ModifierInvocation",
+                "src": src,
+                "modifierName": {"nodeType": "IdentifierPath", "name": name, "src": src},
+            })
+        };
+        let node = SolAST::new(
+            serde_json::json!({
+                "nodeType": "FunctionDefinition",
+                "name": "f",
+                "modifiers": [invocation("lock"), invocation("onlyOwner")],
+            }),
+            None,
+        );
+        let run = |guards: Vec<String>| {
+            RunMutations {
+                reentrancy_guards: guards,
+                ..runner(
+                    fnm.to_str().unwrap(),
+                    node.clone(),
+                    &out,
+                    5,
+                    vec![MutationType::ReentrancyGuardMutation],
+                )
             }
-            Self::inner_loop(
-                mut_dir,
-                self.fnm,
-                self.num_mutants,
-                self.rand,
-                is_valid,
-                mutation_points,
-                mutation_points_todo,
+            .get_mutations(|_| Ok(true))
+            .unwrap()
+            .0
+        };
+        assert!(run(vec!["nonReentrant".into()]).is_empty());
+        let mutants = run(vec!["lock".into()]);
+        assert_eq!(mutants.len(), 1);
+        assert!(mutants[0].security_critical);
+        let mutant = std::fs::read_to_string(out.join(&mutants[0].path)).unwrap();
+        assert!(mutant.contains("function f() external  onlyOwner {}"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_function_report() {
+        use serde_json::{json, Value};
+        let fnm = "benchmarks/FunctionReport/Vault.sol";
+        let source = std::fs::read_to_string(fnm).unwrap();
+        let at = |text: &str| source.find(text).unwrap();
+        let node = |node_type: &str, start: usize, len: usize| json!({"nodeType": node_type, "src": format!("{}:{}:0", start, len)});
+        let text = |node_type: &str, text: &str| node(node_type, at(text), text.len());
+        let binop = |text: &str, op: &str| {
+            let (l, r) = text.split_once(&format!(" {} ", op)).unwrap();
+            let mut b = node("BinaryOperation", at(text), text.len());
+            b["operator"] = json!(op);
+            b["leftExpression"] = node("Identifier", at(text), l.len());
+            b["rightExpression"] = node("Identifier", at(text) + text.len() - r.len(), r.len());
+            b
+        };
+        let statement = |node_type: &str, s: &str, expression: Value| {
+            let mut n = text(node_type, s);
+            n["expression"] = expression;
+            n
+        };
+        let require = |s: &str, cond: Value| {
+            let mut call = text("FunctionCall", s);
+            call["expression"] = json!({"nodeType": "Identifier", "name": "require"});
+            call["arguments"] = json!([cond]);
+            statement("ExpressionStatement", s, call)
+        };
+        let assign = |s: &str, rhs: Value| {
+            let mut a = text("Assignment", s);
+            a["rightHandSide"] = rhs;
+            statement("ExpressionStatement", s, a)
+        };
+        let function = |name: &str, statements: Vec<Value>| {
+            let mut f = text("FunctionDefinition", &format!("function {}", name));
+            f["name"] = json!(name);
+            f["kind"] = json!("function");
+            f["body"] = json!({"nodeType": "Block", "src": "0:0:0", "statements": statements});
+            f
+        };
+        let mut branch = text("IfStatement", "if (amount > limit)");
+        branch["condition"] = binop("amount > limit", ">");
+        branch["trueBody"] =
+            json!({"nodeType": "Block", "statements": [assign("amount = limit", json!(null))]});
+        let mut for_loop = text("ForStatement", "for (uint256 i");
+        for_loop["initializationExpression"] =
+            text("VariableDeclarationStatement", "uint256 i = 0");
+        for_loop["condition"] = binop("i < amount", "<");
+        for_loop["loopExpression"] = text("ExpressionStatement", "i++");
+        for_loop["body"] = json!({"nodeType": "Block", "statements": [assign("total = total - 1", binop("total - 1", "-"))]});
+        // An overload of `deposit` that has no points of its own.
+        let mut overload = function("deposit", vec![]);
+        overload["parameters"] = json!({"nodeType": "ParameterList", "parameters": [{"nodeType": "VariableDeclaration", "typeDescriptions": {"typeString": "address"}}]});
+        let ast = SolAST::new(
+            json!({
+                "nodeType": "ContractDefinition",
+                "contractKind": "contract",
+                "name": "Vault",
+                "nodes": [
+                    function("version", vec![text("Return", "return 1")]),
+                    function("deposit", vec![
+                        require("require(amount > 0)", binop("amount > 0", ">")),
+                        assign("total = total + amount", binop("total + amount", "+")),
+                    ]),
+                    overload,
+                    function("withdraw", vec![
+                        require("require(amount <= total)", binop("amount <= total", "<=")),
+                        branch,
+                        for_loop,
+                    ]),
+                ],
+            }),
+            None,
+        );
+        let out = std::env::temp_dir().join("gambit_test_function_report");
+        let _ = std::fs::remove_dir_all(&out);
+        std::fs::create_dir_all(out.join("benchmarks/FunctionReport")).unwrap();
+        let run = runner(fnm, ast, &out, 3, vec![MutationType::BinaryOpMutation]);
+        let (mutants, _, _) = run.get_mutations(|_| Ok(true)).unwrap();
+        assert!(mutants.iter().all(|m| m.function.is_some()));
+        let report = run.function_report(&mutants).unwrap();
+        assert_eq!(
+            FunctionReport::table(&report),
+            "function        statements  branches  points  mutants  mutations\n\
+             Vault.version            1         0       0        0\n\
+             Vault.deposit            2         1       2        2  BinaryOpMutation\n\
+             Vault.deposit            0         0       0        0\n\
+             Vault.withdraw           7         3       4        1  BinaryOpMutation\n"
+        );
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn test_mutability_scope_partitions_points() {
+        use serde_json::json;
+        let mut next = 0;
+        let mut binop = || {
+            next += 10;
+            json!({"nodeType": "BinaryOperation", "operator": "+", "src": format!("{}:5:0", next)})
+        };
+        let function = |name: &str, mutability: &str, op| {
+            json!({
+                "nodeType": "FunctionDefinition",
+                "name": name,
+                "stateMutability": mutability,
+                "body": {"nodeType": "Block", "statements": [op]},
+            })
+        };
+        let ast = SolAST::new(
+            json!({
+                "nodeType": "SourceUnit",
+                "nodes": [
+                    function("freeFee", "pure", binop()),
+                    {
+                        "nodeType": "ContractDefinition",
+                        "contractKind": "contract",
+                        "name": "C",
+                        "nodes": [
+                            {"nodeType": "VariableDeclaration", "name": "cap", "value": binop()},
+                            function("deposit", "nonpayable", binop()),
+                            function("pay", "payable", binop()),
+                            function("balance", "view", binop()),
+                            function("square", "pure", binop()),
+                            {
+                                "nodeType": "ModifierDefinition",
+                                "name": "onlyOwner",
+                                "body": {"nodeType": "Block", "statements": [binop()]},
+                            },
+                        ],
+                    },
+                ],
+            }),
+            None,
+        );
+        let functions = |scope| {
+            let mut excluded = 0;
+            let mut suppressor = Suppressor::default();
+            let (visitor, skip, accept) = RunMutations::mk_closures(
+                "C.sol".into(),
+                vec![MutationType::BinaryOpMutation],
+                None,
+                None,
+                &mut suppressor,
+                scope,
+                &mut excluded,
+            );
+            let names: HashSet<Option<String>> = ast
+                .clone()
+                .traverse(visitor, skip, accept)
+                .into_iter()
+                .flatten()
+                .map(|(_, node)| node.get_function())
+                .collect();
+            (names, excluded)
+        };
+        let name = |n: &str| Some(n.to_string());
+        let (all, excluded) = functions(MutabilityScope::All);
+        assert_eq!(all.len(), 7);
+        assert_eq!(excluded, 0);
+        let (state_changing, excluded) = functions(MutabilityScope::StateChanging);
+        assert_eq!(
+            state_changing,
+            HashSet::from([None, name("deposit"), name("pay"), name("onlyOwner")])
+        );
+        assert_eq!(excluded, 3);
+        let (read_only, excluded) = functions(MutabilityScope::ReadOnly);
+        assert_eq!(
+            read_only,
+            HashSet::from([name("freeFee"), name("balance"), name("square")])
+        );
+        assert_eq!(excluded, 4);
+        assert!(state_changing.is_disjoint(&read_only));
+        assert_eq!(&state_changing | &read_only, all);
+    }
+
+    #[test]
+    fn test_on_mutant_hook() {
+        let out = std::env::temp_dir().join("gambit_test_on_mutant_hook");
+        let mut hook = MutantHook {
+            command: "test {op} != SwapLinesMutation".into(),
+            timeout: Duration::from_secs(10),
+            required: false,
+        };
+        let types = vec![
+            MutationType::BinaryOpMutation,
+            MutationType::SwapLinesMutation,
+        ];
+        // The two binary operations, and the only block of two statements.
+        let (mutants, _, _) = run_c(&out, types.clone(), Some(hook.clone()));
+        assert_eq!(mutants.len(), 3);
+        for m in &mutants {
+            let expected = if m.mutation == "SwapLinesMutation" {
+                HookStatus::Exited(1)
+            } else {
+                HookStatus::Exited(0)
+            };
+            assert_eq!(m.hook_status, Some(expected));
+        }
+        assert!(mutants.iter().any(|m| m.mutation == "SwapLinesMutation"));
+
+        // With a required hook, the rejected mutants are deleted and their
+        // slots retried, which keeps failing until the attempts run out.
+        hook.required = true;
+        let (mutants, stats, _) = run_c(&out, types, Some(hook));
+        assert_eq!(mutants.len(), 2);
+        assert!(mutants.iter().all(|m| m.mutation == "BinaryOpMutation"));
+        let swap = stats[&MutationType::SwapLinesMutation];
+        assert!(swap.hook_rejected > 0);
+        assert_eq!(swap.accepted, 0);
+        let written: Vec<_> = std::fs::read_dir(out.join("benchmarks/MultipleContracts"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(written.len(), 2);
+        for m in &mutants {
+            assert!(out.join(&m.path).is_file());
+        }
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn test_zero_contribution_report() {
+        let stats = FileStats::from([
+            (
+                MutationType::BinaryOpMutation,
+                MutationStats {
+                    points: 4,
+                    attempts: 3,
+                    accepted: 2,
+                    ..Default::default()
+                },
+            ),
+            (
+                MutationType::RequireMutation,
+                MutationStats {
+                    points: 2,
+                    attempts: 0,
+                    accepted: 0,
+                    ..Default::default()
+                },
+            ),
+            (
+                MutationType::SwapLinesMutation,
+                MutationStats {
+                    points: 1,
+                    attempts: 5,
+                    accepted: 0,
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let requested = [
+            MutationType::BinaryOpMutation,
+            MutationType::RequireMutation,
+            MutationType::SwapLinesMutation,
+            MutationType::ExponentMutation,
+        ];
+        assert_eq!(
+            zero_contribution_report("A.sol", &requested, &stats),
+            vec![
+                "A.sol: RequireMutation produced no mutants: 2 mutation points, but lost out to the budget of mutants",
+                "A.sol: SwapLinesMutation produced no mutants: none of its 5 candidates were new and compiled",
+                "A.sol: ExponentMutation produced no mutants: no mutation points",
+            ]
+        );
+    }
+
+    /// A deliberately broken mutation that, besides swapping the operands
+    /// of a binary operation, rewrites the license header.
+    struct HeaderRewriter;
+
+    impl Mutation for HeaderRewriter {
+        fn is_mutation_point(&self, node: &SolAST) -> bool {
+            node.node_type().as_deref() == Some("BinaryOperation")
+        }
+
+        fn mutate_randomly(&self, node: &SolAST, source: &[u8], _: &mut rand_pcg::Pcg64) -> String {
+            let swapped = MutationType::SwapArgumentsOperatorMutation.mutate_randomly(
+                node,
+                source,
+                &mut RunMutations::attempt_rng(0, 0),
+            );
+            swapped.replacen("GPL-3.0-only", "UNLICENSED", 1)
+        }
+
+        fn mutation_count(&self, _: &SolAST, _: &[u8]) -> Option<usize> {
+            Some(1)
+        }
+    }
+
+    #[test]
+    fn test_check_span() {
+        let source = "// SPDX-License-Identifier: GPL-3.0-only\ncontract C { function f() { x = a - b; } }\n";
+        let node = SolAST::new(
+            serde_json::json!({
+                "nodeType": "BinaryOperation",
+                "operator": "-",
+                "src": format!("{}:5:0", source.find("a - b").unwrap()),
+                "leftExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", source.find("a - b").unwrap())},
+                "rightExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", source.find("a - b").unwrap() + 4)},
+            }),
+            None,
+        );
+        let mut rand = RunMutations::attempt_rng(0, 0);
+        let good = MutationType::SwapArgumentsOperatorMutation.mutate_randomly(
+            &node,
+            source.as_bytes(),
+            &mut rand,
+        );
+        assert!(good.contains("x = b - a;"));
+        assert!(RunMutations::check_span(source.as_bytes(), &good, node.get_bounds()).is_ok());
+
+        assert!(HeaderRewriter.is_mutation_point(&node));
+        let bad = HeaderRewriter.mutate_randomly(&node, source.as_bytes(), &mut rand);
+        let err = RunMutations::check_span(source.as_bytes(), &bad, node.get_bounds()).unwrap_err();
+        assert!(err.starts_with("changed bytes 28.."), "{}", err);
+        // Identical mutants are trivially in span.
+        assert!(RunMutations::check_span(source.as_bytes(), source, (0, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_only_attempt_reproduces_mutants() {
+        let root = std::env::temp_dir().join("gambit_test_only_attempt");
+        let (out, replay) = (root.join("out"), root.join("replay"));
+        // Mutations with few candidates per point, which are soon used up.
+        let mutations = vec![
+            MutationType::DeleteExpressionMutation,
+            MutationType::BinaryOpMutation,
+            MutationType::IfStatementMutation,
+        ];
+        // Reject some candidates, so that points and mutations have a history.
+        let is_valid = |mutant: &str| Ok(!mutant.len().is_multiple_of(3));
+        let fnm = "benchmarks/MultipleContracts/C.sol";
+        let ast = || {
+            let f = File::open("benchmarks/MultipleContracts/C.json").unwrap();
+            SolAST::new(serde_json::from_reader(f).unwrap(), None)
+        };
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(out.join("benchmarks/MultipleContracts")).unwrap();
+        let (mutants, _, _) = runner(fnm, ast(), &out, 20, mutations)
+            .get_mutations(is_valid)
+            .unwrap();
+        assert!(mutants.len() > 10);
+        for m in &mutants {
+            let args: Vec<&str> = m.repro_command.split_whitespace().collect();
+            let arg = |flag: &str| {
+                let i = args.iter().position(|a| *a == flag).unwrap();
+                args[i + 1]
+            };
+            let attempt = arg("--only-attempt").parse().unwrap();
+            let point = arg("--only-point").parse().unwrap();
+            let mutation = MutationType::from_name(&m.mutation).unwrap();
+            let _ = std::fs::remove_dir_all(&replay);
+            std::fs::create_dir_all(replay.join("benchmarks/MultipleContracts")).unwrap();
+            let (replayed, _, _) = RunMutations {
+                only_attempt: Some(attempt),
+                only_point: Some(point),
+                ..runner(fnm, ast(), &replay, 20, vec![mutation])
+            }
+            .get_mutations(is_valid)
+            .unwrap();
+            assert_eq!(replayed.len(), 1, "{}", m.repro_command);
+            assert_eq!(
+                std::fs::read_to_string(replay.join(&replayed[0].path)).unwrap(),
+                std::fs::read_to_string(out.join(&m.path)).unwrap(),
+                "{}",
+                m.repro_command
+            );
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_mutation_count_never_underestimates() {
+        // Points are retired once they yielded as many mutants as counted.
+        for name in [
+            "10Power/TenPower",
+            "AssignmentMutation/AssignmentExample",
+            "ElimDelegateMutation/ElimDelegateExample",
+            "FunctionCallMutation/FunctionCallExample",
+            "IfStatementMutation/IfStatementExample",
+            "MultipleContracts/C",
+            "RequireMutation/RequireExample",
+            "SwapArgumentsOperatorMutation/SwapArgumentsOperatorExample",
+            "SwapLinesMutation/SwapLinesExample",
+            "UnaryOperatorMutation/UnaryOperatorExample",
+        ] {
+            let fnm = format!("benchmarks/{}.sol", name);
+            let f = File::open(format!("benchmarks/{}.json", name)).unwrap();
+            let node = SolAST::new(serde_json::from_reader(f).unwrap(), None);
+            let run = runner(
+                &fnm,
+                node,
+                Path::new("out"),
+                1,
+                MutationType::value_variants().to_vec(),
+            );
+            let source = run.read_source().unwrap();
+            for (mt, point) in run.find_mutation_points(&source) {
+                // Points counted as having no mutants are never drawn.
+                let Some(count @ 1..) = mt.mutation_count(&point, &source) else {
+                    continue;
+                };
+                let distinct: HashSet<String> = (0..50)
+                    .map(|attempt| {
+                        let mut rand = RunMutations::attempt_rng(0, attempt);
+                        mt.mutate_randomly(&point, &source, &mut rand)
+                    })
+                    .filter(|m| m.as_bytes() != source)
+                    .collect();
+                assert!(
+                    distinct.len() <= count,
+                    "{} at {:?} of {}: {} mutants but counted {}",
+                    mt,
+                    point.get_bounds(),
+                    fnm,
+                    distinct.len(),
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_points_know_the_solc_version() {
+        let fnm = "benchmarks/MultipleContracts/C.sol";
+        let f = File::open("benchmarks/MultipleContracts/C.json").unwrap();
+        let node = SolAST::new(serde_json::from_reader(f).unwrap(), None);
+        let run = RunMutations {
+            solc_version: Some((0, 8, 13)),
+            ..runner(
+                fnm,
+                node,
+                Path::new("out"),
+                1,
+                MutationType::value_variants().to_vec(),
             )
-        } else {
-            log::info!("Did not find any mutations");
-            Ok(vec![])
+        };
+        let source = run.read_source().unwrap();
+        let points = run.find_mutation_points(&source);
+        assert!(!points.is_empty());
+        assert!(points
+            .iter()
+            .all(|(_, p)| p.get_solc_version() == Some((0, 8, 13))));
+    }
+
+    #[test]
+    fn test_attempt_rng() {
+        use rand::RngCore;
+        let draw = |seed, attempt| RunMutations::attempt_rng(seed, attempt).next_u64();
+        assert_eq!(draw(0, 3), draw(0, 3));
+        assert_ne!(draw(0, 3), draw(0, 4));
+        assert_ne!(draw(0, 3), draw(1, 3));
+    }
+
+    #[test]
+    fn test_add_mutant_comment() {
+        let source = "contract C {\n    function f() {\n        x = a + b;\n    }\n}\n";
+        let mutant = source.replace("a + b", "a - b");
+        let res = RunMutations::add_mutant_comment(
+            source.as_bytes(),
+            &mutant,
+            &MutationType::BinaryOpMutation,
+            200,
+            None,
+        );
+        assert_eq!(
+            res,
+            "contract C {\n    function f() {\n        /// BinaryOpMutation of: x = a + b;\n        x = a - b;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_mutant_comment() {
+        let source = "contract C {\n    function f() {\n        x = a + b;\n    }\n}\n";
+        let mutant = source.replace("a + b", "a - b");
+        let mt = MutationType::BinaryOpMutation;
+        let commented =
+            RunMutations::add_mutant_comment(source.as_bytes(), &mutant, &mt, 200, None);
+        assert_eq!(
+            RunMutations::strip_mutant_comment(source.as_bytes(), &commented, &mt),
+            Some(mutant.clone())
+        );
+        assert_eq!(
+            RunMutations::strip_mutant_comment(source.as_bytes(), &mutant, &mt),
+            None
+        );
+        assert_eq!(
+            RunMutations::strip_mutant_comment(
+                source.as_bytes(),
+                &commented,
+                &MutationType::AssignmentMutation
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_add_mutant_comment_after_bom() {
+        let source = "\u{feff}x = a + b;\ny = c;\n";
+        let mutant = source.replace("a + b", "a - b");
+        let res = RunMutations::add_mutant_comment(
+            source.as_bytes(),
+            &mutant,
+            &MutationType::BinaryOpMutation,
+            200,
+            Some((3, source.find(';').unwrap() + 1)),
+        );
+        assert_eq!(
+            res,
+            "\u{feff}/// BinaryOpMutation of: x = a + b;\nx = a - b;\ny = c;\n"
+        );
+    }
+
+    #[test]
+    fn test_encoding_preserved() {
+        for (fixture, line) in [
+            ("benchmarks/Encoding/BomExample.sol", "return a + b;"),
+            (
+                "benchmarks/Encoding/CyrillicComments.sol",
+                "return a + b; // сумма",
+            ),
+        ] {
+            let source = std::fs::read(fixture).unwrap();
+            let has_bom = source.starts_with(UTF8_BOM);
+            assert_eq!(has_bom, fixture.ends_with("BomExample.sol"));
+            // The offsets in the AST count the bytes of the file, including
+            // the byte order mark and multi-byte characters before them.
+            let text = String::from_utf8(source.clone()).unwrap();
+            let at = text.find("a + b").unwrap();
+            assert_eq!(&source[at..at + 5], b"a + b");
+            let node = SolAST::new(
+                serde_json::json!({
+                    "nodeType": "BinaryOperation",
+                    "operator": "+",
+                    "src": format!("{}:5:0", at),
+                    "leftExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at)},
+                    "rightExpression": {"nodeType": "Identifier", "src": format!("{}:1:0", at + 4)},
+                }),
+                None,
+            );
+            let out = std::env::temp_dir().join("gambit_test_encoding");
+            let _ = std::fs::remove_dir_all(&out);
+            std::fs::create_dir_all(out.join("benchmarks/Encoding")).unwrap();
+            let (mutants, _, _) =
+                runner(fixture, node, &out, 3, vec![MutationType::BinaryOpMutation])
+                    .get_mutations(|_| Ok(true))
+                    .unwrap();
+            assert_eq!(mutants.len(), 3);
+            for m in &mutants {
+                let mutant = String::from_utf8(std::fs::read(out.join(&m.path)).unwrap()).unwrap();
+                assert_eq!(mutant.starts_with('\u{feff}'), has_bom);
+                assert!(!mutant[has_bom as usize * 3..].contains('\u{feff}'));
+                // Only the mutated line changes, besides the comment above it.
+                let changed: Vec<&str> = mutant
+                    .lines()
+                    .filter(|l| !text.lines().any(|o| o == *l))
+                    .collect();
+                assert_eq!(changed.len(), 2, "{}", mutant);
+                assert_eq!(
+                    changed[0].trim(),
+                    format!("/// BinaryOpMutation of: {}", line)
+                );
+                assert!(!changed[1].contains("a + b"));
+            }
+            std::fs::remove_dir_all(&out).unwrap();
         }
     }
+
+    #[test]
+    fn test_add_mutant_comment_multi_line_statement() {
+        let source = "contract C {\n    function f() {\n        require(\n            a &&\n            b,\n            \"msg\");\n    }\n}\n";
+        let mutant = source.replace("            b,", "            !b,");
+        let stmt_start = source.find("require").unwrap();
+        let stmt_end = source.find(";\n    }").unwrap() + 1;
+        let res = RunMutations::add_mutant_comment(
+            source.as_bytes(),
+            &mutant,
+            &MutationType::UnaryOperatorMutation,
+            200,
+            Some((stmt_start, stmt_end)),
+        );
+        assert_eq!(
+            res,
+            "contract C {\n    function f() {\n        /// UnaryOperatorMutation of: b,\n        require(\n            a &&\n            !b,\n            \"msg\");\n    }\n}\n"
+        );
+    }
+
+    /// Checks that the comment added to `mutant` is indented exactly like
+    /// the line following it, and returns that indentation.
+    fn comment_indent(source: &str, mutant: &str, mut_type: MutationType) -> String {
+        let res = RunMutations::add_mutant_comment(source.as_bytes(), mutant, &mut_type, 200, None);
+        let lines: Vec<&str> = res.lines().collect();
+        let i = lines
+            .iter()
+            .position(|l| l.trim_start().starts_with("/// "))
+            .expect("no comment added");
+        assert_eq!(get_indent(lines[i]), get_indent(lines[i + 1]));
+        get_indent(lines[i])
+    }
+
+    #[test]
+    fn test_add_mutant_comment_indentation() {
+        // Tab indented.
+        let source = "contract C {\n\tfunction f() {\n\t\tx = a + b;\n\t}\n}\n";
+        let mutant = source.replace("a + b", "a - b");
+        let indent = comment_indent(source, &mutant, MutationType::BinaryOpMutation);
+        assert_eq!(indent, "\t\t");
+        // Space indented, with the change in the middle of the line.
+        let source = "contract C {\n    function f() {\n        x = a + b; y = c;\n    }\n}\n";
+        let mutant = source.replace("y = c;", "/*y = c;*/");
+        let indent = comment_indent(source, &mutant, MutationType::DeleteExpressionMutation);
+        assert_eq!(indent, "        ");
+        // Mixed indentation, where swapping lines changes the indentation
+        // of the first line that differs.
+        let source = "contract C {\n  function f() {\n\tx = 1;\n    y = 2;\n  }\n}\n";
+        let mutant = "contract C {\n  function f() {\n    y = 2;\n\tx = 1;\n  }\n}\n";
+        let indent = comment_indent(source, mutant, MutationType::SwapLinesMutation);
+        assert_eq!(indent, "    ");
+        // Unicode whitespace is kept, the line ending is not.
+        let source = "contract C {\r\n\u{3000}x = a + b;\r\n}\r\n";
+        let mutant = source.replace("a + b", "a - b");
+        let res = RunMutations::add_mutant_comment(
+            source.as_bytes(),
+            &mutant,
+            &MutationType::BinaryOpMutation,
+            200,
+            None,
+        );
+        assert_eq!(
+            res,
+            "contract C {\r\n\u{3000}/// BinaryOpMutation of: x = a + b;\r\n\u{3000}x = a - b;\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_mutate_long_line() {
+        // A 100KB contract on a single line, as minifiers produce.
+        let body = "uint256 x = 1; ".repeat(3500);
+        let source = format!("contract C {{ {} x = a + b; {}}}\n", body, body);
+        assert!(source.len() > 100_000);
+        let expr = source.find("a + b").unwrap();
+        let node = SolAST::new(
+            serde_json::json!({
+                "nodeType": "BinaryOperation",
+                "operator": "+",
+                "src": format!("{}:5:0", expr),
+                "leftExpression": {"nodeType": "Identifier", "name": "a", "src": format!("{}:1:0", expr)},
+                "rightExpression": {"nodeType": "Identifier", "name": "b", "src": format!("{}:1:0", expr + 4)},
+            }),
+            None,
+        );
+        let mut_type = MutationType::BinaryOpMutation;
+        let mut rand = rand_pcg::Pcg64::seed_from_u64(0);
+        let mutant = mut_type.mutate_randomly(&node, source.as_bytes(), &mut rand);
+        assert_eq!(mutant.len(), source.len());
+        let stmt = source.find("x = a + b;").unwrap();
+        let res = RunMutations::add_mutant_comment(
+            source.as_bytes(),
+            &mutant,
+            &mut_type,
+            200,
+            Some((stmt, stmt + "x = a + b;".len())),
+        );
+        let comment = res.lines().next().unwrap();
+        assert!(comment.starts_with("/// BinaryOpMutation of: contract C {"));
+        assert!(comment.ends_with("..."));
+        assert!(comment.len() < 300);
+        assert_eq!(res.len(), mutant.len() + comment.len() + 1);
+
+        let diff = render_diff(source.as_bytes(), mutant.as_bytes(), DIFF_MAX_LINE_LEN);
+        let lines: Vec<&str> = diff.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with(&format!("@@ -1,1 +1,1 @@ columns {}", expr + 2)));
+        assert!(lines.iter().all(|l| l.len() < DIFF_MAX_LINE_LEN + 50));
+        assert!(lines[1].contains("a + b"));
+        assert!(!lines[2].contains("a + b"));
+    }
 }
@@ -0,0 +1,106 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{changed_range, sha256_hex, write_atomically, MutantEntry};
+
+/// Name of the marker file written to output directories whose mutants
+/// were emitted with `--emit sparse`.
+pub static SPARSE_MARKER: &str = ".gambit-sparse";
+
+/// How mutants are written to the output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+pub enum Emit {
+    /// Write every mutant as a full copy of the file.
+    #[default]
+    Full,
+    /// Only record the edit of every mutant in the manifest, from which
+    /// `gambit apply` writes the mutant when it is needed.
+    Sparse,
+}
+
+/// The edit that turns the original file into a mutant.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SparseEdit {
+    /// Start of the replaced bytes of the original file.
+    pub start: usize,
+    /// End of the replaced bytes of the original file.
+    pub end: usize,
+    /// The bytes they are replaced with.
+    pub replacement: String,
+    /// SHA-256 of the original file the edit applies to.
+    pub original_sha256: String,
+}
+
+impl SparseEdit {
+    /// The edit turning `original` into `mutant`.
+    pub fn new(original: &[u8], mutant: &str) -> Self {
+        let (start, end, mutant_end) =
+            changed_range(original, mutant.as_bytes()).unwrap_or((0, 0, 0));
+        SparseEdit {
+            start,
+            end,
+            replacement: mutant[start..mutant_end].to_string(),
+            original_sha256: sha256_hex(original),
+        }
+    }
+
+    /// Apply the edit to `original`, checking first that it is the file
+    /// the edit was made for.
+    pub fn apply(&self, original: &[u8]) -> io::Result<Vec<u8>> {
+        let actual = sha256_hex(original);
+        if actual != self.original_sha256 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "original file has changed: expected SHA-256 {} but found {}",
+                    self.original_sha256, actual
+                ),
+            ));
+        }
+        Ok([
+            &original[..self.start],
+            self.replacement.as_bytes(),
+            &original[self.end..],
+        ]
+        .concat())
+    }
+}
+
+/// Write the mutant of `entry` to its path in `outdir`, from the edit
+/// recorded for it. Mutants that were written in full are left alone.
+pub fn materialize(outdir: &Path, entry: &MutantEntry) -> io::Result<()> {
+    let Some(edit) = &entry.edit else {
+        return Ok(());
+    };
+    let original = fs::read(&entry.original)?;
+    let mutant = edit
+        .apply(&original)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", entry.original, e)))?;
+    let path = outdir.join(&entry.path);
+    fs::create_dir_all(path.parent().unwrap())?;
+    write_atomically(&path, &mutant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_edit() {
+        let original = b"uint x = a + b;\nuint y = 1;\n";
+        let mutant = "uint x = a + b;\n/// BinaryOpMutation of: uint y = 1;\nuint y = 2;\n";
+        let edit = SparseEdit::new(original, mutant);
+        assert_eq!(edit.start, 16);
+        assert_eq!(edit.apply(original).unwrap(), mutant.as_bytes());
+        let changed = b"uint x = a - b;\nuint y = 1;\n";
+        assert_eq!(
+            edit.apply(changed).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
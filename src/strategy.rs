@@ -0,0 +1,305 @@
+use clap::ValueEnum;
+use rand::{seq::SliceRandom, Rng};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{MutationType, SolAST};
+
+/// Decides which mutation to try next while generating the mutants of a
+/// file, and at which of its points.
+///
+/// Every attempt gets its own random number generator, seeded from the
+/// seed of the run and the number of the attempt, which strategies must
+/// draw all their randomness from: given the same seed and the same
+/// history, a strategy must make the same choices.
+/// The `repro_command` of a mutant records the point that was chosen, but
+/// `--only-attempt` replays the choice of mutation type without any
+/// history, so the mutants of a strategy whose choices depend on its
+/// history, such as `EpsilonGreedyStrategy`, cannot be reproduced that way
+/// and get no `repro_command`, see `is_reproducible`.
+/// The history of a strategy starts over when a run is resumed.
+pub trait SamplingStrategy {
+    /// Pick the next mutation type to try, as an index into `todo`, which
+    /// holds a mutation type for every mutant still to be generated, along
+    /// with those that were rejected, in the order in which they are due.
+    /// `points` are the mutation points of every mutation type of the file.
+    fn select(
+        &mut self,
+        todo: &VecDeque<MutationType>,
+        points: &HashMap<MutationType, Vec<SolAST>>,
+        rand: &mut Pcg64,
+    ) -> usize;
+
+    /// Pick which point of `mutation` to mutate next, as one of `points`,
+    /// the indices of its mutation points that are left to choose from,
+    /// of which there is at least one.
+    fn select_point(
+        &mut self,
+        _mutation: MutationType,
+        points: &[usize],
+        rand: &mut Pcg64,
+    ) -> usize {
+        *points.choose(rand).unwrap()
+    }
+
+    /// A candidate of `mutation` was `accepted` as a mutant or rejected,
+    /// for not compiling or being a duplicate, among other reasons.
+    fn feedback(&mut self, _mutation: MutationType, _accepted: bool) {}
+
+    /// Whether the choices of the strategy are independent of its history,
+    /// so that `--only-attempt` reproduces the mutants it picks.
+    fn is_reproducible(&self) -> bool {
+        true
+    }
+}
+
+/// Tries the mutation types in turn, each at a random point.
+#[derive(Debug, Default)]
+pub struct RoundRobinStrategy;
+
+impl SamplingStrategy for RoundRobinStrategy {
+    fn select(
+        &mut self,
+        _todo: &VecDeque<MutationType>,
+        _points: &HashMap<MutationType, Vec<SolAST>>,
+        _rand: &mut Pcg64,
+    ) -> usize {
+        0
+    }
+}
+
+/// Picks the next mutation type at random, each with a probability in
+/// proportion to its weight times how often it is due.
+#[derive(Debug, Default)]
+pub struct WeightedStrategy {
+    /// The weight of each mutation type. Without weights, mutation types
+    /// are weighted by their number of points.
+    pub weights: Option<HashMap<MutationType, f64>>,
+}
+
+impl SamplingStrategy for WeightedStrategy {
+    fn select(
+        &mut self,
+        todo: &VecDeque<MutationType>,
+        points: &HashMap<MutationType, Vec<SolAST>>,
+        rand: &mut Pcg64,
+    ) -> usize {
+        let weight = |m: &MutationType| match &self.weights {
+            Some(weights) => weights.get(m).copied().unwrap_or(0.0),
+            None => points.get(m).map_or(0, |ps| ps.len()) as f64,
+        };
+        let total: f64 = todo.iter().map(weight).sum();
+        if total <= 0.0 {
+            return 0;
+        }
+        let mut pick = rand.gen::<f64>() * total;
+        for (i, m) in todo.iter().enumerate() {
+            pick -= weight(m);
+            if pick < 0.0 {
+                return i;
+            }
+        }
+        todo.len() - 1
+    }
+}
+
+/// A bandit that mostly picks the mutation type whose candidates were
+/// accepted most often so far, and with probability `epsilon` one at random.
+#[derive(Debug)]
+pub struct EpsilonGreedyStrategy {
+    pub epsilon: f64,
+    /// The number of accepted and of all candidates of each mutation type.
+    history: HashMap<MutationType, (usize, usize)>,
+}
+
+impl EpsilonGreedyStrategy {
+    pub fn new(epsilon: f64) -> Self {
+        EpsilonGreedyStrategy {
+            epsilon,
+            history: HashMap::new(),
+        }
+    }
+
+    /// The estimated chance that a candidate of `mutation` is accepted,
+    /// starting at one half.
+    fn estimate(&self, mutation: &MutationType) -> f64 {
+        let (accepted, tried) = self.history.get(mutation).copied().unwrap_or_default();
+        (accepted as f64 + 1.0) / (tried as f64 + 2.0)
+    }
+}
+
+impl Default for EpsilonGreedyStrategy {
+    fn default() -> Self {
+        EpsilonGreedyStrategy::new(0.1)
+    }
+}
+
+impl SamplingStrategy for EpsilonGreedyStrategy {
+    fn select(
+        &mut self,
+        todo: &VecDeque<MutationType>,
+        _points: &HashMap<MutationType, Vec<SolAST>>,
+        rand: &mut Pcg64,
+    ) -> usize {
+        if rand.gen::<f64>() < self.epsilon {
+            return rand.gen_range(0, todo.len());
+        }
+        // The first of the best, so that ties go in turn.
+        let mut best = 0;
+        for (i, m) in todo.iter().enumerate() {
+            if self.estimate(m) > self.estimate(&todo[best]) {
+                best = i;
+            }
+        }
+        best
+    }
+
+    fn feedback(&mut self, mutation: MutationType, accepted: bool) {
+        let (a, t) = self.history.entry(mutation).or_default();
+        *a += accepted as usize;
+        *t += 1;
+    }
+
+    fn is_reproducible(&self) -> bool {
+        false
+    }
+}
+
+/// The built-in strategies, selected with `--strategy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+pub enum Strategy {
+    /// Try the mutation types in turn.
+    #[default]
+    RoundRobin,
+    /// Try mutation types with more points more often.
+    Weighted,
+    /// Try the mutation types whose candidates compile most often,
+    /// exploring the others a tenth of the time.
+    EpsilonGreedy,
+}
+
+impl Strategy {
+    pub fn build(&self) -> Box<dyn SamplingStrategy> {
+        match self {
+            Strategy::RoundRobin => Box::new(RoundRobinStrategy),
+            Strategy::Weighted => Box::<WeightedStrategy>::default(),
+            Strategy::EpsilonGreedy => Box::<EpsilonGreedyStrategy>::default(),
+        }
+    }
+}
+
+/// A handle to the strategy of a run, shared by all of its files.
+#[derive(Clone)]
+pub struct Sampler(Rc<RefCell<Box<dyn SamplingStrategy>>>);
+
+impl Sampler {
+    pub fn new(strategy: Box<dyn SamplingStrategy>) -> Self {
+        Sampler(Rc::new(RefCell::new(strategy)))
+    }
+
+    /// Call `f` on the strategy.
+    pub fn with<T>(&self, f: impl FnOnce(&mut dyn SamplingStrategy) -> T) -> T {
+        f(self.0.borrow_mut().as_mut())
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler::new(Box::new(RoundRobinStrategy))
+    }
+}
+
+impl fmt::Debug for Sampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sampler")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const A: MutationType = MutationType::BinaryOpMutation;
+    const B: MutationType = MutationType::RequireMutation;
+
+    /// How often `strategy` picks each entry of `todo` over `n` attempts,
+    /// where `A` has three points and `B` one.
+    fn picks(strategy: &mut dyn SamplingStrategy, todo: &[MutationType], n: u64) -> Vec<usize> {
+        let points = HashMap::from([
+            (A, vec![SolAST::default(); 3]),
+            (B, vec![SolAST::default()]),
+        ]);
+        let todo: VecDeque<MutationType> = todo.iter().copied().collect();
+        let mut counts = vec![0; todo.len()];
+        for seed in 0..n {
+            let mut rand = Pcg64::seed_from_u64(seed);
+            counts[strategy.select(&todo, &points, &mut rand)] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_round_robin_strategy() {
+        assert_eq!(picks(&mut RoundRobinStrategy, &[B, A, B], 100), [100, 0, 0]);
+        let mut rand = Pcg64::seed_from_u64(0);
+        let live = [4, 7, 9];
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            let point = RoundRobinStrategy.select_point(A, &live, &mut rand);
+            counts[live.iter().position(|p| *p == point).unwrap()] += 1;
+        }
+        assert!(
+            counts.iter().all(|c| (900..1100).contains(c)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn test_weighted_strategy() {
+        // By number of points, `A` is picked three times as often as `B`.
+        let counts = picks(&mut WeightedStrategy::default(), &[A, B], 4000);
+        assert!((2900..3100).contains(&counts[0]), "{:?}", counts);
+        // An entry that is due twice is picked twice as often.
+        let counts = picks(&mut WeightedStrategy::default(), &[B, A, B], 5000);
+        assert!((900..1100).contains(&counts[0]), "{:?}", counts);
+        assert!((900..1100).contains(&counts[2]), "{:?}", counts);
+
+        let mut weighted = WeightedStrategy {
+            weights: Some(HashMap::from([(B, 1.0)])),
+        };
+        assert_eq!(picks(&mut weighted, &[A, B, A], 100), [0, 100, 0]);
+        assert_eq!(
+            picks(&mut WeightedStrategy::default(), &[A, B], 10),
+            picks(&mut WeightedStrategy::default(), &[A, B], 10)
+        );
+    }
+
+    #[test]
+    fn test_epsilon_greedy_strategy() {
+        let mut greedy = EpsilonGreedyStrategy::new(0.2);
+        // Without any history, ties go to the first entry but for exploring.
+        let counts = picks(&mut greedy, &[A, B], 4000);
+        assert!((3500..3700).contains(&counts[0]), "{:?}", counts);
+
+        for i in 0..10 {
+            greedy.feedback(A, false);
+            greedy.feedback(B, i != 0);
+        }
+        let counts = picks(&mut greedy, &[A, B], 4000);
+        assert!((3500..3700).contains(&counts[1]), "{:?}", counts);
+
+        let mut never = EpsilonGreedyStrategy::new(0.0);
+        never.feedback(B, true);
+        assert_eq!(picks(&mut never, &[A, B, A], 100), [0, 100, 0]);
+        assert!(!never.is_reproducible());
+        assert!(RoundRobinStrategy.is_reproducible());
+    }
+}
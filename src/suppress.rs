@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{MutationType, SolAST};
+
+/// A rule from the `suppress` section of a config file, e.g.,
+/// `{"mutation": "DeleteExpressionMutation", "node_type": "EmitStatement"}`.
+/// A mutation point is suppressed by a rule if it matches every key
+/// the rule specifies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuppressRule {
+    /// Name of the mutation type.
+    pub mutation: Option<String>,
+    /// `nodeType` of the AST node being mutated.
+    pub node_type: Option<String>,
+    /// Name of the function or modifier containing the node.
+    pub within_function: Option<String>,
+    /// Name of the contract containing the node.
+    pub within_contract: Option<String>,
+    /// First and last line (1-based, inclusive) that the node may start on.
+    pub line_range: Option<(usize, usize)>,
+}
+
+impl fmt::Display for SuppressRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).unwrap())
+    }
+}
+
+/// A `SuppressRule` with its mutation type resolved.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    rule: SuppressRule,
+    mutation: Option<MutationType>,
+}
+
+/// Decides which mutation points are suppressed by the rules of a config
+/// file and counts how many points each rule suppressed.
+/// When several rules match a point, it is counted for the first one.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressor {
+    rules: Vec<CompiledRule>,
+    counts: Vec<usize>,
+    line_starts: Vec<usize>,
+}
+
+impl Suppressor {
+    /// Compile `rules`, checking that each of them is well-formed.
+    pub fn new(rules: Vec<SuppressRule>) -> Result<Self, String> {
+        let mut compiled = vec![];
+        for rule in rules {
+            if rule == SuppressRule::default() {
+                return Err("suppress rule must specify at least one key".into());
+            }
+            let mutation = match &rule.mutation {
+                Some(m) => Some(MutationType::parse_name(m).map_err(|e| e + " in suppress rule")?),
+                None => None,
+            };
+            if let Some((first, last)) = rule.line_range {
+                if first == 0 || first > last {
+                    return Err(format!("invalid line_range in suppress rule {}", rule));
+                }
+            }
+            compiled.push(CompiledRule { rule, mutation });
+        }
+        Ok(Suppressor {
+            counts: vec![0; compiled.len()],
+            rules: compiled,
+            line_starts: vec![],
+        })
+    }
+
+    /// Record where the lines of `source` start, which `line_range` rules
+    /// are checked against.
+    pub fn index_lines(&mut self, source: &[u8]) {
+        self.line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| **b == b'\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+    }
+
+    /// The 1-based line on which `node` starts.
+    fn line_of(&self, node: &SolAST) -> usize {
+        let (start, _) = node.get_bounds();
+        self.line_starts.partition_point(|s| *s <= start)
+    }
+
+    fn matches(&self, rule: &CompiledRule, mut_type: &MutationType, node: &SolAST) -> bool {
+        let eq = |expected: &Option<String>, actual: Option<String>| {
+            expected
+                .as_ref()
+                .map_or_else(|| true, |e| actual.map_or_else(|| false, |a| *e == a))
+        };
+        rule.mutation.map_or_else(|| true, |m| m == *mut_type)
+            && eq(&rule.rule.node_type, node.node_type())
+            && eq(&rule.rule.within_function, node.get_function())
+            && eq(&rule.rule.within_contract, node.get_contract())
+            && rule.rule.line_range.map_or_else(
+                || true,
+                |(first, last)| {
+                    node.src().is_some() && (first..=last).contains(&self.line_of(node))
+                },
+            )
+    }
+
+    /// Check whether mutating `node` with `mut_type` is suppressed,
+    /// counting it against the first rule that matches.
+    pub fn suppresses(&mut self, mut_type: &MutationType, node: &SolAST) -> bool {
+        match (0..self.rules.len()).find(|i| self.matches(&self.rules[*i], mut_type, node)) {
+            Some(i) => {
+                self.counts[i] += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The rules this was compiled from.
+    pub fn rules(&self) -> Vec<SuppressRule> {
+        self.rules.iter().map(|r| r.rule.clone()).collect()
+    }
+
+    /// The number of mutation points suppressed by each rule, in order.
+    pub fn counts(&self) -> Vec<(&SuppressRule, usize)> {
+        self.rules
+            .iter()
+            .map(|r| &r.rule)
+            .zip(self.counts.iter().copied())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(v: serde_json::Value) -> Result<Suppressor, String> {
+        let rules: Vec<SuppressRule> = serde_json::from_value(v).map_err(|e| e.to_string())?;
+        Suppressor::new(rules)
+    }
+
+    fn node(node_type: &str, src: &str, contract: &str, function: Option<&str>) -> SolAST {
+        let mut node = SolAST::new(
+            json!({"nodeType": node_type, "src": src}),
+            Some(contract.into()),
+        );
+        node.function = function.map(|f| f.to_string());
+        node
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let s = rules(json!([
+            {"mutation": "DeleteExpressionMutation", "node_type": "EmitStatement"},
+            {"mutation": "swap-lines-mutation", "within_function": "rebalance"},
+            {"within_contract": "C", "line_range": [3, 5]},
+        ]))
+        .unwrap();
+        assert_eq!(s.rules.len(), 3);
+        assert_eq!(
+            s.rules[0].mutation,
+            Some(MutationType::DeleteExpressionMutation)
+        );
+        assert_eq!(s.rules[1].mutation, Some(MutationType::SwapLinesMutation));
+        assert_eq!(s.rules[2].rule.line_range, Some((3, 5)));
+    }
+
+    #[test]
+    fn test_parse_bad_rules() {
+        assert!(rules(json!([{"mutation": "NoSuchMutation"}])).is_err());
+        assert!(rules(json!([{"function": "f"}])).is_err());
+        assert!(rules(json!([{}])).is_err());
+        assert!(rules(json!([{"line_range": [5, 3]}])).is_err());
+    }
+
+    #[test]
+    fn test_matching() {
+        let mut s = rules(json!([
+            {"mutation": "DeleteExpressionMutation", "node_type": "EmitStatement"},
+            {"within_function": "rebalance"},
+            {"line_range": [2, 2]},
+        ]))
+        .unwrap();
+        s.index_lines(b"line 1\nline 2\nline 3\n");
+        let emit = node("EmitStatement", "14:4:0", "C", Some("f"));
+        assert!(s.suppresses(&MutationType::DeleteExpressionMutation, &emit));
+        assert!(!s.suppresses(&MutationType::SwapLinesMutation, &emit));
+        let in_rebalance = node("ExpressionStatement", "14:4:0", "C", Some("rebalance"));
+        assert!(s.suppresses(&MutationType::SwapLinesMutation, &in_rebalance));
+        let on_line_2 = node("ExpressionStatement", "7:4:0", "C", Some("f"));
+        assert!(s.suppresses(&MutationType::SwapLinesMutation, &on_line_2));
+        // Matches both the `within_function` and the `line_range` rule,
+        // but is only counted for the first.
+        let both = node("ExpressionStatement", "7:4:0", "C", Some("rebalance"));
+        assert!(s.suppresses(&MutationType::SwapLinesMutation, &both));
+        let counts: Vec<usize> = s.counts().iter().map(|(_, c)| *c).collect();
+        assert_eq!(counts, vec![1, 2, 1]);
+    }
+}
@@ -1,14 +1,35 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::{
     error::Error,
     path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
 };
 
+/// The Levenshtein distance between `a` and `b`, i.e., the number of
+/// single character insertions, deletions and substitutions needed
+/// to turn one into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            cur.push(sub.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 /// Given a `line`, get the indentation in terms of
-/// a string of white spaces.
+/// a string of white spaces, keeping tabs and other whitespace as is.
 pub fn get_indent(line: &str) -> String {
     let mut res = String::new();
     for c in line.chars() {
-        if c.is_whitespace() {
+        if c.is_whitespace() && c != '\r' && c != '\n' {
             res += &c.to_string();
         } else {
             break;
@@ -17,6 +38,171 @@ pub fn get_indent(line: &str) -> String {
     res
 }
 
+/// Trim `line` and cap it at `max_len` characters, appending an ellipsis
+/// when anything was cut off. Used for excerpts of the original code that
+/// end up in comments and logs.
+pub fn excerpt(line: &str, max_len: usize) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(max_len) {
+        Some((idx, _)) => line[..idx].to_string() + "...",
+        None => line.to_string(),
+    }
+}
+
+/// Returns the byte offset of the start of the line containing `pos`.
+pub fn line_start(source: &[u8], pos: usize) -> usize {
+    source[..pos]
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1)
+}
+
+/// Returns the byte offset of the end (exclusive, without the newline)
+/// of the line containing `pos`.
+pub fn line_end(source: &[u8], pos: usize) -> usize {
+    source[pos..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(source.len(), |i| pos + i)
+}
+
+/// The UTF-8 byte order mark that some editors put at the start of files.
+pub static UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Checks whether `pos` is at the start of a character of the UTF-8
+/// encoded `bytes`, or at their end.
+fn is_char_boundary(bytes: &[u8], pos: usize) -> bool {
+    bytes.get(pos).map_or_else(|| true, |b| b & 0xC0 != 0x80)
+}
+
+/// Returns the range of bytes that differ between `orig` and `mutant` as
+/// `(start, orig_end, mutant_end)`, found by scanning for the common prefix
+/// and suffix. For UTF-8 input, the range is widened to whole characters,
+/// e.g., `Б` and `В` share their first byte but are replaced as a whole.
+/// Returns `None` if the two are identical.
+pub fn changed_range(orig: &[u8], mutant: &[u8]) -> Option<(usize, usize, usize)> {
+    let mut prefix = orig
+        .iter()
+        .zip(mutant.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == orig.len() && prefix == mutant.len() {
+        return None;
+    }
+    while !(is_char_boundary(orig, prefix) && is_char_boundary(mutant, prefix)) {
+        prefix -= 1;
+    }
+    let max_suffix = std::cmp::min(orig.len(), mutant.len()) - prefix;
+    let mut suffix = orig
+        .iter()
+        .rev()
+        .zip(mutant.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !(is_char_boundary(orig, orig.len() - suffix)
+        && is_char_boundary(mutant, mutant.len() - suffix))
+    {
+        suffix -= 1;
+    }
+    Some((prefix, orig.len() - suffix, mutant.len() - suffix))
+}
+
+/// Render a single line of a diff. Lines longer than `max_len` are cut down
+/// to a window of `max_len` bytes starting a little before column `col`,
+/// where the change begins.
+fn render_diff_line(line: &str, col: usize, max_len: usize) -> String {
+    if line.len() <= max_len {
+        return line.to_string();
+    }
+    let mut from = std::cmp::min(col, line.len()).saturating_sub(max_len / 4);
+    let mut to = std::cmp::min(line.len(), from + max_len);
+    while !line.is_char_boundary(from) {
+        from -= 1;
+    }
+    while !line.is_char_boundary(to) {
+        to += 1;
+    }
+    let mut res = String::new();
+    if from > 0 {
+        res += "...";
+    }
+    res += &line[from..to];
+    if to < line.len() {
+        res += "...";
+    }
+    res
+}
+
+/// Renders the difference between `orig` and `mutant` as a unified-style
+/// hunk of the changed lines. The header records the line and column
+/// offsets of the change so that it can still be located when long lines
+/// are truncated to `max_len` characters.
+pub fn render_diff(orig: &[u8], mutant: &[u8], max_len: usize) -> String {
+    let (start, orig_end, mutant_end) = match changed_range(orig, mutant) {
+        Some(r) => r,
+        None => return String::new(),
+    };
+    let first = line_start(orig, start);
+    let line_no = orig[..first].iter().filter(|b| **b == b'\n').count() + 1;
+    let col = start - first;
+    let render = |src: &[u8], end: usize, sign: &str| -> Vec<String> {
+        let last = line_end(src, std::cmp::max(end, start));
+        String::from_utf8_lossy(&src[first..last])
+            .split('\n')
+            .enumerate()
+            .map(|(i, l)| {
+                let c = if i == 0 { col } else { 0 };
+                sign.to_string() + &render_diff_line(l, c, max_len)
+            })
+            .collect()
+    };
+    let removed = render(orig, orig_end, "-");
+    let added = render(mutant, mutant_end, "+");
+    let mut res = vec![format!(
+        "@@ -{},{} +{},{} @@ columns {}-{}",
+        line_no,
+        removed.len(),
+        line_no,
+        added.len(),
+        col,
+        col + (orig_end - start)
+    )];
+    res.extend(
+        removed
+            .iter()
+            .map(|l| ansi_term::Colour::Red.paint(l).to_string()),
+    );
+    res.extend(
+        added
+            .iter()
+            .map(|l| ansi_term::Colour::Green.paint(l).to_string()),
+    );
+    res.join("\n")
+}
+
+/// Write `contents` to `path` by first writing them to a temporary file
+/// next to it and then renaming it, so that `path` never holds a
+/// partially written file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Quote `arg` so that a POSIX shell reads it back as a single word.
+/// Arguments made up only of characters that are safe in a shell
+/// are returned as is.
+pub fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.to_string()
+    } else {
+        "'".to_string() + &arg.replace('\'', "'\\''") + "'"
+    }
+}
+
 type CommandOutput = (Option<i32>, Vec<u8>, Vec<u8>);
 
 /// Utility for invoking any command `cmd` with `args`.
@@ -33,6 +219,29 @@ pub fn invoke_command(cmd: &str, args: Vec<&str>) -> Result<CommandOutput, Box<d
     }
 }
 
+/// Lexically normalizes `path`, dropping `.` components and resolving `..`
+/// against the component before it, if there is one.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for c in path.components() {
+        match c {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if matches!(
+                    normal.components().next_back(),
+                    Some(std::path::Component::Normal(_))
+                ) {
+                    normal.pop();
+                } else {
+                    normal.push("..");
+                }
+            }
+            c => normal.push(c),
+        }
+    }
+    normal
+}
+
 /// Given a path, returns the Normal components of the path as a PathBuf.
 /// This includes the leaf of the path.
 pub fn get_path_normals(path: &str) -> Option<PathBuf> {
@@ -53,9 +262,224 @@ pub fn get_path_normals(path: &str) -> Option<PathBuf> {
     Some(root.to_path_buf())
 }
 
+/// A Solidity compiler version as `(major, minor, patch)`.
+pub type SolcVersion = (u64, u64, u64);
+
+/// The version of the Solidity compiler `solc`, as it reports it with
+/// `--version`, or `None` if it cannot be run or does not report one.
+pub fn solc_version(solc: &str) -> Option<SolcVersion> {
+    let out = Command::new(solc).arg("--version").output().ok()?;
+    parse_solc_version(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// The version in the output of `solc --version`, e.g.,
+/// `Version: 0.8.13+commit.abaa5c0e.Linux.g++`.
+fn parse_solc_version(output: &str) -> Option<SolcVersion> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r"Version:\s*(\d+)\.(\d+)\.(\d+)").unwrap());
+    let v = version.captures(output)?;
+    let part = |i: usize| v[i].parse().ok();
+    Some((part(1)?, part(2)?, part(3)?))
+}
+
+/// Checks whether the `pragma solidity` directives of `source` allow it to
+/// be compiled with `version` or a later compiler, i.e., none of them
+/// has an upper bound below `version`. A source without such pragmas may
+/// be compiled with any version.
+pub fn pragma_admits_at_least(source: &[u8], version: SolcVersion) -> bool {
+    let source = String::from_utf8_lossy(source);
+    static PRAGMA: OnceLock<Regex> = OnceLock::new();
+    static BOUND: OnceLock<Regex> = OnceLock::new();
+    let pragma = PRAGMA.get_or_init(|| Regex::new(r"pragma\s+solidity\s+([^;]*);").unwrap());
+    let bound = BOUND.get_or_init(|| {
+        Regex::new(r"(\^|~|>=|<=|>|<|=)?\s*(\d+)(?:\.(\d+))?(?:\.(\d+))?").unwrap()
+    });
+    let admits = pragma.captures_iter(&source).all(|p| {
+        p[1].split("||").any(|alternative| {
+            bound.captures_iter(alternative).all(|b| {
+                let part = |i: usize| b.get(i).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+                let v = (part(2), part(3), part(4));
+                match b.get(1).map_or("", |m| m.as_str()) {
+                    ">" | ">=" => true,
+                    "<" => version < v,
+                    "^" if v.0 > 0 => version < (v.0 + 1, 0, 0),
+                    "^" if v.1 > 0 => version < (0, v.1 + 1, 0),
+                    "^" => version < (0, 0, v.2 + 1),
+                    "~" => version < (v.0, v.1 + 1, 0),
+                    _ => version <= v,
+                }
+            })
+        })
+    });
+    admits
+}
+
+/// Checks whether `path` matches the glob `pattern`, in which `*` and `?`
+/// match within a single path component and `**` matches any number of
+/// components. A relative pattern may match any trailing part of `path`,
+/// so that `contracts/**` matches `/home/me/repo/contracts/A.sol`.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let mut re = String::from(if pattern.starts_with('/') {
+        "^"
+    } else {
+        "^(?:.*/)?"
+    });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).unwrap().is_match(path)
+}
+
+/// Solidity compilers to use for the files that match a glob, given as
+/// `glob=solc,glob=solc`, e.g.,
+/// `contracts/legacy/**=solc-0.7.6,contracts/v2/**=solc-0.8.21`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SolcMap(pub Vec<(String, String)>);
+
+impl SolcMap {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        s.split(',')
+            .map(|entry| match entry.trim().split_once('=') {
+                Some((glob, solc)) if !glob.is_empty() && !solc.is_empty() => {
+                    Ok((glob.to_string(), solc.to_string()))
+                }
+                _ => Err(format!(
+                    "invalid solc map entry `{}`, expected `glob=solc`",
+                    entry
+                )),
+            })
+            .collect::<Result<_, _>>()
+            .map(SolcMap)
+    }
+
+    /// The compiler for `path`, from the first glob that matches it.
+    pub fn solc_for(&self, path: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(glob, _)| glob_matches(glob, path))
+            .map(|(_, solc)| solc.as_str())
+    }
+}
+
+/// The fully qualified name of the contract `name` of the file `path`,
+/// in the `path:Name` form that foundry uses.
+pub fn qualified_contract(path: &str, name: &str) -> String {
+    format!("{}:{}", path, name)
+}
+
+/// Checks whether the contract `name` of the file `path` is selected by
+/// `filter`. A bare contract name selects the contracts of that name in
+/// any file, while a qualified one such as `src/A.sol:Token` also requires
+/// `path` to end with the given path.
+pub fn contract_matches(filter: &str, path: &str, name: &str) -> bool {
+    match filter.rsplit_once(':') {
+        Some((file, contract)) => {
+            contract == name
+                && normalize_path(Path::new(path)).ends_with(normalize_path(Path::new(file)))
+        }
+        None => filter == name,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_contract_matches() {
+        let (a, b) = ("/repo/src/A.sol", "/repo/test/mocks/B.sol");
+        // Both files define `Token`, which a bare name does not tell apart.
+        assert!(contract_matches("Token", a, "Token"));
+        assert!(contract_matches("Token", b, "Token"));
+        assert!(!contract_matches("Token", a, "Vault"));
+        assert!(contract_matches("src/A.sol:Token", a, "Token"));
+        assert!(!contract_matches("src/A.sol:Token", b, "Token"));
+        assert!(contract_matches("./test/mocks/B.sol:Token", b, "Token"));
+        assert!(!contract_matches("mocks/A.sol:Token", a, "Token"));
+        assert!(!contract_matches("src/A.sol:Vault", a, "Token"));
+        // Only whole path components match.
+        assert!(!contract_matches("c/A.sol:Token", a, "Token"));
+        assert_eq!(qualified_contract(a, "Token"), "/repo/src/A.sol:Token");
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "contracts/legacy/**",
+            "contracts/legacy/A.sol"
+        ));
+        assert!(glob_matches(
+            "contracts/legacy/**",
+            "contracts/legacy/lib/B.sol"
+        ));
+        assert!(glob_matches(
+            "contracts/legacy/**",
+            "/repo/contracts/legacy/A.sol"
+        ));
+        assert!(glob_matches("**/*.sol", "A.sol"));
+        assert!(glob_matches("contracts/*.sol", "./contracts/A.sol"));
+        assert!(glob_matches("contracts/?.sol", "contracts/A.sol"));
+        assert!(!glob_matches("contracts/*.sol", "contracts/lib/A.sol"));
+        assert!(!glob_matches("contracts/legacy/**", "contracts/v2/A.sol"));
+        assert!(!glob_matches("/contracts/**", "/repo/contracts/A.sol"));
+        assert!(!glob_matches("a.sol", "xa.sol"));
+    }
+
+    #[test]
+    fn test_solc_map() {
+        let map =
+            SolcMap::parse("contracts/legacy/**=solc-0.7.6, contracts/**=solc-0.8.21").unwrap();
+        assert_eq!(map.solc_for("contracts/legacy/A.sol"), Some("solc-0.7.6"));
+        assert_eq!(map.solc_for("contracts/v2/B.sol"), Some("solc-0.8.21"));
+        assert_eq!(map.solc_for("test/C.sol"), None);
+        assert!(SolcMap::parse("contracts/**").is_err());
+        assert!(SolcMap::parse("contracts/**=").is_err());
+    }
+
+    #[test]
+    fn test_parse_solc_version() {
+        assert_eq!(
+            parse_solc_version(
+                "solc, the solidity compiler commandline interface\nVersion: 0.8.13+commit.abaa5c0e.Linux.g++\n"
+            ),
+            Some((0, 8, 13))
+        );
+        assert_eq!(parse_solc_version("0.7.6"), None);
+        assert_eq!(solc_version("/nonexistent/solc"), None);
+    }
+
+    #[test]
+    fn test_pragma_admits_at_least() {
+        let admits = |pragma: &str| pragma_admits_at_least(pragma.as_bytes(), (0, 8, 0));
+        assert!(admits("contract C {}"));
+        assert!(admits("pragma solidity ^0.8.0;"));
+        assert!(admits(
+            "pragma solidity >0.7.0;\npragma experimental ABIEncoderV2;"
+        ));
+        assert!(admits("pragma solidity >=0.7.0 <0.9.0;"));
+        assert!(admits("pragma solidity ^0.7.6 || ^0.8.2;"));
+        assert!(admits("pragma solidity 0.8.17;"));
+        assert!(!admits("pragma solidity ^0.7.6;"));
+        assert!(!admits("pragma solidity ~0.6.12;"));
+        assert!(!admits("pragma solidity >=0.6.0 <0.8.0;"));
+        assert!(!admits("pragma solidity 0.7.6;"));
+        assert!(!admits("pragma solidity >=0.4.0; pragma solidity <=0.7.6;"));
+    }
     #[test]
     fn test_convert_path1() {
         let path1 = "../../../gambit/out/foo/TenPower.sol0.sol";
@@ -117,4 +541,124 @@ mod tests {
         let res = get_indent(s);
         assert_eq!(res, "   ")
     }
+
+    #[test]
+    fn test_normalize_path() {
+        let norm = |p: &str| normalize_path(Path::new(p)).to_str().unwrap().to_string();
+        assert_eq!(
+            norm("benchmarks/config-jsons/../10Power/TenPower.sol"),
+            "benchmarks/10Power/TenPower.sol"
+        );
+        assert_eq!(norm("./a/./b.sol"), "a/b.sol");
+        assert_eq!(norm("../a/b.sol"), "../a/b.sol");
+        assert_eq!(norm("a/../../b.sol"), "../b.sol");
+        assert_eq!(norm("/x/y/../b.sol"), "/x/b.sol");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("binary-op-mutaton", "binary-op-mutation"), 1);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("benchmarks/10Power/TenPower.sol"),
+            "benchmarks/10Power/TenPower.sol"
+        );
+        assert_eq!(shell_quote("--solc-remapping"), "--solc-remapping");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("my contracts/A.sol"), "'my contracts/A.sol'");
+        assert_eq!(shell_quote("it's.sol"), "'it'\\''s.sol'");
+        assert_eq!(shell_quote("\"quoted\".sol"), "'\"quoted\".sol'");
+        assert_eq!(shell_quote("$HOME/*.sol"), "'$HOME/*.sol'");
+    }
+
+    #[test]
+    fn test_shell_quote_roundtrip() {
+        for arg in [
+            "a b",
+            "it's",
+            "\"x\"",
+            "$(rm -rf /)",
+            "back\\slash",
+            "tab\there",
+        ] {
+            let (_, out, _) = invoke_command(
+                "sh",
+                vec!["-c", &("printf %s ".to_string() + &shell_quote(arg))],
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), arg);
+        }
+    }
+
+    #[test]
+    fn test_excerpt_short() {
+        assert_eq!(excerpt("   x = a + b;  ", 200), "x = a + b;");
+    }
+
+    #[test]
+    fn test_excerpt_long() {
+        let line = "a".repeat(1000);
+        let res = excerpt(&line, 200);
+        assert_eq!(res.len(), 203);
+        assert!(res.ends_with("..."));
+    }
+
+    #[test]
+    fn test_excerpt_multibyte() {
+        let res = excerpt("ж".repeat(10).as_str(), 3);
+        assert_eq!(res, "жжж...");
+    }
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range(b"x = a + b;", b"x = a + b;"), None);
+        assert_eq!(changed_range(b"x = a + b;", b"x = a - b;"), Some((6, 7, 7)));
+        assert_eq!(changed_range(b"x = a + b;", b"x = a;"), Some((5, 9, 5)));
+        // `Б` and `В` only differ in their second byte.
+        assert_eq!(
+            changed_range("s = \"Ба\";".as_bytes(), "s = \"Ва\";".as_bytes()),
+            Some((5, 7, 7))
+        );
+        // `а` and `в` only differ in their second byte, and so do `р` and `с`.
+        assert_eq!(
+            changed_range("ар".as_bytes(), "вс".as_bytes()),
+            Some((0, 4, 4))
+        );
+        assert_eq!(
+            changed_range("аб".as_bytes(), "вб".as_bytes()),
+            Some((0, 2, 2))
+        );
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let orig = b"line1\n  x = a + b;\nline3\n";
+        let mutant = b"line1\n  x = a - b;\nline3\n";
+        let diff = render_diff(orig, mutant, 200);
+        let lines: Vec<&str> = diff.lines().collect();
+        assert_eq!(lines[0], "@@ -2,1 +2,1 @@ columns 8-9");
+        assert!(lines[1].contains("-  x = a + b;"));
+        assert!(lines[2].contains("+  x = a - b;"));
+    }
+
+    #[test]
+    fn test_render_diff_long_line() {
+        let body = "uint256 x = 1; ".repeat(7000);
+        let orig = format!("contract C {{ {} x = a + b; {}}}", body, body);
+        let mutant = orig.replace("x = a + b;", "x = a - b;");
+        let diff = render_diff(orig.as_bytes(), mutant.as_bytes(), 200);
+        assert!(diff.len() < 1000);
+        assert!(diff.contains("a + b"));
+        assert!(diff.contains("a - b"));
+        assert!(diff.starts_with(&format!(
+            "@@ -1,1 +1,1 @@ columns {}",
+            orig.find("+ b").unwrap()
+        )));
+    }
 }
@@ -0,0 +1,135 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    changed_range, sha256_hex, verify_checksums, Manifest, MutantEntry, CHECKSUMS, MANIFEST,
+    SPARSE_MARKER,
+};
+
+/// Check that the output directory `outdir` is consistent with its manifest:
+/// ids are unique and sequential, every mutant differs from its original
+/// in exactly the bytes recorded for it and has the recorded hash, and
+/// every file is either a mutant of the manifest or something else Gambit
+/// writes there.
+/// `skip` lists further files and directories, relative to `outdir`, that
+/// are not mutants, e.g., the AST cache.
+/// Returns a description of every discrepancy.
+pub fn verify_output(outdir: &Path, skip: &[PathBuf]) -> io::Result<Vec<String>> {
+    let manifest = Manifest::read(outdir)?;
+    let mut discrepancies = vec![];
+
+    let mut ids = HashSet::new();
+    for (i, m) in manifest.mutants.iter().enumerate() {
+        if !ids.insert(m.id) {
+            discrepancies.push(format!("id {} is used by more than one mutant", m.id));
+        } else if m.id != i + 1 {
+            discrepancies.push(format!(
+                "ids are not sequential: expected {} but found {}",
+                i + 1,
+                m.id
+            ));
+        }
+    }
+
+    // Sparse mutants are listed in the checksums without being written.
+    let checksums = outdir.join(CHECKSUMS).is_file() && !outdir.join(SPARSE_MARKER).exists();
+    if checksums {
+        discrepancies.extend(verify_checksums(outdir)?);
+    }
+    for m in &manifest.mutants {
+        let path = outdir.join(&m.path);
+        if !path.is_file() {
+            // The checksums already report the missing mutants.
+            if m.edit.is_none() && !checksums {
+                discrepancies.push(format!("{}: missing", m.path));
+            }
+            continue;
+        }
+        let mutant = fs::read(&path)?;
+        if let (false, Some(expected)) = (checksums, &m.sha256) {
+            let actual = sha256_hex(&mutant);
+            if actual != *expected {
+                discrepancies.push(format!(
+                    "{}: expected {} but found {}",
+                    m.path, expected, actual
+                ));
+            }
+        }
+        if let Some(d) = span_discrepancy(m, &mutant)? {
+            discrepancies.push(format!("{}: {}", m.path, d));
+        }
+    }
+
+    let mutants: HashSet<PathBuf> = manifest
+        .mutants
+        .iter()
+        .map(|m| m.path.clone().into())
+        .collect();
+    let mut stray = BTreeSet::new();
+    collect_files(outdir, Path::new(""), skip, &mut stray)?;
+    for file in stray.iter().filter(|f| !mutants.contains(*f)) {
+        discrepancies.push(format!(
+            "{}: not a mutant in {}",
+            file.to_string_lossy(),
+            MANIFEST
+        ));
+    }
+    Ok(discrepancies)
+}
+
+/// Describes how `mutant`, the contents of the mutant file of `m`, differs
+/// from its original other than in the range of bytes recorded for it.
+fn span_discrepancy(m: &MutantEntry, mutant: &[u8]) -> io::Result<Option<String>> {
+    let recorded = match (&m.edit, m.span) {
+        (Some(edit), _) => Some((edit.start, edit.end)),
+        (None, span) => span,
+    };
+    let original = match fs::read(&m.original) {
+        Ok(original) => original,
+        Err(e) => {
+            return Ok(Some(format!(
+                "cannot read the original {}: {}",
+                m.original, e
+            )))
+        }
+    };
+    Ok(match (changed_range(&original, mutant), recorded) {
+        (None, _) => Some(format!("identical to {}", m.original)),
+        (Some((start, end, _)), Some(span)) if (start, end) != span => Some(format!(
+            "changes bytes {}..{} of {} but the manifest records {}..{}",
+            start, end, m.original, span.0, span.1
+        )),
+        _ => None,
+    })
+}
+
+/// Collect the files under `dir`, relative to the output directory, except
+/// for those in `skip` and those Gambit writes next to the manifest.
+fn collect_files(
+    outdir: &Path,
+    dir: &Path,
+    skip: &[PathBuf],
+    files: &mut BTreeSet<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(outdir.join(dir))? {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        let top_level = dir.as_os_str().is_empty();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if skip.contains(&path)
+            || top_level
+                && (name == MANIFEST || name == SPARSE_MARKER || name.starts_with(CHECKSUMS))
+        {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            collect_files(outdir, &path, skip, files)?;
+        } else {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
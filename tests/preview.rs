@@ -0,0 +1,53 @@
+use std::{fs, os::unix::fs::PermissionsExt, process::Command};
+
+/// `--preview` prints candidate mutants of every requested mutation to
+/// stdout without touching the output directory.
+#[test]
+fn test_preview() {
+    let root = std::env::temp_dir().join("gambit_test_preview");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let sol = root.join("C.sol");
+    fs::copy("benchmarks/MultipleContracts/C.sol", &sol).unwrap();
+    // A stand-in for solc that emits the checked-in AST of `C.sol`.
+    let ast = fs::canonicalize("benchmarks/MultipleContracts/C.json").unwrap();
+    let solc = root.join("solc");
+    fs::write(
+        &solc,
+        format!(
+            "#!/bin/sh\nmkdir -p \"$4\" && cp '{}' \"$4/$(basename \"$2\")_json.ast\"\n",
+            ast.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&solc, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let out = root.join("out");
+    let mutations = [
+        "BinaryOpMutation",
+        "AssignmentMutation",
+        "DeleteExpressionMutation",
+    ];
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_gambit"));
+    cmd.args(["mutate", "--filename", sol.to_str().unwrap()])
+        .args(["--solc", solc.to_str().unwrap()])
+        .args(["--outdir", out.to_str().unwrap()])
+        .args(["--preview", "2"]);
+    for m in mutations {
+        cmd.args(["--mutations", m]);
+    }
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for m in mutations {
+        let header = format!("=== {} on {}: ", m, sol.to_str().unwrap());
+        assert!(stdout.contains(&header), "no header for {}", m);
+        assert!(
+            stdout.contains(&format!("--- preview 1 of {} (unvalidated) ---", m)),
+            "no preview for {}",
+            m
+        );
+    }
+    assert!(!out.exists());
+    fs::remove_dir_all(&root).unwrap();
+}